@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use super::dnd5eapi::{Dnd5eApiRequester, PerformsRequest};
+
+/// Where the answers of the dnd5eapi are kept, e.g. `~/.cache/dnd-simp/dnd5eapi`.
+pub fn default_cache_folder() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dnd-simp").join("dnd5eapi"))
+}
+
+/// The dnd5eapi behind the cache in `folder`, or in the default cache folder.
+pub fn cached_dnd5eapi(folder: Option<PathBuf>) -> anyhow::Result<CachedRequester<Dnd5eApiRequester>> {
+    let Some(folder) = folder.or_else(default_cache_folder) else {
+        anyhow::bail!("There is no cache directory for the dnd5eapi");
+    };
+
+    Ok(CachedRequester::new(folder, Dnd5eApiRequester {}))
+}
+
+/// Answers requests from a folder shaped like `assets/dnd5eapi`, where `/api/magic-items/cape`
+/// is `magic-items/cape.json`. Everything not in it yet is requested from `fallback` and kept,
+/// so once everything was asked for, no network is needed anymore.
+pub struct CachedRequester<R: PerformsRequest> {
+    folder: PathBuf,
+    fallback: R,
+}
+
+impl<R: PerformsRequest> CachedRequester<R> {
+    pub fn new(folder: impl Into<PathBuf>, fallback: R) -> Self {
+        Self {
+            folder: folder.into(),
+            fallback,
+        }
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    /// The file of a sub url, `None` for urls that do not look like the API.
+    fn path_of(&self, sub_url: &str) -> Option<PathBuf> {
+        let resource = sub_url.trim_matches('/').strip_prefix("api/")?;
+        let is_safe = |segment: &str| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        };
+        if !resource.split('/').all(is_safe) {
+            return None;
+        }
+
+        Some(self.folder.join(format!("{resource}.json")))
+    }
+
+    pub fn is_cached(&self, sub_url: &str) -> bool {
+        self.path_of(sub_url).is_some_and(|path| path.is_file())
+    }
+
+    /// Requests the sub url from the fallback even if it is cached, and keeps the new answer.
+    pub fn refresh(&self, sub_url: &str) -> anyhow::Result<String> {
+        let response = self
+            .fallback
+            .request_from_sub_url(sub_url)
+            .with_context(|| format!("Could not download {sub_url}"))?;
+
+        if let Some(path) = self.path_of(sub_url) {
+            if let Err(e) = write_atomically(&path, &response) {
+                log::error!("Could not cache {sub_url} in {path:?}: {e:?}");
+            }
+        }
+
+        Ok(response)
+    }
+}
+
+fn write_atomically(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+impl<R: PerformsRequest> PerformsRequest for CachedRequester<R> {
+    fn request_from_sub_url(&self, sub_url: &str) -> anyhow::Result<String> {
+        if let Some(path) = self.path_of(sub_url) {
+            match std::fs::read_to_string(&path) {
+                Ok(cached) => return Ok(cached),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log::error!("Could not read the cached {sub_url} from {path:?}: {e:?}"),
+            }
+        }
+
+        self.refresh(sub_url).with_context(|| {
+            format!(
+                "{sub_url} is not in the cache at {} yet, run `dnd-simp sync` while there is a network",
+                self.folder.display()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::apis::dnd5eapi::MockPerformsRequest;
+
+    #[test]
+    fn cached_answers_need_no_network() -> anyhow::Result<()> {
+        let mut fallback = MockPerformsRequest::new();
+        fallback.expect_request_from_sub_url().times(0);
+
+        let requester = CachedRequester::new("assets/dnd5eapi", fallback);
+        let response = requester.request_from_sub_url("/api/magic-items/apparatus-of-the-crab")?;
+
+        assert!(response.contains("Apparatus of the Crab"));
+        Ok(())
+    }
+
+    #[test]
+    fn downloaded_answers_are_kept() -> anyhow::Result<()> {
+        let folder = std::env::temp_dir().join(format!("dnd-simp-dnd5eapi-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+
+        let mut fallback = MockPerformsRequest::new();
+        fallback
+            .expect_request_from_sub_url()
+            .with(eq("/api/magic-items/bag-of-holding"))
+            .times(1)
+            .returning(|_| Ok("{\"name\": \"Bag of Holding\"}".into()));
+
+        let requester = CachedRequester::new(&folder, fallback);
+        let downloaded = requester.request_from_sub_url("/api/magic-items/bag-of-holding")?;
+        let cached = requester.request_from_sub_url("/api/magic-items/bag-of-holding")?;
+
+        assert_eq!(downloaded, cached);
+        assert!(folder.join("magic-items").join("bag-of-holding.json").is_file());
+
+        std::fs::remove_dir_all(&folder)?;
+        Ok(())
+    }
+
+    #[test]
+    fn odd_urls_are_not_cached() {
+        let requester = CachedRequester::new("assets/dnd5eapi", MockPerformsRequest::new());
+
+        assert_eq!(
+            requester.path_of("/api/magic-items"),
+            Some(PathBuf::from("assets/dnd5eapi/magic-items.json"))
+        );
+        assert_eq!(requester.path_of("/api/../../etc/passwd"), None);
+        assert_eq!(requester.path_of("/images/cape.png"), None);
+    }
+}
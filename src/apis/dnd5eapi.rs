@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::fmt::Write as _;
 
@@ -9,17 +10,19 @@ use anyhow::anyhow;
 use mockall::predicate::*;
 use mockall::*;
 
-use crate::data::item::{ItemCategory, ItemType, Rarity};
+use crate::data::item::{ApiReference, ItemCategory, ItemType, Rarity};
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+use super::cache::CachedRequester;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 struct Dnd5eApiItemVariant {
     index: String,
     name: String,
     url: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
-struct Dnd5eApiItem {
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Dnd5eApiItem {
     index: String,
     name: String,
     equipment_category: Dnd5eApiEquipmentCategory,
@@ -30,12 +33,12 @@ struct Dnd5eApiItem {
     url: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 struct Dnd5eApiItemRarity {
     name: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 struct Dnd5eApiEquipmentCategory {
     index: String,
     name: String,
@@ -50,18 +53,19 @@ impl Dnd5eApiItem {
 }
 
 const DND5EAPI_BASEURL: &str = "https://www.dnd5eapi.co";
+pub const MAGIC_ITEMS_SUB_URL: &str = "/api/magic-items";
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
-struct Dnd5eApiMagicItemList {
+pub struct Dnd5eApiMagicItemList {
     count: u64,
     results: Vec<Dnd5eApiMagicItemListing>,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
-struct Dnd5eApiMagicItemListing {
-    index: String,
-    name: String,
-    url: String,
+pub struct Dnd5eApiMagicItemListing {
+    pub index: String,
+    pub name: String,
+    pub url: String,
 }
 
 #[derive(Debug)]
@@ -70,7 +74,7 @@ pub enum Dnd5eApiError {
 }
 
 #[automock]
-trait PerformsRequest {
+pub trait PerformsRequest {
     fn request_from_sub_url(&self, sub_url: &str) -> anyhow::Result<String>;
 }
 
@@ -88,6 +92,10 @@ impl Dnd5eApiMagicItemList {
         Ok(list)
     }
 
+    pub fn download(requester: &dyn PerformsRequest) -> anyhow::Result<Self> {
+        Self::from_json(&requester.request_from_sub_url(MAGIC_ITEMS_SUB_URL)?)
+    }
+
     pub fn search_for_url(&self, item_regex: &str) -> anyhow::Result<String> {
         let re = Regex::new(item_regex)?;
 
@@ -100,6 +108,21 @@ impl Dnd5eApiMagicItemList {
         Err(anyhow!(Dnd5eApiError::ItemNotFound))
     }
 
+    pub fn listing(&self, index: &str) -> Option<&Dnd5eApiMagicItemListing> {
+        self.results.iter().find(|item| item.index == index)
+    }
+
+    /// Every listed item whose name or index matches, in the order of the list.
+    pub fn search_all(&self, item_regex: &str) -> anyhow::Result<Vec<&Dnd5eApiMagicItemListing>> {
+        let re = Regex::new(item_regex)?;
+
+        Ok(self
+            .results
+            .iter()
+            .filter(|item| re.is_match(&item.name) || re.is_match(&item.index))
+            .collect())
+    }
+
     pub fn download_item(
         &self,
         sub_url: &str,
@@ -121,29 +144,125 @@ impl Dnd5eApiMagicItemList {
     }
 }
 
-pub fn dnd5eapi_to_itemtype(item: &Dnd5eApiItem) -> anyhow::Result<ItemType> {
-    let rarity = match item.rarity.name.as_str() {
-        "Common" => Rarity::Common,
-        "Rare" => Rarity::Rare,
-        "Very Rare" => Rarity::VeryRare,
-        "Legendary" => Rarity::Legendary,
-        "Artifact" => Rarity::Artifact,
-        _ => anyhow::bail!("Invalid rarity string encountered."),
-    };
+/// Rarity of items whose variants differ in rarity, like `Armor, +1, +2, or +3`.
+const VARYING_RARITY: &str = "Varies";
+
+fn category_of(category: &Dnd5eApiEquipmentCategory) -> ItemCategory {
+    match category.index.as_str() {
+        "wondrous-items" => ItemCategory::WondrousItem,
+        "simple-weapons" => ItemCategory::SimpleWeapon,
+        "wand" => ItemCategory::Wand,
+        "armor" => ItemCategory::Armor,
+        "weapon" => ItemCategory::Weapon,
+        "potion" => ItemCategory::Potion,
+        "ring" => ItemCategory::Ring,
+        "rod" => ItemCategory::Rod,
+        "scroll" => ItemCategory::Scroll,
+        "staff" => ItemCategory::Staff,
+        _ => ItemCategory::from_name(&category.name).unwrap_or(ItemCategory::WondrousItem),
+    }
+}
 
-    let category = match item.equipment_category.name.as_str() {
-        "Wondrous Item" => ItemCategory::WondrousItem,
-        "Wand" => ItemCategory::Wand,
-        "Simple Weapons" => ItemCategory::SimpleWeapon,
-        _ => ItemCategory::WondrousItem,
+/// The SRD text is English and becomes the base text of the item, translations come from the
+/// campaign's YAML, see [`crate::registry::ItemRegistry::import`]. The API index becomes the
+/// identifier, and the item remembers where it came from.
+///
+/// `variants` are only needed for an item whose rarity varies: it gets the lowest rarity of
+/// them, the one it is found with first.
+pub fn dnd5eapi_to_itemtype(item: &Dnd5eApiItem, variants: &[Dnd5eApiItem]) -> anyhow::Result<ItemType> {
+    let rarity = if item.rarity.name == VARYING_RARITY {
+        variants
+            .iter()
+            .filter_map(|variant| Rarity::from_name(&variant.rarity.name))
+            .min()
+            .ok_or_else(|| anyhow!("The rarity of {} varies, but none of its variants has one", item.index))?
+    } else {
+        Rarity::from_name(&item.rarity.name)
+            .ok_or_else(|| anyhow!("Invalid rarity string encountered: {}", item.rarity.name))?
     };
 
-    Ok(ItemType::new(
-        item.name.clone(),
-        rarity,
-        category,
-        item.desc.join("\n"),
-    ))
+    Ok(ItemType {
+        identifier: item.index.clone(),
+        source: Some(ApiReference {
+            index: item.index.clone(),
+            url: item.url.clone(),
+            variants: item.variants.iter().map(|variant| variant.url.clone()).collect(),
+            variant: item.variant,
+        }),
+        ..ItemType::new(
+            item.name.clone(),
+            rarity,
+            category_of(&item.equipment_category),
+            item.desc.join("\n"),
+        )
+    })
+}
+
+/// What [`download_item_types`] got.
+#[derive(Debug, Default)]
+pub struct Dnd5eApiDownload {
+    pub items: Vec<ItemType>,
+    /// Sub url and error of every item that could not be downloaded or converted.
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+/// Downloads the item once, even if it is needed as an item and as a variant.
+fn fetch_item(
+    sub_url: &str,
+    requester: &dyn PerformsRequest,
+    downloaded: &mut HashMap<String, Dnd5eApiItem>,
+) -> anyhow::Result<Dnd5eApiItem> {
+    if let Some(item) = downloaded.get(sub_url) {
+        return Ok(item.clone());
+    }
+
+    let item = Dnd5eApiItem::from_json(&requester.request_from_sub_url(sub_url)?)?;
+    downloaded.insert(sub_url.to_string(), item.clone());
+    Ok(item)
+}
+
+/// Downloads and converts the items at the sub urls, and with `follow_variants` every variant of
+/// them, e.g. each `+1`, `+2` and `+3` armor. Every item comes once. The variants of an item whose
+/// rarity varies are downloaded in any case, to find its rarity.
+pub fn download_item_types(sub_urls: &[String], follow_variants: bool, requester: &dyn PerformsRequest) -> Dnd5eApiDownload {
+    let mut download = Dnd5eApiDownload::default();
+    let mut queue: VecDeque<String> = sub_urls.iter().cloned().collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut downloaded: HashMap<String, Dnd5eApiItem> = HashMap::new();
+
+    while let Some(sub_url) = queue.pop_front() {
+        if !seen.insert(sub_url.clone()) {
+            continue;
+        }
+
+        let item = match fetch_item(&sub_url, requester, &mut downloaded) {
+            Ok(item) => item,
+            Err(e) => {
+                download.failed.push((sub_url, e));
+                continue;
+            }
+        };
+
+        if follow_variants {
+            queue.extend(item.variants.iter().map(|variant| variant.url.clone()));
+        }
+
+        let variants: Vec<Dnd5eApiItem> = if item.rarity.name == VARYING_RARITY {
+            item.variants
+                .iter()
+                .filter_map(|variant| fetch_item(&variant.url, requester, &mut downloaded).ok())
+                .collect()
+        } else {
+            vec![]
+        };
+
+        match dnd5eapi_to_itemtype(&item, &variants) {
+            Ok(item_type) => download.items.push(item_type),
+            Err(e) => download.failed.push((sub_url, e)),
+        }
+    }
+
+    download
 }
 
 pub struct Dnd5eApiRequester {}
@@ -151,15 +270,61 @@ pub struct Dnd5eApiRequester {}
 impl PerformsRequest for Dnd5eApiRequester {
     fn request_from_sub_url(&self, sub_url: &str) -> anyhow::Result<String> {
         let mut url = String::new();
-        write!(url, "{DND5EAPI_BASEURL}/{sub_url}")?;
+        write!(url, "{DND5EAPI_BASEURL}/{}", sub_url.trim_start_matches('/'))?;
 
-        Ok(reqwest::blocking::get(url)?.text()?)
+        Ok(reqwest::blocking::get(url)?.error_for_status()?.text()?)
     }
 }
 
+/// What [`sync_magic_items`] did.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub total: usize,
+    pub downloaded: usize,
+    pub already_cached: usize,
+    /// Index and error of every item that could not be downloaded.
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+/// Puts the list of magic items and every item on it into the cache, so the app works without
+/// a network afterwards. The list is always downloaded again to find new items, the items only
+/// if they are not cached yet or `refresh` is set. `progress` hears of every item before it is downloaded.
+pub fn sync_magic_items<R: PerformsRequest>(
+    requester: &CachedRequester<R>,
+    refresh: bool,
+    mut progress: impl FnMut(usize, usize, &str),
+) -> anyhow::Result<SyncReport> {
+    let list = Dnd5eApiMagicItemList::from_json(&requester.refresh(MAGIC_ITEMS_SUB_URL)?)?;
+    let mut report = SyncReport {
+        total: list.results.len(),
+        ..SyncReport::default()
+    };
+
+    for (idx, listing) in list.results.iter().enumerate() {
+        if !refresh && requester.is_cached(&listing.url) {
+            report.already_cached += 1;
+            continue;
+        }
+
+        progress(idx + 1, report.total, &listing.name);
+        match requester.refresh(&listing.url) {
+            Ok(_) => report.downloaded += 1,
+            Err(e) => report.failed.push((listing.index.clone(), e)),
+        }
+    }
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{dnd5eapi_to_itemtype, Dnd5eApiItem, Dnd5eApiMagicItemList, MockPerformsRequest};
+    use super::{
+        dnd5eapi_to_itemtype, download_item_types, sync_magic_items, Dnd5eApiItem, Dnd5eApiMagicItemList,
+        MockPerformsRequest,
+    };
+    use crate::data::item::{ItemCategory, Rarity};
+    use strum::IntoEnumIterator;
+    use crate::apis::cache::CachedRequester;
     use mockall::predicate::eq;
 
     #[test]
@@ -229,10 +394,133 @@ mod tests {
             std::fs::read_to_string("assets/dnd5eapi/magic-items/apparatus-of-the-crab.json")?;
         let item = Dnd5eApiItem::from_json(item_contents.as_str())?;
 
-        let converted_type = dnd5eapi_to_itemtype(&item)?;
+        let converted_type = dnd5eapi_to_itemtype(&item, &[])?;
 
         assert_eq!(converted_type.name, item.name);
+        assert_eq!(converted_type.identifier, "apparatus-of-the-crab");
+        assert_eq!(converted_type.category, ItemCategory::WondrousItem);
+        assert!(converted_type.translations.is_empty());
+
+        let source = converted_type.source.expect("the API reference is kept");
+        assert_eq!(source.url, "/api/magic-items/apparatus-of-the-crab");
+        assert!(!source.variant);
+
+        Ok(())
+    }
+
+    #[test]
+    fn every_rarity_and_the_category_index_are_converted() -> anyhow::Result<()> {
+        for rarity in Rarity::iter() {
+            let item = Dnd5eApiItem::from_json(&item_json("armor-1", &rarity.to_string(), &[]))?;
+            let converted_type = dnd5eapi_to_itemtype(&item, &[])?;
+
+            assert_eq!(converted_type.rarity, rarity);
+            assert_eq!(converted_type.category, ItemCategory::Armor);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn varying_rarities_are_resolved_through_the_variants() -> anyhow::Result<()> {
+        let item = Dnd5eApiItem::from_json(&item_json("armor", "Varies", &["armor-1", "armor-3"]))?;
+        let variants = [
+            Dnd5eApiItem::from_json(&item_json("armor-3", "Legendary", &[]))?,
+            Dnd5eApiItem::from_json(&item_json("armor-1", "Rare", &[]))?,
+        ];
+
+        assert!(dnd5eapi_to_itemtype(&item, &[]).is_err());
+
+        let converted_type = dnd5eapi_to_itemtype(&item, &variants)?;
+        assert_eq!(converted_type.rarity, Rarity::Rare);
+        assert_eq!(
+            converted_type.source.map(|source| source.variants),
+            Some(vec!["/api/magic-items/armor-1".to_string(), "/api/magic-items/armor-3".to_string()])
+        );
 
         Ok(())
     }
+
+    #[test]
+    fn sync_downloads_what_is_not_cached() -> anyhow::Result<()> {
+        let folder = std::env::temp_dir().join(format!("dnd-simp-sync-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(folder.join("magic-items"))?;
+        std::fs::write(folder.join("magic-items").join("cape.json"), "{}")?;
+
+        let list = r#"{"count": 2, "results": [
+            {"index": "cape", "name": "Cape", "url": "/api/magic-items/cape"},
+            {"index": "ring", "name": "Ring", "url": "/api/magic-items/ring"}
+        ]}"#;
+        let mut fallback = MockPerformsRequest::new();
+        fallback
+            .expect_request_from_sub_url()
+            .with(eq("/api/magic-items"))
+            .times(1)
+            .returning(move |_| Ok(list.to_string()));
+        fallback
+            .expect_request_from_sub_url()
+            .with(eq("/api/magic-items/ring"))
+            .times(1)
+            .returning(|_| Ok("{}".into()));
+
+        let requester = CachedRequester::new(&folder, fallback);
+        let mut announced = vec![];
+        let report = sync_magic_items(&requester, false, |_, _, name| announced.push(name.to_string()))?;
+
+        assert_eq!((report.total, report.downloaded, report.already_cached), (2, 1, 1));
+        assert_eq!(announced, vec!["Ring"]);
+        assert!(folder.join("magic-items.json").is_file());
+
+        std::fs::remove_dir_all(&folder)?;
+        Ok(())
+    }
+
+    fn item_json(index: &str, rarity: &str, variants: &[&str]) -> String {
+        let variants: Vec<String> = variants
+            .iter()
+            .map(|variant| format!(r#"{{"index": "{variant}", "name": "{variant}", "url": "/api/magic-items/{variant}"}}"#))
+            .collect();
+
+        format!(
+            r#"{{"index": "{index}", "name": "{index}", "url": "/api/magic-items/{index}", "desc": [],
+                "equipment_category": {{"index": "armor", "name": "Armor", "url": "/api/equipment-categories/armor"}},
+                "rarity": {{"name": "{rarity}"}}, "variants": [{}], "variant": {}}}"#,
+            variants.join(","),
+            variants.is_empty()
+        )
+    }
+
+    #[test]
+    fn all_matches_are_found() -> anyhow::Result<()> {
+        let contents = std::fs::read_to_string("assets/dnd5eapi-itemlist.json")?;
+        let l = Dnd5eApiMagicItemList::from_json(&contents)?;
+        let indexes: Vec<&str> = l.search_all("^armor(-[0-9])?$")?.iter().map(|item| item.index.as_str()).collect();
+
+        assert_eq!(indexes, vec!["armor", "armor-1", "armor-2", "armor-3"]);
+        Ok(())
+    }
+
+    #[test]
+    fn variants_are_downloaded_once_with_their_index() {
+        let mut requester = MockPerformsRequest::new();
+        // armor-2 is asked for again after it failed while finding the rarity of armor.
+        requester
+            .expect_request_from_sub_url()
+            .times(4)
+            .returning(|sub_url| match sub_url {
+                "/api/magic-items/armor" => Ok(item_json("armor", "Varies", &["armor-1", "armor-2"])),
+                "/api/magic-items/armor-1" => Ok(item_json("armor-1", "Rare", &[])),
+                _ => anyhow::bail!("offline"),
+            });
+
+        let urls = vec!["/api/magic-items/armor".to_string(), "/api/magic-items/armor-1".to_string()];
+        let download = download_item_types(&urls, true, &requester);
+
+        let identifiers: Vec<&str> = download.items.iter().map(|item| item.identifier.as_str()).collect();
+        let failed: Vec<&str> = download.failed.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(identifiers, vec!["armor", "armor-1"]);
+        assert_eq!(failed, vec!["/api/magic-items/armor-2"]);
+        assert_eq!(download.items[0].rarity, Rarity::Rare);
+    }
 }
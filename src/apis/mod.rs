@@ -1 +1,2 @@
-mod dnd5eapi;
+pub mod cache;
+pub mod dnd5eapi;
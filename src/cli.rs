@@ -0,0 +1,359 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use clap::{Args, Subcommand};
+
+use crate::{
+    apis::{
+        cache::cached_dnd5eapi,
+        dnd5eapi::{download_item_types, sync_magic_items, Dnd5eApiMagicItemList},
+    },
+    config::settings::{HandoutFormat, Settings},
+    data::{
+        campaign::{load_campaign_folder, Campaign},
+        gold::roll_gold,
+    },
+    export::{load_template, write_handout, Handout},
+    import::{read_rows, write_items, ColumnMapping, ItemSheet, SheetFormat},
+    state::campaign::{CampaignCommand, CampaignStore},
+    ui::translator::{I18nPhrase, I18ner, Translator},
+};
+
+/// Things to do without opening the user interface.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Write a shop inventory, a new offer or a wish list into a handout for the players
+    Export(ExportArgs),
+    /// Add the items of a CSV or JSON sheet, e.g. homebrew items kept in a spreadsheet
+    Import(ImportArgs),
+    /// Download every SRD magic item of the dnd5eapi, so no network is needed at the table
+    Sync(SyncArgs),
+    /// Find SRD magic items of the dnd5eapi and add them to the campaign
+    Srd(SrdArgs),
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    handout: HandoutKind,
+
+    /// Defaults to the handout format of the settings
+    #[arg(long, value_enum, global = true)]
+    format: Option<HandoutFormat>,
+
+    /// Template to fill instead of the one in the config directory or the built-in one
+    #[arg(long, global = true)]
+    template: Option<PathBuf>,
+
+    /// Leave out the prices, also done if the settings say so
+    #[arg(long, global = true)]
+    hide_prices: bool,
+
+    /// File to write, `-` for standard output. Defaults to the handouts folder of the campaign
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum HandoutKind {
+    /// Everything a shop has in stock
+    Inventory { shop: String },
+    /// A new offer of a shop, with rolled prices
+    Offer { shop: String },
+    /// The wish list of a character
+    Wishes { character: String },
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    sheet: PathBuf,
+
+    /// Defaults to JSON for `.json` files and CSV for everything else
+    #[arg(long, value_enum)]
+    format: Option<SheetFormat>,
+
+    /// YAML file naming the column of every item field, by default the columns are named like the fields
+    #[arg(long)]
+    mapping: Option<PathBuf>,
+
+    /// Overwrite items the campaign already has, keeping their translations
+    #[arg(long)]
+    replace: bool,
+
+    /// Write one YAML file per item into this folder instead of saving them into the campaign
+    #[arg(long)]
+    into: Option<PathBuf>,
+
+    /// Only report what would be imported
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct SyncArgs {
+    /// Download the items that are cached already again
+    #[arg(long)]
+    refresh: bool,
+
+    /// Defaults to dnd-simp/dnd5eapi in the cache directory of the user
+    #[arg(long)]
+    cache: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct SrdArgs {
+    #[command(subcommand)]
+    command: SrdCommand,
+
+    /// Defaults to dnd-simp/dnd5eapi in the cache directory of the user
+    #[arg(long, global = true)]
+    cache: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum SrdCommand {
+    /// List every item whose name or index matches a regular expression
+    Search { pattern: String },
+    /// Add items by their index, e.g. `cape-of-the-mountebank`, and every variant of them
+    Import {
+        indexes: Vec<String>,
+
+        /// Also add every item whose name or index matches this regular expression
+        #[arg(long)]
+        matching: Option<String>,
+
+        /// Leave out the variants, e.g. `armor-1` of `armor`
+        #[arg(long)]
+        no_variants: bool,
+
+        /// Only report what would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Runs the command. Commands on a campaign see the edits in its journal that are not saved yet.
+pub fn run(command: Command, campaign_folder: Option<&Path>, settings: &Settings) -> anyhow::Result<()> {
+    match command {
+        Command::Export(args) => {
+            let (store, folder) = open_campaign(campaign_folder)?;
+            export(args, store.campaign(), folder, settings)
+        }
+        Command::Import(args) => {
+            let (mut store, folder) = open_campaign(campaign_folder)?;
+            import(args, &mut store, folder, settings)
+        }
+        Command::Sync(args) => sync(args, settings),
+        Command::Srd(args) => srd(args, campaign_folder, settings),
+    }
+}
+
+fn open_campaign(campaign_folder: Option<&Path>) -> anyhow::Result<(CampaignStore, &Path)> {
+    let Some(folder) = campaign_folder else {
+        bail!("There is no campaign, pass its folder with --campaign-file or set a default campaign");
+    };
+
+    let campaign = load_campaign_folder(folder).with_context(|| format!("Could not load campaign {}", folder.display()))?;
+    Ok((CampaignStore::open(campaign.into_campaign(), folder), folder))
+}
+
+/// Finds a shop or character by name, ignoring case.
+fn find_named<'c, T>(kind: &str, name: &str, candidates: &'c [T], name_of: impl Fn(&T) -> &str) -> anyhow::Result<&'c T> {
+    if let Some(found) = candidates.iter().find(|candidate| name_of(candidate).eq_ignore_ascii_case(name)) {
+        return Ok(found);
+    }
+
+    let known: Vec<&str> = candidates.iter().map(name_of).collect();
+    bail!("There is no {kind} named '{name}'. Known are: {}", known.join(", "))
+}
+
+fn export(args: ExportArgs, campaign: &Campaign, campaign_folder: &Path, settings: &Settings) -> anyhow::Result<()> {
+    let i18n = Translator::new(settings);
+
+    let handout = match &args.handout {
+        HandoutKind::Inventory { shop } => {
+            let shop = find_named("shop", shop, &campaign.shops, |shop| &shop.name)?;
+            Handout::inventory(campaign, shop, settings.pricing, &i18n)
+        }
+        HandoutKind::Offer { shop } => {
+            let shop = find_named("shop", shop, &campaign.shops, |shop| &shop.name)?;
+            let offer: Vec<_> = shop
+                .produce_offer(settings.offer_size)
+                .into_iter()
+                .filter_map(|stocked| campaign.registry.get(&stocked.identifier))
+                .map(|item_type| (item_type.clone(), roll_gold(&settings.pricing.cost_expression(item_type))))
+                .collect();
+            let title = i18n.i18n_with(
+                I18nPhrase::OfferTitle,
+                &[("shop", &shop.name), ("date", &campaign.formatted_date())],
+            );
+
+            Handout::offer(title, &offer, &i18n)
+        }
+        HandoutKind::Wishes { character } => {
+            let character = find_named("character", character, &campaign.characters, |character| &character.name)?;
+            Handout::wish_list(campaign, character, &i18n)
+        }
+    };
+
+    let format = args.format.unwrap_or(settings.handout_format);
+    let template = load_template(format, args.template.as_deref())?;
+    let rendered = handout.render(format, &template, args.hide_prices || settings.hide_prices_in_handouts, &i18n);
+
+    match args.output {
+        Some(path) if path.as_os_str() == "-" => print!("{rendered}"),
+        Some(path) => {
+            std::fs::write(&path, rendered).with_context(|| format!("Could not write {}", path.display()))?;
+            println!("{}", i18n.i18n_with(I18nPhrase::ExportedTo, &[("path", &path.display().to_string())]));
+        }
+        None => {
+            let path = write_handout(campaign_folder, &handout, format, &rendered)?;
+            println!("{}", i18n.i18n_with(I18nPhrase::ExportedTo, &[("path", &path.display().to_string())]));
+        }
+    }
+
+    Ok(())
+}
+
+fn import(args: ImportArgs, store: &mut CampaignStore, campaign_folder: &Path, settings: &Settings) -> anyhow::Result<()> {
+    let i18n = Translator::new(settings);
+
+    let mapping = match &args.mapping {
+        Some(path) => ColumnMapping::load(path)?,
+        None => ColumnMapping::default(),
+    };
+    let source = std::fs::read_to_string(&args.sheet).with_context(|| format!("Could not read {}", args.sheet.display()))?;
+    let format = args.format.unwrap_or_else(|| SheetFormat::of_path(&args.sheet));
+    let rows = read_rows(&source, format).with_context(|| format!("Could not read {}", args.sheet.display()))?;
+
+    let registry = &store.campaign().registry;
+    let sheet = ItemSheet::read(&rows, &mapping, registry);
+    let items = sheet.merged(registry, args.replace);
+
+    for note in &sheet.notes {
+        println!("{}", i18n.message(&note.message(args.replace)));
+    }
+    println!(
+        "{}",
+        i18n.i18n_with(
+            I18nPhrase::ImportSummary,
+            &[("imported", &items.len().to_string()), ("rows", &sheet.rows.to_string())]
+        )
+    );
+
+    if args.dry_run {
+        println!("{}", i18n.i18n(I18nPhrase::ImportDryRun));
+        return Ok(());
+    }
+
+    match &args.into {
+        Some(folder) => {
+            write_items(folder, &items)?;
+            println!("{}", i18n.i18n_with(I18nPhrase::ImportWroteInto, &[("path", &folder.display().to_string())]));
+        }
+        None => {
+            for item_type in items {
+                store.apply(CampaignCommand::UpsertItem(item_type))?;
+            }
+            store.save()?;
+            println!(
+                "{}",
+                i18n.i18n_with(I18nPhrase::ImportSavedInto, &[("path", &campaign_folder.display().to_string())])
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn sync(args: SyncArgs, settings: &Settings) -> anyhow::Result<()> {
+    let i18n = Translator::new(settings);
+
+    let requester = cached_dnd5eapi(args.cache)?;
+
+    let report = sync_magic_items(&requester, args.refresh, |done, total, item| {
+        println!(
+            "{}",
+            i18n.i18n_with(
+                I18nPhrase::SyncProgress,
+                &[("done", &done.to_string()), ("total", &total.to_string()), ("item", item)]
+            )
+        );
+    })?;
+
+    for (item, error) in &report.failed {
+        println!("{}", i18n.i18n_with(I18nPhrase::SyncFailed, &[("item", item), ("error", &format!("{error:#}"))]));
+    }
+    println!(
+        "{}",
+        i18n.i18n_with(
+            I18nPhrase::SyncSummary,
+            &[
+                ("downloaded", &report.downloaded.to_string()),
+                ("total", &report.total.to_string()),
+                ("cached", &report.already_cached.to_string()),
+                ("path", &requester.folder().display().to_string()),
+            ]
+        )
+    );
+
+    Ok(())
+}
+
+fn srd(args: SrdArgs, campaign_folder: Option<&Path>, settings: &Settings) -> anyhow::Result<()> {
+    let i18n = Translator::new(settings);
+    let requester = cached_dnd5eapi(args.cache)?;
+    let list = Dnd5eApiMagicItemList::download(&requester)?;
+
+    match args.command {
+        SrdCommand::Search { pattern } => {
+            for listing in list.search_all(&format!("(?i){pattern}"))? {
+                println!("{:<40} {}", listing.index, listing.name);
+            }
+        }
+        SrdCommand::Import {
+            indexes,
+            matching,
+            no_variants,
+            dry_run,
+        } => {
+            let (mut store, folder) = open_campaign(campaign_folder)?;
+
+            let mut sub_urls = vec![];
+            for index in &indexes {
+                match list.listing(index) {
+                    Some(listing) => sub_urls.push(listing.url.clone()),
+                    None => bail!("There is no SRD magic item {index}, search for it with `srd search`"),
+                }
+            }
+            if let Some(pattern) = matching {
+                sub_urls.extend(list.search_all(&format!("(?i){pattern}"))?.iter().map(|listing| listing.url.clone()));
+            }
+
+            let download = download_item_types(&sub_urls, !no_variants, &requester);
+            for (sub_url, error) in &download.failed {
+                println!("{}", i18n.i18n_with(I18nPhrase::SrdImportFailed, &[("item", sub_url), ("error", &format!("{error:#}"))]));
+            }
+            for item_type in &download.items {
+                println!(
+                    "{}",
+                    i18n.i18n_with(I18nPhrase::SrdImportedItem, &[("name", &item_type.name), ("identifier", &item_type.identifier)])
+                );
+            }
+
+            if dry_run {
+                println!("{}", i18n.i18n(I18nPhrase::ImportDryRun));
+                return Ok(());
+            }
+
+            for item_type in download.items {
+                let item_type = store.campaign().registry.as_import(item_type);
+                store.apply(CampaignCommand::UpsertItem(item_type))?;
+            }
+            store.save()?;
+            println!("{}", i18n.i18n_with(I18nPhrase::ImportSavedInto, &[("path", &folder.display().to_string())]));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+use anyhow::Context;
+use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+
+/// Context whose overrides apply to every other context, unless that context overrides the action itself.
+pub const ALL_CONTEXTS: &str = "all";
+
+/// A key together with its modifiers, as written in the keymap file, e.g. `Ctrl+s`, `PgDn` or `k`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text.trim();
+
+        // "+" alone and "Ctrl++" name the plus key, so only split while something follows the separator.
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match prefix.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            rest = key;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "del" | "delete" => KeyCode::Delete,
+            "ins" | "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pgup" | "pageup" => KeyCode::PageUp,
+            "pgdn" | "pagedown" => KeyCode::PageDown,
+            "up" | "↑" => KeyCode::Up,
+            "down" | "↓" => KeyCode::Down,
+            "left" | "←" => KeyCode::Left,
+            "right" | "→" => KeyCode::Right,
+            function if function.len() > 1 && function.starts_with('f') => {
+                let number = function[1..].parse().with_context(|| format!("Unknown key '{text}'"))?;
+                KeyCode::F(number)
+            }
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::Char(c.to_ascii_uppercase()),
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => anyhow::bail!("Unknown key '{text}'"),
+                }
+            }
+        };
+
+        // Shift is part of the upper case character and of Back Tab, the same way key events are matched.
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers -= KeyModifiers::SHIFT;
+        }
+
+        Ok(Self { code, modifiers })
+    }
+
+    /// Printable characters without Ctrl or Alt, which a text input types instead of treating them as commands.
+    pub fn is_typed(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && !self.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Keys the user assigned to named actions, per context. Read from a YAML file like
+///
+/// ```yaml
+/// all:
+///   up: k
+///   down: j
+/// shops:
+///   new: [n, Ctrl+n]
+///   delete: []
+/// ```
+///
+/// An action with an empty list is unbound. Actions that are not mentioned keep their default keys.
+#[derive(Debug, Default)]
+pub struct KeyBindings {
+    contexts: HashMap<String, HashMap<String, Vec<KeyChord>>>,
+}
+
+impl KeyBindings {
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        let raw: HashMap<String, HashMap<String, KeyList>> = serde_yaml::from_str(yaml)?;
+
+        let mut contexts = HashMap::new();
+        for (context, actions) in raw {
+            let mut parsed = HashMap::new();
+            for (action, keys) in actions {
+                let keys = match keys {
+                    KeyList::One(key) => vec![key],
+                    KeyList::Many(keys) => keys,
+                };
+                let chords = keys
+                    .iter()
+                    .map(|key| KeyChord::parse(key))
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .with_context(|| format!("Invalid keys for {context}.{action}"))?;
+                parsed.insert(action, chords);
+            }
+            contexts.insert(context, parsed);
+        }
+
+        Ok(Self { contexts })
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let yaml = fs::read_to_string(path).with_context(|| format!("Could not read keymap {}", path.display()))?;
+        Self::from_yaml(&yaml).with_context(|| format!("Could not parse keymap {}", path.display()))
+    }
+
+    /// The keymap of the user config directory, if there is one.
+    pub fn default_path() -> Option<std::path::PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dnd-simp").join("keymap.yaml"))
+    }
+
+    /// The keys the user chose for an action, `None` if the default keys stay.
+    pub fn keys_for(&self, context: &str, action: &str) -> Option<&[KeyChord]> {
+        [context, ALL_CONTEXTS]
+            .iter()
+            .find_map(|context| self.contexts.get(*context)?.get(action))
+            .map(Vec::as_slice)
+    }
+}
+
+static INSTALLED: OnceLock<KeyBindings> = OnceLock::new();
+
+/// Makes the bindings the ones every key map uses. Only the first call has an effect, so call it before the UI starts.
+pub fn install(bindings: KeyBindings) {
+    if INSTALLED.set(bindings).is_err() {
+        log::warn!("Key bindings were already installed, ignoring the new ones.");
+    }
+}
+
+pub fn installed() -> &'static KeyBindings {
+    INSTALLED.get_or_init(KeyBindings::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        KeyChord { code, modifiers }
+    }
+
+    #[test]
+    fn keys_are_parsed_like_they_are_labeled() {
+        assert_eq!(KeyChord::parse("k").unwrap(), chord(KeyCode::Char('k'), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("Ctrl+s").unwrap(), chord(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert_eq!(KeyChord::parse("Shift+t").unwrap(), chord(KeyCode::Char('T'), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("+").unwrap(), chord(KeyCode::Char('+'), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("Alt++").unwrap(), chord(KeyCode::Char('+'), KeyModifiers::ALT));
+        assert_eq!(KeyChord::parse("PgDn").unwrap(), chord(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("Shift+Tab").unwrap(), chord(KeyCode::BackTab, KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("F5").unwrap(), chord(KeyCode::F(5), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("Space").unwrap(), chord(KeyCode::Char(' '), KeyModifiers::NONE));
+
+        assert!(KeyChord::parse("Hyper+x").is_err());
+        assert!(KeyChord::parse("jk").is_err());
+    }
+
+    #[test]
+    fn context_overrides_win_over_all() {
+        let bindings = KeyBindings::from_yaml(
+            "
+all:
+  up: k
+  down: [j, Down]
+shops:
+  up: Ctrl+p
+  delete: []
+",
+        )
+        .unwrap();
+
+        assert_eq!(bindings.keys_for("shops", "up"), Some(&[chord(KeyCode::Char('p'), KeyModifiers::CONTROL)][..]));
+        assert_eq!(bindings.keys_for("items", "up"), Some(&[chord(KeyCode::Char('k'), KeyModifiers::NONE)][..]));
+        assert_eq!(bindings.keys_for("shops", "down").map(<[_]>::len), Some(2));
+        assert_eq!(bindings.keys_for("shops", "delete"), Some(&[][..]));
+        assert_eq!(bindings.keys_for("shops", "new"), None);
+    }
+
+    #[test]
+    fn invalid_keys_name_the_action() {
+        let error = KeyBindings::from_yaml("shops:\n  new: Meta+n\n").unwrap_err();
+
+        assert!(format!("{error:#}").contains("shops.new"));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod keymap;
+pub mod settings;
+pub mod theme;
@@ -0,0 +1,294 @@
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use crate::{
+    data::item::ItemType,
+    i18n,
+    registry::{dmg_magic_item_cost, xanathar_magic_item_cost},
+};
+
+pub type SharedSettings = Rc<RefCell<SettingsStore>>;
+
+pub const MIN_OFFER_SIZE: u8 = 1;
+pub const MAX_OFFER_SIZE: u8 = 10;
+pub const MAX_AUTOSAVE_MINUTES: u8 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize, EnumIter, Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum Language {
+    /// Whatever the locale of the environment asks for.
+    #[default]
+    System,
+    English,
+    #[strum(to_string = "Deutsch")]
+    German,
+}
+
+impl Language {
+    /// The language to show, never [`Language::System`].
+    pub fn resolve(&self) -> Language {
+        match self {
+            Self::System => i18n::system_language(),
+            language => *language,
+        }
+    }
+}
+
+/// Which table the prices of magic items are rolled from.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, EnumIter, Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum PricingStrategy {
+    #[default]
+    #[strum(to_string = "Xanathar's Guide")]
+    Xanathar,
+    #[strum(to_string = "Dungeon Master's Guide")]
+    DungeonMastersGuide,
+}
+
+impl PricingStrategy {
+    /// Dice expression for the price of the item in gold.
+    pub fn cost_expression(&self, item: &ItemType) -> String {
+        match self {
+            Self::Xanathar => xanathar_magic_item_cost(item),
+            Self::DungeonMastersGuide => dmg_magic_item_cost(item),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, EnumIter, Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    ColorBlindSafe,
+}
+
+/// File format of exported handouts.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, EnumIter, Display, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum HandoutFormat {
+    #[default]
+    Markdown,
+    #[strum(to_string = "HTML")]
+    Html,
+    Text,
+}
+
+impl HandoutFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Text => "txt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, EnumIter, Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(&self) -> LevelFilter {
+        match self {
+            Self::Off => LevelFilter::Off,
+            Self::Error => LevelFilter::Error,
+            Self::Warn => LevelFilter::Warn,
+            Self::Info => LevelFilter::Info,
+            Self::Debug => LevelFilter::Debug,
+            Self::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Preferences of the user, independent of the campaign.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Campaign folder to open when none is given on the command line.
+    pub default_campaign: Option<PathBuf>,
+    pub language: Language,
+    pub pricing: PricingStrategy,
+    /// Number of items a shop puts on the counter for an offer.
+    pub offer_size: u8,
+    pub theme: Theme,
+    pub handout_format: HandoutFormat,
+    /// Leave prices out of exported handouts.
+    pub hide_prices_in_handouts: bool,
+    /// Minutes between automatic saves, 0 turns auto-save off.
+    pub autosave_minutes: u8,
+    pub log_level: LogLevel,
+    /// Use icons of a Nerd Font instead of plain text.
+    pub nerd_font: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            default_campaign: None,
+            language: Language::default(),
+            pricing: PricingStrategy::default(),
+            offer_size: 3,
+            theme: Theme::default(),
+            handout_format: HandoutFormat::default(),
+            hide_prices_in_handouts: false,
+            autosave_minutes: 5,
+            log_level: LogLevel::default(),
+            nerd_font: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    pub fn from_yaml(yaml_text: &str) -> anyhow::Result<Self> {
+        let mut settings: Self = serde_yaml::from_str(yaml_text)?;
+        settings.offer_size = settings.offer_size.clamp(MIN_OFFER_SIZE, MAX_OFFER_SIZE);
+        settings.autosave_minutes = settings.autosave_minutes.min(MAX_AUTOSAVE_MINUTES);
+        Ok(settings)
+    }
+
+    /// Time between automatic saves, `None` while auto-save is off.
+    pub fn autosave_interval(&self) -> Option<Duration> {
+        match self.autosave_minutes {
+            0 => None,
+            minutes => Some(Duration::from_secs(u64::from(minutes) * 60)),
+        }
+    }
+}
+
+/// The settings together with the file they are kept in. Every change is written right away.
+#[derive(Debug)]
+pub struct SettingsStore {
+    settings: Settings,
+    path: Option<PathBuf>,
+}
+
+impl SettingsStore {
+    /// Settings file in the user config directory, e.g. `~/.config/dnd-simp/settings.yaml`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dnd-simp").join("settings.yaml"))
+    }
+
+    /// Reads the settings from the file, a missing file means the defaults.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let settings = if path.exists() {
+            let yaml = fs::read_to_string(path).with_context(|| format!("Could not read settings {}", path.display()))?;
+            Settings::from_yaml(&yaml).with_context(|| format!("Could not parse settings {}", path.display()))?
+        } else {
+            Settings::default()
+        };
+
+        Ok(Self {
+            settings,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Settings that are not backed by a file.
+    pub fn in_memory(settings: Settings) -> Self {
+        Self { settings, path: None }
+    }
+
+    pub fn into_shared(self) -> SharedSettings {
+        Rc::new(RefCell::new(self))
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Changes the settings and saves them. The change is kept even if saving fails.
+    pub fn update(&mut self, change: impl FnOnce(&mut Settings)) -> anyhow::Result<()> {
+        change(&mut self.settings);
+        self.save()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Could not create {}", dir.display()))?;
+        }
+        fs::write(path, self.settings.to_yaml()?).with_context(|| format!("Could not write settings {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use crate::data::item::{ItemCategory, Rarity};
+
+    use super::*;
+
+    #[test]
+    fn missing_settings_fall_back_to_defaults() {
+        let settings = Settings::from_yaml("language: german\noffer_size: 99\n").unwrap();
+
+        assert_eq!(settings.language, Language::German);
+        assert_eq!(settings.offer_size, MAX_OFFER_SIZE);
+        assert_eq!(settings.pricing, PricingStrategy::Xanathar);
+        assert!(settings.nerd_font);
+        assert_eq!(settings.autosave_interval(), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn every_pricing_strategy_rolls_every_rarity() {
+        for pricing in PricingStrategy::iter() {
+            for rarity in Rarity::iter() {
+                let item = ItemType::new("Test".into(), rarity, ItemCategory::WondrousItem, String::new());
+                let expression = pricing.cost_expression(&item);
+
+                assert!(expression.parse::<tyche::Expr>().is_ok(), "{pricing} cannot roll {expression}");
+            }
+        }
+    }
+
+    #[test]
+    fn settings_are_saved_on_every_change() {
+        let path = std::env::temp_dir().join(format!("dnd-simp-settings-{}", std::process::id())).join("settings.yaml");
+
+        let mut store = SettingsStore::open(&path).unwrap();
+        store
+            .update(|settings| {
+                settings.pricing = PricingStrategy::DungeonMastersGuide;
+                settings.default_campaign = Some(PathBuf::from("campaigns/tina"));
+            })
+            .unwrap();
+
+        let reopened = SettingsStore::open(&path).unwrap();
+        assert_eq!(reopened.settings(), store.settings());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+use std::{cell::RefCell, fs, path::Path, rc::Rc, sync::OnceLock};
+
+use anyhow::Context;
+use ratatui::style::{palette::material, Color};
+use serde::{Deserialize, Serialize};
+
+use super::settings::Theme;
+
+/// Background of a tab in the tab bar.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TabColors {
+    pub idle: Color,
+    pub selected: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RarityColors {
+    pub common: Color,
+    pub uncommon: Color,
+    pub rare: Color,
+    pub very_rare: Color,
+    pub legendary: Color,
+    pub artifact: Color,
+}
+
+/// Every color the user interface draws with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub text: Color,
+    /// Hints and other text that steps back, like undone edits.
+    pub muted: Color,
+    pub border: Color,
+    pub app_title: Color,
+    pub campaign_title: Color,
+    pub date: Color,
+    /// One per tab, repeated if there are more tabs.
+    pub tabs: Vec<TabColors>,
+    /// Background of table headers.
+    pub header: Color,
+    pub key: Color,
+    pub key_background: Color,
+    /// Something the party is looking for, like a wished item in stock.
+    pub highlight: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub die: Color,
+    pub rarity: RarityColors,
+}
+
+fn tab(palette: material::AccentedPalette) -> TabColors {
+    TabColors {
+        idle: palette.c900,
+        selected: palette.a100,
+    }
+}
+
+impl Palette {
+    pub fn built_in(theme: Theme) -> Self {
+        match theme {
+            Theme::Dark => Self::dark(),
+            Theme::Light => Self::light(),
+            Theme::HighContrast => Self::high_contrast(),
+            Theme::ColorBlindSafe => Self::color_blind_safe(),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::DarkGray,
+            border: material::RED.a100,
+            app_title: Color::Blue,
+            campaign_title: Color::Yellow,
+            date: Color::Gray,
+            tabs: [material::BLUE, material::RED, material::YELLOW, material::PURPLE, material::GREEN, material::INDIGO]
+                .into_iter()
+                .map(tab)
+                .collect(),
+            header: material::GREEN.c600,
+            key: Color::Black,
+            key_background: Color::White,
+            highlight: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            die: material::WHITE,
+            rarity: RarityColors {
+                common: Color::Gray,
+                uncommon: Color::White,
+                rare: Color::Green,
+                very_rare: Color::Magenta,
+                legendary: Color::Red,
+                artifact: Color::Red,
+            },
+        }
+    }
+
+    /// For terminals with a light background.
+    fn light() -> Self {
+        Self {
+            text: Color::Black,
+            muted: Color::Gray,
+            border: material::RED.c700,
+            app_title: material::BLUE.c200,
+            campaign_title: material::YELLOW.c200,
+            date: material::GRAY.c300,
+            tabs: [material::BLUE, material::RED, material::YELLOW, material::PURPLE, material::GREEN, material::INDIGO]
+                .into_iter()
+                .map(|palette| TabColors {
+                    idle: palette.c100,
+                    selected: palette.c300,
+                })
+                .collect(),
+            header: material::GREEN.c200,
+            key: Color::White,
+            key_background: Color::Black,
+            highlight: material::GREEN.c800,
+            warning: material::ORANGE.c900,
+            error: material::RED.c800,
+            die: material::GRAY.c900,
+            rarity: RarityColors {
+                common: material::GRAY.c700,
+                uncommon: Color::Black,
+                rare: material::GREEN.c800,
+                very_rare: material::PURPLE.c700,
+                legendary: material::RED.c800,
+                artifact: material::RED.c900,
+            },
+        }
+    }
+
+    /// Only black, white and the brightest colors.
+    fn high_contrast() -> Self {
+        let tab = TabColors {
+            idle: Color::Black,
+            selected: Color::White,
+        };
+
+        Self {
+            text: Color::White,
+            muted: Color::White,
+            border: Color::White,
+            app_title: Color::Black,
+            campaign_title: Color::Black,
+            date: Color::Black,
+            tabs: vec![tab],
+            header: Color::Black,
+            key: Color::Black,
+            key_background: Color::LightYellow,
+            highlight: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            die: Color::White,
+            rarity: RarityColors {
+                common: Color::White,
+                uncommon: Color::LightCyan,
+                rare: Color::LightGreen,
+                very_rare: Color::LightMagenta,
+                legendary: Color::LightYellow,
+                artifact: Color::LightRed,
+            },
+        }
+    }
+
+    /// The palette of Okabe and Ito, which stays apart for every kind of color blindness.
+    fn color_blind_safe() -> Self {
+        let orange = Color::Rgb(0xe6, 0x9f, 0x00);
+        let sky_blue = Color::Rgb(0x56, 0xb4, 0xe9);
+        let bluish_green = Color::Rgb(0x00, 0x9e, 0x73);
+        let yellow = Color::Rgb(0xf0, 0xe4, 0x42);
+        let blue = Color::Rgb(0x00, 0x72, 0xb2);
+        let vermillion = Color::Rgb(0xd5, 0x5e, 0x00);
+        let reddish_purple = Color::Rgb(0xcc, 0x79, 0xa7);
+
+        Self {
+            text: Color::White,
+            muted: Color::DarkGray,
+            border: sky_blue,
+            app_title: blue,
+            campaign_title: orange,
+            date: Color::DarkGray,
+            tabs: vec![TabColors {
+                idle: Color::Rgb(0x1a, 0x3a, 0x52),
+                selected: blue,
+            }],
+            header: blue,
+            key: Color::Black,
+            key_background: Color::White,
+            highlight: sky_blue,
+            warning: orange,
+            error: vermillion,
+            die: Color::White,
+            rarity: RarityColors {
+                common: Color::Gray,
+                uncommon: Color::White,
+                rare: bluish_green,
+                very_rare: reddish_purple,
+                legendary: yellow,
+                artifact: vermillion,
+            },
+        }
+    }
+
+    /// The palette with the colors named in the YAML replaced, e.g. `highlight: "#56b4e9"` or
+    /// `rarity: { rare: light-green }`.
+    pub fn with_overrides(self, overrides: &ThemeOverrides) -> anyhow::Result<Self> {
+        let mut palette = serde_yaml::to_value(self)?;
+        merge(&mut palette, &overrides.0);
+        Ok(serde_yaml::from_value(palette)?)
+    }
+}
+
+fn merge(target: &mut serde_yaml::Value, overrides: &serde_yaml::Value) {
+    match (target, overrides) {
+        (serde_yaml::Value::Mapping(target), serde_yaml::Value::Mapping(overrides)) => {
+            for (key, value) in overrides {
+                match target.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, overrides) => *target = overrides.clone(),
+    }
+}
+
+/// Colors the user changed in the theme file, applied on top of whichever theme is chosen.
+#[derive(Debug, Clone, Default)]
+pub struct ThemeOverrides(serde_yaml::Value);
+
+impl ThemeOverrides {
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        let overrides = Self(serde_yaml::from_str(yaml)?);
+        // Broken colors show up now instead of whenever the theme changes.
+        Palette::dark().with_overrides(&overrides)?;
+        Ok(overrides)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let yaml = fs::read_to_string(path).with_context(|| format!("Could not read theme {}", path.display()))?;
+        Self::from_yaml(&yaml).with_context(|| format!("Could not parse theme {}", path.display()))
+    }
+
+    /// The theme file of the user config directory, if there is one.
+    pub fn default_path() -> Option<std::path::PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dnd-simp").join("theme.yaml"))
+    }
+}
+
+static OVERRIDES: OnceLock<ThemeOverrides> = OnceLock::new();
+
+thread_local! {
+    static ACTIVE: RefCell<Rc<Palette>> = RefCell::new(Rc::new(Palette::dark()));
+}
+
+/// Keeps the overrides of the user for every theme applied afterwards. Only the first call has an effect.
+pub fn install_overrides(overrides: ThemeOverrides) {
+    if OVERRIDES.set(overrides).is_err() {
+        log::warn!("Theme overrides were already installed, ignoring the new ones.");
+    }
+}
+
+/// Makes the theme the one every widget draws with from now on.
+pub fn apply(theme: Theme) {
+    let palette = Palette::built_in(theme);
+    let palette = match OVERRIDES.get() {
+        Some(overrides) => palette.with_overrides(overrides).unwrap_or_else(|e| {
+            log::error!("Ignoring the theme overrides. {:?}", e);
+            Palette::built_in(theme)
+        }),
+        None => palette,
+    };
+
+    ACTIVE.with(|active| *active.borrow_mut() = Rc::new(palette));
+}
+
+pub fn palette() -> Rc<Palette> {
+    ACTIVE.with(|active| active.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn overrides_replace_single_colors() {
+        let overrides = ThemeOverrides::from_yaml("highlight: '#56b4e9'\nrarity:\n  rare: light-green\n").unwrap();
+        let palette = Palette::built_in(Theme::Light).with_overrides(&overrides).unwrap();
+
+        assert_eq!(palette.highlight, Color::Rgb(0x56, 0xb4, 0xe9));
+        assert_eq!(palette.rarity.rare, Color::LightGreen);
+        assert_eq!(palette.text, Palette::built_in(Theme::Light).text);
+    }
+
+    #[test]
+    fn broken_overrides_are_rejected() {
+        assert!(ThemeOverrides::from_yaml("highlight: not-a-color\n").is_err());
+    }
+
+    #[test]
+    fn every_theme_has_tabs() {
+        for theme in Theme::iter() {
+            assert!(!Palette::built_in(theme).tabs.is_empty(), "{theme} has no tab colors");
+        }
+    }
+}
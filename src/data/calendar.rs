@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+
+pub const HOURS_PER_DAY: u32 = 24;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarMonth {
+    pub name: String,
+    pub days: u32,
+}
+
+/// A single day outside of the regular months, e.g. Midwinter in the Calendar of Harptos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Festival {
+    pub name: String,
+    /// Index of the month this festival follows.
+    pub after_month: usize,
+    /// The festival only takes place in years divisible by this number (leap days).
+    #[serde(default)]
+    pub every_years: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calendar {
+    pub name: String,
+    pub year_suffix: String,
+    pub months: Vec<CalendarMonth>,
+    #[serde(default)]
+    pub festivals: Vec<Festival>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CampaignDate {
+    pub year: i32,
+    /// Zero based day of the year, festivals included.
+    pub day: u32,
+    pub hour: u32,
+}
+
+impl Default for CampaignDate {
+    fn default() -> Self {
+        Self {
+            year: 1492,
+            day: 0,
+            hour: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalendarDay<'a> {
+    MonthDay { month: &'a CalendarMonth, day: u32 },
+    Festival(&'a Festival),
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self::harptos()
+    }
+}
+
+impl Calendar {
+    /// The Calendar of Harptos used in the Forgotten Realms.
+    pub fn harptos() -> Self {
+        let month = |name: &str| CalendarMonth {
+            name: name.into(),
+            days: 30,
+        };
+        let festival = |name: &str, after_month: usize| Festival {
+            name: name.into(),
+            after_month,
+            every_years: None,
+        };
+
+        Self {
+            name: "Calendar of Harptos".into(),
+            year_suffix: "DR".into(),
+            months: vec![
+                month("Hammer"),
+                month("Alturiak"),
+                month("Ches"),
+                month("Tarsakh"),
+                month("Mirtul"),
+                month("Kythorn"),
+                month("Flamerule"),
+                month("Eleasis"),
+                month("Eleint"),
+                month("Marpenoth"),
+                month("Uktar"),
+                month("Nightal"),
+            ],
+            festivals: vec![
+                festival("Midwinter", 0),
+                festival("Greengrass", 3),
+                festival("Midsummer", 6),
+                Festival {
+                    name: "Shieldmeet".into(),
+                    after_month: 6,
+                    every_years: Some(4),
+                },
+                festival("Highharvestide", 8),
+                festival("Feast of the Moon", 10),
+            ],
+        }
+    }
+
+    fn festival_takes_place(festival: &Festival, year: i32) -> bool {
+        festival
+            .every_years
+            .is_none_or(|every| every != 0 && year.rem_euclid(every as i32) == 0)
+    }
+
+    /// All days of the given year in order, festivals placed behind their month.
+    fn days_of_year(&self, year: i32) -> impl Iterator<Item = CalendarDay<'_>> {
+        self.months.iter().enumerate().flat_map(move |(idx, month)| {
+            let month_days = (1..=month.days).map(move |day| CalendarDay::MonthDay { month, day });
+            let festival_days = self
+                .festivals
+                .iter()
+                .filter(move |festival| festival.after_month == idx)
+                .filter(move |festival| Self::festival_takes_place(festival, year))
+                .map(CalendarDay::Festival);
+
+            month_days.chain(festival_days)
+        })
+    }
+
+    pub fn days_in_year(&self, year: i32) -> u32 {
+        self.days_of_year(year).count() as u32
+    }
+
+    /// Fails for dates this calendar does not have, e.g. day 400 of a hand-edited `simp.yaml`.
+    pub fn check(&self, date: &CampaignDate) -> anyhow::Result<()> {
+        if date.hour >= HOURS_PER_DAY {
+            anyhow::bail!("Hour {} does not exist, a day has {HOURS_PER_DAY} hours", date.hour);
+        }
+
+        let days = self.days_in_year(date.year);
+        if date.day >= days {
+            anyhow::bail!("Day {} does not exist, the year {} {} has {days} days", date.day, date.year, self.year_suffix);
+        }
+
+        Ok(())
+    }
+
+    pub fn day_of(&self, date: &CampaignDate) -> Option<CalendarDay<'_>> {
+        self.days_of_year(date.year).nth(date.day as usize)
+    }
+
+    /// Moves `date` forward by `hours`, carrying over into days and years.
+    pub fn advance_hours(&self, date: &CampaignDate, hours: u32) -> CampaignDate {
+        let total_hours = date.hour + hours;
+        let mut next = CampaignDate {
+            hour: total_hours % HOURS_PER_DAY,
+            ..*date
+        };
+
+        let mut remaining_days = total_hours / HOURS_PER_DAY;
+        while remaining_days > 0 {
+            let days_in_year = self.days_in_year(next.year).max(1);
+            let days_left_in_year = days_in_year.saturating_sub(next.day);
+
+            if remaining_days < days_left_in_year {
+                next.day += remaining_days;
+                remaining_days = 0;
+            } else {
+                remaining_days -= days_left_in_year;
+                next.day = 0;
+                next.year += 1;
+            }
+        }
+
+        next
+    }
+
+    pub fn advance_days(&self, date: &CampaignDate, days: u32) -> CampaignDate {
+        self.advance_hours(date, days * HOURS_PER_DAY)
+    }
+
+    /// Number of midnights between `from` and `to`.
+    pub fn days_between(&self, from: &CampaignDate, to: &CampaignDate) -> u32 {
+        let mut days = 0;
+        let mut year = from.year;
+        let mut day = from.day;
+
+        while year < to.year {
+            days += self.days_in_year(year).saturating_sub(day);
+            day = 0;
+            year += 1;
+        }
+
+        days + to.day.saturating_sub(day)
+    }
+
+    pub fn format(&self, date: &CampaignDate) -> String {
+        let day = match self.day_of(date) {
+            Some(CalendarDay::MonthDay { month, day }) => format!("{day}. {}", month.name),
+            Some(CalendarDay::Festival(festival)) => festival.name.clone(),
+            None => format!("Day {}", date.day + 1),
+        };
+
+        format!("{day} {} {}, {:02}:00", date.year, self.year_suffix, date.hour)
+    }
+}
+
+/// Something that happened at a specific in-game date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamped<T> {
+    pub date: CampaignDate,
+    pub value: T,
+}
+
+impl<T> Stamped<T> {
+    pub fn new(date: CampaignDate, value: T) -> Self {
+        Self { date, value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calendar, CalendarDay, CampaignDate};
+
+    #[test]
+    fn harptos_has_leap_day_every_four_years() {
+        let calendar = Calendar::harptos();
+
+        assert_eq!(calendar.days_in_year(1491), 365);
+        assert_eq!(calendar.days_in_year(1492), 366);
+    }
+
+    #[test]
+    fn festivals_follow_their_month() {
+        let calendar = Calendar::harptos();
+        let midwinter = CampaignDate {
+            year: 1491,
+            day: 30,
+            hour: 0,
+        };
+
+        assert!(matches!(
+            calendar.day_of(&midwinter),
+            Some(CalendarDay::Festival(festival)) if festival.name == "Midwinter"
+        ));
+        assert_eq!(calendar.format(&midwinter), "Midwinter 1491 DR, 00:00");
+    }
+
+    #[test]
+    fn advancing_hours_carries_into_days_and_years() {
+        let calendar = Calendar::harptos();
+        let date = CampaignDate {
+            year: 1491,
+            day: 364,
+            hour: 20,
+        };
+
+        let next = calendar.advance_hours(&date, 6);
+
+        assert_eq!(
+            next,
+            CampaignDate {
+                year: 1492,
+                day: 0,
+                hour: 2
+            }
+        );
+        assert_eq!(calendar.format(&next), "1. Hammer 1492 DR, 02:00");
+        assert_eq!(calendar.days_between(&date, &next), 1);
+    }
+
+    #[test]
+    fn advancing_days_keeps_the_hour() {
+        let calendar = Calendar::harptos();
+        let date = CampaignDate::default();
+
+        let next = calendar.advance_days(&date, 400);
+
+        assert_eq!(next.hour, date.hour);
+        assert_eq!(calendar.days_between(&date, &next), 400);
+    }
+
+    #[test]
+    fn dates_outside_the_calendar_are_caught() {
+        let calendar = Calendar::harptos();
+        let leap_year_end = CampaignDate { year: 1492, day: 365, hour: 23 };
+        let too_late = CampaignDate { year: 1491, day: 365, hour: 8 };
+
+        assert!(calendar.check(&leap_year_end).is_ok());
+        assert!(calendar.check(&too_late).is_err());
+        assert!(calendar.check(&CampaignDate { hour: 24, ..CampaignDate::default() }).is_err());
+        assert_eq!(calendar.days_between(&too_late, &CampaignDate { year: 1492, day: 0, hour: 8 }), 0);
+    }
+}
@@ -1,30 +1,49 @@
-use std::path::PathBuf;
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}};
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
 use crate::registry::ItemRegistry;
 
-use super::{character::{Character, FileCharacter}, item::ItemType, shop::{FileShop, Shop}};
+use rand::Rng;
 
+use super::{calendar::{Calendar, CampaignDate, Stamped}, character::Character, item::{slugify, ItemIdentifier, ItemType}, shop::{Shop, ShopChangelog, Stock}, weather::Climate};
 
-use std::{cell::RefCell, rc::Rc};
-
-use crate::item::Item;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMeta {
     pub name: String,
+    #[serde(default)]
+    pub calendar: Calendar,
+    #[serde(default)]
+    pub date: CampaignDate,
+    #[serde(default)]
+    pub climate: Climate,
+    /// Restocks of the shops so far, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ledger: Vec<Stamped<ShopChangelog>>,
 }
 
-fn load_object_vector<T: for<'a> Deserialize<'a>>(folder: PathBuf) -> anyhow::Result<Vec<T>> {
-    let entries = std::fs::read_dir(folder)?;
-
-    let objects = entries
-        .filter_map(|maybe_entry| 
-            match maybe_entry {
-                Ok(entry) => load_object(&entry.path()).ok(),
-                Err(_) => None
-            })
+pub const META_FILE: &str = "simp.yaml";
+pub const ITEMS_FOLDER: &str = "items";
+pub const CHARACTERS_FOLDER: &str = "characters";
+pub const SHOPS_FOLDER: &str = "shops";
+
+/// Loads every object in `folder`, sorted by file name so that indices stay stable between runs.
+/// Returns the file name each object was loaded from.
+fn load_object_vector<T: for<'a> Deserialize<'a>>(folder: PathBuf) -> anyhow::Result<Vec<(String, T)>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(folder)?
+        .filter_map(|maybe_entry| maybe_entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .collect();
+    paths.sort();
+
+    let objects = paths
+        .iter()
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_string_lossy().into_owned();
+            load_object(path).ok().map(|object| (file_name, object))
+        })
         .collect();
 
     Ok(objects)
@@ -42,99 +61,485 @@ fn load_object<T: for<'a> Deserialize<'a>>(path: &PathBuf) -> anyhow::Result<T>{
     Ok(object)
 }
 
-fn load_characters(character_folder: PathBuf) -> anyhow::Result<Vec<FileCharacter>> {
-    load_object_vector(character_folder)
-}
+pub fn load_campaign_folder(folder_path: &Path) -> anyhow::Result<CampaignFolder> {
+    let mut files = CampaignFiles::default();
 
-fn load_items(items_folder: PathBuf) -> anyhow::Result<Vec<ItemType>> {
-    load_object_vector(items_folder)
-}
+    let items = load_object_vector::<ItemType>(folder_path.join(ITEMS_FOLDER))
+        .unwrap_or(vec![])
+        .into_iter()
+        .map(|(file, item)| {
+            files.items.insert(item.identifier.clone(), file);
+            item
+        })
+        .collect();
+    let item_registry = ItemRegistry { items };
 
+    let characters = load_object_vector::<Character>(folder_path.join(CHARACTERS_FOLDER))
+        .unwrap_or(vec![])
+        .into_iter()
+        .map(|(file, character)| {
+            files.characters.insert(character.name.clone(), file);
+            character
+        })
+        .collect();
 
-fn load_campaign_folder(folder_path: PathBuf) -> anyhow::Result<CampaignFolder> {
-    let items_path = folder_path.join("items"); // @todo make this some global const
-    let items = load_items(items_path)
-        .unwrap_or(vec![]);
-    let item_registry = ItemRegistry { items };
-    
-    let character_path = folder_path.join("characters"); // @todo make this some global const
-    let characters = load_characters(character_path) 
-        .unwrap_or(vec![]);
+    let shops = load_object_vector::<Shop>(folder_path.join(SHOPS_FOLDER))
+        .unwrap_or(vec![])
+        .into_iter()
+        .map(|(file, shop)| {
+            files.shops.insert(shop.name.clone(), file);
+            shop
+        })
+        .collect();
 
-    let f = std::fs::File::open(folder_path.join("simp.yaml"))?;
+    let f = std::fs::File::open(folder_path.join(META_FILE))?;
     let meta: FileMeta = serde_yaml::from_reader(f)?;
-    
+    meta.calendar
+        .check(&meta.date)
+        .with_context(|| format!("The date in {} is not on its calendar", folder_path.join(META_FILE).display()))?;
+
     let cf = CampaignFolder {
         characters,
         meta,
         item_registry,
-        shops: vec![],
+        shops,
+        files,
     };
-    
+
     Ok(cf)
+}
+
+/// Remembers which file in the campaign folder holds which object, keyed by item identifier,
+/// shop name and character name. Saving reuses these files and deletes the ones whose object is gone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CampaignFiles {
+    pub items: HashMap<String, String>,
+    pub characters: HashMap<String, String>,
+    pub shops: HashMap<String, String>,
+}
 
+pub(crate) fn write_yaml<T: Serialize>(path: &Path, object: &T) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("yaml.tmp");
+    std::fs::write(&tmp_path, serde_yaml::to_string(object)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
 }
 
+/// The file `key` was loaded from, or a free one named after it.
+fn file_for(folder: &Path, key: &str, known_files: &HashMap<String, String>, used: &HashSet<String>) -> String {
+    if let Some(file) = known_files.get(key) {
+        if !used.contains(file) {
+            return file.clone();
+        }
+    }
+
+    let stem = match slugify(key) {
+        stem if stem.is_empty() => "unnamed".to_string(),
+        stem => stem,
+    };
+    let is_free = |candidate: &String| {
+        !used.contains(candidate) && !known_files.values().any(|known| known == candidate) && !folder.join(candidate).exists()
+    };
+
+    (1..)
+        .map(|n| if n == 1 { format!("{stem}.yaml") } else { format!("{stem}-{n}.yaml") })
+        .find(is_free)
+        .expect("there is always a free file name")
+}
+
+/// Writes one file per object into `folder` and returns the new key to file mapping.
+fn save_object_vector<'o, T: Serialize + 'o>(
+    folder: &Path,
+    objects: impl Iterator<Item = (&'o String, &'o T)>,
+    known_files: &HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, String>> {
+    std::fs::create_dir_all(folder)?;
+
+    let mut written: HashMap<String, String> = HashMap::new();
+    let mut used: HashSet<String> = HashSet::new();
+
+    for (key, object) in objects {
+        let file = file_for(folder, key, known_files, &used);
+        write_yaml(&folder.join(&file), object)?;
+        used.insert(file.clone());
+        written.insert(key.clone(), file);
+    }
+
+    for stale in known_files.values().filter(|file| !used.contains(*file)) {
+        log::info!("Removing {stale} from {folder:?}");
+        std::fs::remove_file(folder.join(stale))?;
+    }
+
+    Ok(written)
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum FileStorageVersion {
     V1,
 }
 
-pub struct Campaign<'a> {
+/// One item that is on at least one wish list, with everyone who wants it and the shops that have it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WishedItem {
+    pub identifier: ItemIdentifier,
+    /// `None` if the registry does not know the identifier.
+    pub item_type: Option<ItemType>,
+    pub wished_by: Vec<String>,
+    pub stocked_in: Vec<(String, Stock)>,
+}
+
+/// The campaign as it is played, owning all its data.
+#[derive(Debug, Clone)]
+pub struct Campaign {
     pub name: String,
-    pub characters: Vec<Character<'a>>,
-    pub shops: Vec<Shop<'a>>,
+    pub registry: ItemRegistry,
+    pub characters: Vec<Character>,
+    pub shops: Vec<Shop>,
+    pub calendar: Calendar,
+    pub date: CampaignDate,
+    pub climate: Climate,
+    pub ledger: Vec<Stamped<ShopChangelog>>,
+    pub files: CampaignFiles,
 }
 
 pub struct CampaignFolder {
     pub meta: FileMeta,
     pub item_registry: ItemRegistry,
-    pub characters: Vec<FileCharacter>,
-    pub shops: Vec<FileShop>,
+    pub characters: Vec<Character>,
+    pub shops: Vec<Shop>,
+    pub files: CampaignFiles,
 }
 
 impl CampaignFolder {
     pub fn empty(name: String) -> Self {
         Self {
-            meta: FileMeta { name: name },
+            meta: FileMeta {
+                name,
+                calendar: Calendar::default(),
+                date: CampaignDate::default(),
+                climate: Climate::default(),
+                ledger: vec![],
+            },
             item_registry: ItemRegistry { items: vec![] },
             characters: vec![],
             shops: vec![],
+            files: CampaignFiles::default(),
         }
     }
 
-    pub fn destructure<'a>(&'a self) -> (Campaign<'a>, &'a ItemRegistry) {
-        let campaign = Campaign::from_files(self.meta.clone(), self.characters.clone(), &self.item_registry);
-
-        (campaign, &self.item_registry)
+    pub fn into_campaign(self) -> Campaign {
+        Campaign {
+            name: self.meta.name,
+            registry: self.item_registry,
+            characters: self.characters,
+            shops: self.shops,
+            calendar: self.meta.calendar,
+            date: self.meta.date,
+            climate: self.meta.climate,
+            ledger: self.meta.ledger,
+            files: self.files,
+        }
     }
 }
 
 
-impl<'a> Campaign<'a> {
-    pub fn from_files(meta: FileMeta, characters: Vec<FileCharacter>, registry: &'a ItemRegistry) -> Self {
-        Self {
-            name: meta.name,
-            characters: characters
-                .into_iter()
-                .map(|ch| 
-                    registry.link_character(ch))
-                .collect(),
-            shops: vec![],
+impl Campaign {
+    pub fn formatted_date(&self) -> String {
+        self.calendar.format(&self.date)
+    }
+
+    /// Shops that currently have the item in stock.
+    pub fn shops_stocking(&self, identifier: &ItemIdentifier) -> Vec<&Shop> {
+        self.shops.iter().filter(|shop| shop.has_in_stock(identifier)).collect()
+    }
+
+    /// Characters that have the item on their wish list.
+    pub fn wishing_characters(&self, identifier: &ItemIdentifier) -> Vec<&Character> {
+        self.characters
+            .iter()
+            .filter(|character| character.wish_list.contains(identifier))
+            .collect()
+    }
+
+    /// Every wished-for item in the order the wish lists name them, together with the
+    /// shops that have it in stock.
+    pub fn wish_report(&self) -> Vec<WishedItem> {
+        let mut identifiers: Vec<&ItemIdentifier> = vec![];
+        for identifier in self.characters.iter().flat_map(|character| character.wish_list.iter()) {
+            if !identifiers.contains(&identifier) {
+                identifiers.push(identifier);
+            }
         }
+
+        identifiers
+            .into_iter()
+            .map(|identifier| WishedItem {
+                identifier: identifier.clone(),
+                item_type: self.registry.get(identifier).cloned(),
+                wished_by: self
+                    .wishing_characters(identifier)
+                    .into_iter()
+                    .map(|character| character.name.clone())
+                    .collect(),
+                stocked_in: self
+                    .shops_stocking(identifier)
+                    .into_iter()
+                    .filter_map(|shop| shop.stock_of(identifier).map(|stock| (shop.name.clone(), stock.clone())))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Whether the shop has anything in stock that is on some character's wish list.
+    pub fn stocks_wished_item(&self, shop: &Shop) -> bool {
+        self.characters
+            .iter()
+            .flat_map(|character| character.wish_list.iter())
+            .any(|identifier| shop.has_in_stock(identifier))
+    }
+
+    /// Writes the campaign into `folder` in the layout [`load_campaign_folder`] reads.
+    pub fn save(&mut self, folder: &Path) -> anyhow::Result<()> {
+        let items = save_object_vector(
+            &folder.join(ITEMS_FOLDER),
+            self.registry.items().iter().map(|item| (&item.identifier, item)),
+            &self.files.items,
+        )?;
+        let characters = save_object_vector(
+            &folder.join(CHARACTERS_FOLDER),
+            self.characters.iter().map(|character| (&character.name, character)),
+            &self.files.characters,
+        )?;
+        let shops = save_object_vector(
+            &folder.join(SHOPS_FOLDER),
+            self.shops.iter().map(|shop| (&shop.name, shop)),
+            &self.files.shops,
+        )?;
+
+        let meta = FileMeta {
+            name: self.name.clone(),
+            calendar: self.calendar.clone(),
+            date: self.date,
+            climate: self.climate.clone(),
+            ledger: self.ledger.clone(),
+        };
+        write_yaml(&folder.join(META_FILE), &meta)?;
+
+        self.files = CampaignFiles { items, characters, shops };
+
+        log::info!("Saved campaign {} to {folder:?}", self.name);
+
+        Ok(())
+    }
+
+    /// Writes just one item type into `items/` of the folder, the rest of the campaign is left as it is.
+    pub fn save_item(&mut self, folder: &Path, identifier: &ItemIdentifier) -> anyhow::Result<PathBuf> {
+        let Some(item) = self.registry.get(identifier) else {
+            anyhow::bail!("There is no item {identifier} to save");
+        };
+
+        let folder = folder.join(ITEMS_FOLDER);
+        std::fs::create_dir_all(&folder)?;
+
+        let file = file_for(&folder, identifier, &self.files.items, &HashSet::new());
+        write_yaml(&folder.join(&file), item)?;
+        self.files.items.insert(identifier.clone(), file.clone());
+
+        Ok(folder.join(file))
+    }
+
+    /// Deletes the file of an item type that is no longer in the registry, if it has one.
+    pub fn remove_item_file(&mut self, folder: &Path, identifier: &ItemIdentifier) -> anyhow::Result<()> {
+        let Some(file) = self.files.items.remove(identifier) else {
+            return Ok(());
+        };
+
+        match std::fs::remove_file(folder.join(ITEMS_FOLDER).join(file)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves the campaign clock forward by `hours`. Shops restock once for every midnight passed.
+    /// Returns one changelog per shop whose inventory changed.
+    pub fn advance_hours_with_rng<R: Rng>(&mut self, hours: u32, rng: &mut R) -> Vec<Stamped<ShopChangelog>> {
+        let next_date = self.calendar.advance_hours(&self.date, hours);
+        let days = self.calendar.days_between(&self.date, &next_date);
+        self.date = next_date;
+
+        if days == 0 {
+            return vec![];
+        }
+
+        let registry = &self.registry;
+
+        let changelogs: Vec<_> = self.shops
+            .iter_mut()
+            .map(|shop| shop.advance_days(days, registry, rng))
+            .filter(|changelog| !changelog.changes.is_empty())
+            .map(|changelog| Stamped::new(next_date, changelog))
+            .collect();
+
+        self.ledger.extend(changelogs.iter().cloned());
+
+        changelogs
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::data::calendar::{Stamped, HOURS_PER_DAY};
+    use crate::data::character::Character;
+    use crate::data::item::{ItemCategory, ItemType, Rarity};
+    use crate::data::shop::{InventoryChange, RestockRules, Shop, ShopChangelog, Stock, StockedItem};
     use crate::CampaignFolder;
 
+    use super::load_campaign_folder;
+
     #[test]
     fn new_campaign_is_empty() {
         let e = CampaignFolder::empty("New Campaign".into());
         assert_eq!(e.shops.len(), 0)
     }
-}
 
+    fn sold_mantel_shop(name: &str, return_after_days: Option<u32>) -> Shop {
+        Shop::with_inventory(
+            name.into(),
+            None,
+            vec![StockedItem { identifier: "Mantel".into(), stock: Stock::Sold, days_sold_out: 0 }],
+            RestockRules { return_after_days, ..Default::default() },
+        )
+    }
+
+    #[test]
+    fn advancing_time_reports_changed_shops_only() {
+        let mut folder = CampaignFolder::empty("New Campaign".into());
+        folder.item_registry.add(ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()));
+        folder.shops = vec![
+            sold_mantel_shop("Tina's", Some(1)),
+            sold_mantel_shop("Harry's", None),
+        ];
+
+        let mut campaign = folder.into_campaign();
+        let changelogs = campaign.advance_hours_with_rng(HOURS_PER_DAY, &mut StdRng::seed_from_u64(0));
+
+        assert_eq!(changelogs.len(), 1);
+        assert_eq!(changelogs[0].value.shop_name, "Tina's");
+        assert_eq!(changelogs[0].date, campaign.date);
+        assert_eq!(campaign.ledger.len(), 1);
+    }
+
+    #[test]
+    fn shops_only_restock_when_a_day_passes() {
+        let mut folder = CampaignFolder::empty("New Campaign".into());
+        folder.meta.date.hour = 20;
+        folder.item_registry.add(ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()));
+        folder.shops = vec![sold_mantel_shop("Tina's", Some(1))];
+
+        let mut campaign = folder.into_campaign();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert!(campaign.advance_hours_with_rng(3, &mut rng).is_empty());
+        assert_eq!(campaign.date.hour, 23);
+        assert_eq!(campaign.advance_hours_with_rng(1, &mut rng).len(), 1);
+        assert_eq!(campaign.date.hour, 0);
+    }
+
+    #[test]
+    fn shops_with_wished_items_are_found() {
+        let mut folder = CampaignFolder::empty("New Campaign".into());
+        folder.shops = vec![
+            sold_mantel_shop("Tina's", None),
+            Shop::with_inventory(
+                "Harry's".into(),
+                None,
+                vec![StockedItem { identifier: "Mantel".into(), stock: Stock::Infinite, days_sold_out: 0 }],
+                RestockRules::default(),
+            ),
+        ];
+        folder.characters = vec![Character { name: "Gandalf".into(), wish_list: vec!["Mantel".into()] }];
+
+        let campaign = folder.into_campaign();
+
+        let stocking: Vec<_> = campaign.shops_stocking(&"Mantel".into()).iter().map(|shop| shop.name.clone()).collect();
+        assert_eq!(stocking, vec!["Harry's"]);
+        assert!(!campaign.stocks_wished_item(&campaign.shops[0]));
+        assert!(campaign.stocks_wished_item(&campaign.shops[1]));
+    }
+
+    #[test]
+    fn wish_report_lists_each_wished_item_once() {
+        let mut folder = CampaignFolder::empty("New Campaign".into());
+        folder.item_registry.add(ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()));
+        folder.shops = vec![Shop::with_inventory(
+            "Harry's".into(),
+            None,
+            vec![StockedItem { identifier: "Mantel".into(), stock: Stock::Infinite, days_sold_out: 0 }],
+            RestockRules::default(),
+        )];
+        folder.characters = vec![
+            Character { name: "Gandalf".into(), wish_list: vec!["Mantel".into(), "Stab".into()] },
+            Character { name: "Frodo".into(), wish_list: vec!["Mantel".into()] },
+        ];
+
+        let report = folder.into_campaign().wish_report();
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].identifier, "Mantel");
+        assert_eq!(report[0].wished_by, vec!["Gandalf", "Frodo"]);
+        assert_eq!(report[0].stocked_in, vec![("Harry's".to_string(), Stock::Infinite)]);
+        assert_eq!(report[1].item_type, None);
+        assert!(report[1].stocked_in.is_empty());
+    }
+
+    #[test]
+    fn saved_campaign_loads_again() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-save-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut folder = CampaignFolder::empty("Saved Campaign".into());
+        folder.meta.date.day = 42;
+        folder.item_registry.add(ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()));
+        folder.shops = vec![sold_mantel_shop("Tina's", Some(1)), sold_mantel_shop("Harry's", None)];
+
+        let mut campaign = folder.into_campaign();
+        campaign.save(&dir)?;
+
+        campaign.shops.remove(1);
+        campaign.shops[0].name = "Tina's New Shop".into();
+        campaign.ledger.push(Stamped::new(
+            campaign.date,
+            ShopChangelog { shop_name: "Tina's New Shop".into(), changes: vec![InventoryChange::Returned("Mantel".into())] },
+        ));
+        campaign.save(&dir)?;
+
+        let loaded = load_campaign_folder(&dir)?.into_campaign();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert_eq!(loaded.name, "Saved Campaign");
+        assert_eq!(loaded.date.day, 42);
+        assert_eq!(loaded.registry, campaign.registry);
+        assert_eq!(loaded.shops, campaign.shops);
+        assert_eq!(loaded.files, campaign.files);
+        assert_eq!(loaded.ledger, campaign.ledger);
+        Ok(())
+    }
 
+    #[test]
+    fn dates_that_are_not_on_the_calendar_are_refused() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-bad-date-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut campaign = CampaignFolder::empty("Hand-edited".into()).into_campaign();
+        campaign.date.day = 400;
+        campaign.save(&dir)?;
+
+        let loaded = load_campaign_folder(&dir);
+        std::fs::remove_dir_all(&dir)?;
+
+        assert!(loaded.is_err());
+        Ok(())
+    }
+}
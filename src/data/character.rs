@@ -5,31 +5,23 @@ use crate::registry::ItemRegistry;
 
 use super::item::{Item, ItemIdentifier};
 
-#[derive(Debug, Clone)]
-pub struct Character<'a> {
-    pub state: FileCharacter,
-    pub registry: &'a ItemRegistry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    pub wish_list: Vec<ItemIdentifier>,
 }
 
-impl<'a> Character<'a> {
-    pub fn name(&'a self) -> &'a String {
-        &self.state.name
+impl Character {
+    pub fn name(&self) -> &String {
+        &self.name
     }
 
-    // maybe cache this?
-    pub fn wish_list(&self) -> Vec<Item<'a>> {
-        self.state.wish_list
+    pub fn wish_list<'r>(&self, registry: &'r ItemRegistry) -> Vec<Item<'r>> {
+        self.wish_list
             .iter()
-            .map(|id| self.registry
+            .map(|id| registry
                 .get(id)
                 .map_or_else(|| Item::Unresolved(id.clone()), |item| Item::Concrete(item)))
             .collect()
     }
 }
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileCharacter {
-    pub name: String,
-    pub wish_list: Vec<ItemIdentifier>,
-}
-
@@ -3,7 +3,9 @@ use std::{
     ops::{Add, Mul, Neg, Sub},
 };
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub struct GoldAmount {
     copper: isize,
 }
@@ -62,6 +64,14 @@ impl GoldAmount {
     }
 }
 
+/// Rolls a dice expression for a price in gold, e.g. `2d10 * 1000`. `None` if it is no valid expression.
+pub fn roll_gold(expression: &str) -> Option<GoldAmount> {
+    let expression: tyche::Expr = expression.parse().ok()?;
+    let rolled = expression.eval(&mut tyche::dice::roller::FastRand::default()).ok()?;
+
+    rolled.calc().ok().map(|value| (value as isize).gold())
+}
+
 pub trait AsGoldCurrency {
     fn gold(&self) -> GoldAmount;
     fn silver(&self) -> GoldAmount;
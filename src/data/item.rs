@@ -1,14 +1,40 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::config::settings::Language;
 
 pub type ItemIdentifier = String;
 
+/// Lowercase ASCII letters and digits, everything else collapsed into single dashes.
+/// German umlauts and ß are transliterated.
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .replace('ä', "ae")
+        .replace('ö', "oe")
+        .replace('ü', "ue")
+        .replace('ß', "ss")
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Item<'a> {
     Unresolved(ItemIdentifier),
     Concrete(&'a ItemType)
 }
 
+/// Name and details of an item in another language than its base text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ItemTranslation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ItemType {
     pub identifier: ItemIdentifier,
@@ -16,6 +42,26 @@ pub struct ItemType {
     pub details: String,
     pub rarity: Rarity,
     pub category: ItemCategory,
+    /// Name and details in other languages. The base text is used for every language without one.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<Language, ItemTranslation>,
+    /// Where the item was imported from, to sync it again later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ApiReference>,
+}
+
+/// An item of the dnd5eapi, as the API refers to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiReference {
+    pub index: String,
+    /// Sub url of the item, e.g. `/api/magic-items/armor-1`.
+    pub url: String,
+    /// Sub urls of the variants of the item, e.g. the `+1`, `+2` and `+3` of an armor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    /// Whether the item is a variant of another one.
+    #[serde(default)]
+    pub variant: bool,
 }
 
 impl ItemType {
@@ -26,11 +72,49 @@ impl ItemType {
             rarity,
             category,
             details,
+            translations: BTreeMap::new(),
+            source: None,
         }
     }
+
+    pub fn with_translation(mut self, language: Language, translation: ItemTranslation) -> Self {
+        self.translations.insert(language, translation);
+        self
+    }
+
+    pub fn name_in(&self, language: Language) -> &str {
+        self.translations
+            .get(&language)
+            .and_then(|translation| translation.name.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    pub fn details_in(&self, language: Language) -> &str {
+        self.translations
+            .get(&language)
+            .and_then(|translation| translation.details.as_deref())
+            .unwrap_or(&self.details)
+    }
+
+    /// The base name followed by every translated one.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str())
+            .chain(self.translations.values().filter_map(|translation| translation.name.as_deref()))
+    }
+
+    /// Takes everything but the translations from `imported`, so a fresh import replaces the
+    /// base text while the translations kept in the local YAML stay.
+    pub fn rebase(&mut self, imported: ItemType) {
+        let translations = std::mem::take(&mut self.translations);
+        *self = ItemType {
+            translations: imported.translations.into_iter().chain(translations).collect(),
+            ..imported
+        };
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Ordered from the most common to the rarest.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, EnumIter)]
 pub enum Rarity {
     Common,
     Uncommon,
@@ -40,11 +124,18 @@ pub enum Rarity {
     Artifact,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumIter)]
 pub enum ItemCategory {
     WondrousItem,
     SimpleWeapon,
     Wand,
+    Armor,
+    Weapon,
+    Potion,
+    Ring,
+    Rod,
+    Scroll,
+    Staff,
 }
 
 impl Display for ItemCategory {
@@ -53,6 +144,13 @@ impl Display for ItemCategory {
             ItemCategory::WondrousItem => "Wondrous Item",
             ItemCategory::SimpleWeapon => "Simple Weapon",
             ItemCategory::Wand => "Wand",
+            ItemCategory::Armor => "Armor",
+            ItemCategory::Weapon => "Weapon",
+            ItemCategory::Potion => "Potion",
+            ItemCategory::Ring => "Ring",
+            ItemCategory::Rod => "Rod",
+            ItemCategory::Scroll => "Scroll",
+            ItemCategory::Staff => "Staff",
         };
         f.write_str(s)
     }
@@ -73,7 +171,29 @@ impl Display for Rarity {
     }
 }
 
+/// Lowercase letters and digits only, so `Very Rare`, `very-rare` and `VeryRare` are the same.
+fn name_key(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+impl ItemCategory {
+    /// Reads a category like the SRD names it, e.g. `Wondrous Items` or `Simple Weapons`.
+    pub fn from_name(name: &str) -> Option<ItemCategory> {
+        let key = name_key(name);
+        let key = key.strip_suffix('s').unwrap_or(&key);
+
+        ItemCategory::iter().find(|category| name_key(&category.to_string()) == key)
+    }
+}
+
 impl Rarity {
+    /// Reads a rarity like the SRD names it, e.g. `Very Rare`.
+    pub fn from_name(name: &str) -> Option<Rarity> {
+        let key = name_key(name);
+        Rarity::iter().find(|rarity| name_key(&rarity.to_string()) == key)
+    }
+
+    /// One letter per rarity, so rarities can be told apart without their colors.
     pub fn as_symbol(&self) -> String {
         match self {
             Rarity::Common => "C".into(),
@@ -82,8 +202,83 @@ impl Rarity {
             Rarity::Rare => "R".into(),
             Rarity::VeryRare => "V".into(),
             Rarity::Legendary => "L".into(),
-            _ => "I am to lazy".into(),
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_are_lowercase_and_dashed() {
+        assert_eq!(slugify("Mantel der Arschlecken 3,50"), "mantel-der-arschlecken-3-50");
+        assert_eq!(slugify("  Tina's  Shop! "), "tina-s-shop");
+        assert_eq!(slugify("Ärger über Straßen"), "aerger-ueber-strassen");
+    }
+
+    fn cape() -> ItemType {
+        ItemType::new("Cape of the Mountebank".into(), Rarity::Rare, ItemCategory::WondrousItem, "Smells of brimstone.".into())
+            .with_translation(
+                Language::German,
+                ItemTranslation { name: Some("Umhang des Gauklers".into()), details: None },
+            )
+    }
+
+    #[test]
+    fn names_are_read_regardless_of_spelling() {
+        assert_eq!(Rarity::from_name("Very Rare"), Some(Rarity::VeryRare));
+        assert_eq!(Rarity::from_name("very-rare"), Some(Rarity::VeryRare));
+        assert_eq!(Rarity::from_name("uncommon"), Some(Rarity::Uncommon));
+        assert_eq!(Rarity::from_name("Varies"), None);
+
+        assert_eq!(ItemCategory::from_name("Wondrous Items"), Some(ItemCategory::WondrousItem));
+        assert_eq!(ItemCategory::from_name("simple weapons"), Some(ItemCategory::SimpleWeapon));
+        assert_eq!(ItemCategory::from_name("Wand"), Some(ItemCategory::Wand));
+        assert_eq!(ItemCategory::from_name("Armor"), Some(ItemCategory::Armor));
+        assert_eq!(ItemCategory::from_name("Cutlery"), None);
+    }
+
+    #[test]
+    fn every_rarity_has_its_own_symbol() {
+        let symbols: std::collections::HashSet<_> = Rarity::iter().map(|rarity| rarity.as_symbol()).collect();
+
+        assert_eq!(symbols.len(), Rarity::iter().count());
+    }
+
+    #[test]
+    fn translations_fall_back_to_the_base_text() {
+        let cape = cape();
+
+        assert_eq!(cape.name_in(Language::German), "Umhang des Gauklers");
+        assert_eq!(cape.details_in(Language::German), "Smells of brimstone.");
+        assert_eq!(cape.name_in(Language::English), "Cape of the Mountebank");
+    }
+
+    #[test]
+    fn translations_are_read_from_yaml() {
+        let yaml = "identifier: cape\nname: Cape\ndetails: ''\nrarity: Rare\ncategory: WondrousItem\n\
+                    translations:\n  german:\n    name: Umhang\n";
+        let cape: ItemType = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(cape.name_in(Language::German), "Umhang");
+        assert!(!serde_yaml::to_string(&ItemType::new("Cape".into(), Rarity::Rare, ItemCategory::Wand, String::new()))
+            .unwrap()
+            .contains("translations"));
+    }
+
+    #[test]
+    fn rebasing_keeps_local_translations() {
+        let mut cape = cape();
+        cape.rebase(ItemType::new(
+            "Cape of the Mountebank".into(),
+            Rarity::Rare,
+            ItemCategory::WondrousItem,
+            "While wearing it, you can cast dimension door.".into(),
+        ));
+
+        assert_eq!(cape.details, "While wearing it, you can cast dimension door.");
+        assert_eq!(cape.name_in(Language::German), "Umhang des Gauklers");
+    }
+}
@@ -2,4 +2,6 @@ pub mod gold;
 pub mod shop;
 pub mod character;
 pub mod item;
-pub mod campaign;
\ No newline at end of file
+pub mod campaign;
+pub mod calendar;
+pub mod weather;
@@ -1,48 +1,106 @@
-use rand::seq::SliceRandom;
+use rand::{seq::{IteratorRandom, SliceRandom}, Rng};
 use serde::{Deserialize, Serialize};
-use std::num::NonZero;
+use std::{fmt::Display, num::NonZero};
 
-use super::item::{ItemIdentifier, ItemType};
+use crate::registry::ItemRegistry;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::item::{ItemIdentifier, Rarity};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Stock {
     Stocked(NonZero<u32>),
     Sold,
     Infinite
 }
 
-#[derive(Debug, Clone)]
-pub struct StockedItem<'a> {
-    pub item_type: &'a ItemType,
-    pub stock: Stock
+impl Stock {
+    pub fn is_available(&self) -> bool {
+        !matches!(self, Stock::Sold)
+    }
+
+    /// The stock after one item has been sold.
+    pub fn after_sale(&self) -> Stock {
+        match self {
+            Stock::Stocked(count) => NonZero::new(count.get() - 1).map_or(Stock::Sold, Stock::Stocked),
+            Stock::Sold => Stock::Sold,
+            Stock::Infinite => Stock::Infinite,
+        }
+    }
+
+    /// The stock after one more item came in. An infinite stock stays infinite.
+    pub fn one_more(&self) -> Stock {
+        match self {
+            Stock::Stocked(count) => Stock::Stocked(count.saturating_add(1)),
+            Stock::Sold => Stock::Stocked(NonZero::<u32>::MIN),
+            Stock::Infinite => Stock::Infinite,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct Shop<'a> {
-    pub name: String,
-    short_name: Option<String>,
-    inventory: Vec<StockedItem<'a>>,
+impl Display for Stock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stock::Stocked(count) => write!(f, "{count}"),
+            Stock::Sold => f.write_str("Sold"),
+            Stock::Infinite => f.write_str("∞"),
+        }
+    }
+}
+
+/// Rules that decide how a shop refills its inventory while in-game time passes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestockRules {
+    /// Sold items are back in stock after this many days. `None` means they never return.
+    #[serde(default)]
+    pub return_after_days: Option<u32>,
+    /// Chance (0.0 to 1.0) per day that the shop acquires a random item from the registry.
+    #[serde(default)]
+    pub new_item_chance: f64,
+    /// Rarities a randomly acquired item may have. Empty means every rarity is allowed.
+    #[serde(default)]
+    pub allowed_rarities: Vec<Rarity>,
+    /// Maximum number of available inventory entries.
+    #[serde(default)]
+    pub stock_cap: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InventoryChange {
+    Returned(ItemIdentifier),
+    Added(ItemIdentifier),
+    Removed(ItemIdentifier),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileStockedItem {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShopChangelog {
+    pub shop_name: String,
+    pub changes: Vec<InventoryChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockedItem {
     pub identifier: ItemIdentifier,
-    pub stock: Stock
+    pub stock: Stock,
+    #[serde(default)]
+    pub days_sold_out: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileShop {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shop {
     pub name: String,
     pub short_name: Option<String>,
-    pub inventory: Vec<FileStockedItem>,
+    inventory: Vec<StockedItem>,
+    #[serde(default)]
+    pub restock: RestockRules,
 }
 
-impl<'a> Shop<'a> {
+impl Shop {
     pub fn new(name: String) -> Self {
         Self {
             name,
             short_name: None,
             inventory: vec![],
+            restock: RestockRules::default(),
         }
     }
 
@@ -51,14 +109,30 @@ impl<'a> Shop<'a> {
             name,
             short_name: Some(short_name),
             inventory: vec![],
+            restock: RestockRules::default(),
+        }
+    }
+
+    pub fn with_inventory(
+        name: String,
+        short_name: Option<String>,
+        inventory: Vec<StockedItem>,
+        restock: RestockRules,
+    ) -> Self {
+        Self {
+            name,
+            short_name,
+            inventory,
+            restock,
         }
     }
 
+    /// The short name, or the first 20 characters of the name while there is none.
     pub fn short_name(&self) -> String {
         if let Some(name) = &self.short_name {
             name.clone()
         } else {
-            self.name[..20].into()
+            self.name.chars().take(20).collect()
         }
     }
 
@@ -66,16 +140,220 @@ impl<'a> Shop<'a> {
         self.inventory.as_slice()
     }
 
+    pub fn stock_of(&self, identifier: &ItemIdentifier) -> Option<&Stock> {
+        self.inventory
+            .iter()
+            .find(|stocked| stocked.identifier == *identifier)
+            .map(|stocked| &stocked.stock)
+    }
+
+    /// Whether the shop lists the item and has at least one left.
+    pub fn has_in_stock(&self, identifier: &ItemIdentifier) -> bool {
+        self.stock_of(identifier).is_some_and(Stock::is_available)
+    }
+
+    /// Sets the stock of an item that is part of the inventory.
+    /// Returns the previous stock or `None` if the shop does not list the item.
+    pub fn set_stock(&mut self, identifier: &ItemIdentifier, stock: Stock) -> Option<Stock> {
+        let stocked = self
+            .inventory
+            .iter_mut()
+            .find(|stocked| stocked.identifier == *identifier)?;
+
+        stocked.days_sold_out = 0;
+        Some(std::mem::replace(&mut stocked.stock, stock))
+    }
+
+    /// Puts a new entry into the inventory at `index` (or at the end if it is past the end).
+    /// Returns `false` if the shop already lists the item.
+    pub fn insert_item(&mut self, index: usize, stocked: StockedItem) -> bool {
+        if self.stock_of(&stocked.identifier).is_some() {
+            return false;
+        }
+
+        self.inventory.insert(index.min(self.inventory.len()), stocked);
+        true
+    }
+
+    /// Removes the entry of an item and returns its position and the entry itself.
+    pub fn remove_item(&mut self, identifier: &ItemIdentifier) -> Option<(usize, StockedItem)> {
+        let idx = self
+            .inventory
+            .iter()
+            .position(|stocked| stocked.identifier == *identifier)?;
+
+        Some((idx, self.inventory.remove(idx)))
+    }
+
     pub fn produce_offer(&self, amount: u8) -> Vec<&StockedItem> {
         self.inventory
             .choose_multiple(&mut rand::thread_rng(), amount.into())
             .collect()
     }
+
+    fn available_count(&self) -> usize {
+        self.inventory
+            .iter()
+            .filter(|item| item.stock.is_available())
+            .count()
+    }
+
+    fn has_room(&self) -> bool {
+        self.restock
+            .stock_cap
+            .is_none_or(|cap| self.available_count() < cap)
+    }
+
+    /// Lets `days` in-game days pass and applies the restock rules of this shop.
+    /// Returns what the shop gained or lost in the meantime.
+    pub fn advance_days<R: Rng>(
+        &mut self,
+        days: u32,
+        registry: &ItemRegistry,
+        rng: &mut R,
+    ) -> ShopChangelog {
+        let mut changes = vec![];
+
+        for _ in 0..days {
+            self.return_sold_items(&mut changes);
+
+            let chance = self.restock.new_item_chance.clamp(0.0, 1.0);
+            if rng.gen_bool(chance) {
+                self.acquire_random_item(registry, rng, &mut changes);
+            }
+        }
+
+        self.enforce_stock_cap(rng, &mut changes);
+
+        ShopChangelog {
+            shop_name: self.name.clone(),
+            changes,
+        }
+    }
+
+    fn return_sold_items(&mut self, changes: &mut Vec<InventoryChange>) {
+        for idx in 0..self.inventory.len() {
+            if self.inventory[idx].stock.is_available() {
+                continue;
+            }
+
+            self.inventory[idx].days_sold_out += 1;
+
+            let is_due = self
+                .restock
+                .return_after_days
+                .is_some_and(|days| self.inventory[idx].days_sold_out >= days);
+
+            if is_due && self.has_room() {
+                let item = &mut self.inventory[idx];
+                item.stock = Stock::Stocked(NonZero::<u32>::MIN);
+                item.days_sold_out = 0;
+                changes.push(InventoryChange::Returned(item.identifier.clone()));
+            }
+        }
+    }
+
+    fn acquire_random_item<R: Rng>(
+        &mut self,
+        registry: &ItemRegistry,
+        rng: &mut R,
+        changes: &mut Vec<InventoryChange>,
+    ) {
+        if !self.has_room() {
+            return;
+        }
+
+        let candidate = registry
+            .items()
+            .iter()
+            .filter(|item_type| {
+                self.restock.allowed_rarities.is_empty()
+                    || self.restock.allowed_rarities.contains(&item_type.rarity)
+            })
+            .filter(|item_type| {
+                !self.inventory.iter().any(|stocked| {
+                    stocked.stock.is_available() && stocked.identifier == item_type.identifier
+                })
+            })
+            .choose(rng);
+
+        let Some(item_type) = candidate else {
+            return;
+        };
+
+        let sold_entry = self
+            .inventory
+            .iter_mut()
+            .find(|stocked| stocked.identifier == item_type.identifier);
+
+        if let Some(entry) = sold_entry {
+            entry.stock = Stock::Stocked(NonZero::<u32>::MIN);
+            entry.days_sold_out = 0;
+        } else {
+            self.inventory.push(StockedItem {
+                identifier: item_type.identifier.clone(),
+                stock: Stock::Stocked(NonZero::<u32>::MIN),
+                days_sold_out: 0,
+            });
+        }
+
+        changes.push(InventoryChange::Added(item_type.identifier.clone()));
+    }
+
+    fn enforce_stock_cap<R: Rng>(&mut self, rng: &mut R, changes: &mut Vec<InventoryChange>) {
+        let Some(cap) = self.restock.stock_cap else {
+            return;
+        };
+
+        while self.available_count() > cap {
+            let available: Vec<usize> = self
+                .inventory
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.stock.is_available())
+                .map(|(idx, _)| idx)
+                .collect();
+
+            let Some(&idx) = available.choose(rng) else {
+                return;
+            };
+
+            let removed = self.inventory.remove(idx);
+            changes.push(InventoryChange::Removed(removed.identifier));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Shop;
+    use std::num::NonZero;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        data::item::{ItemCategory, ItemType, Rarity},
+        registry::ItemRegistry,
+    };
+
+    use super::{InventoryChange, RestockRules, Shop, Stock, StockedItem};
+
+    fn registry() -> ItemRegistry {
+        ItemRegistry {
+            items: vec![
+                ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()),
+                ItemType::new("Ring".into(), Rarity::Common, ItemCategory::WondrousItem, "".into()),
+                ItemType::new("Stab".into(), Rarity::Legendary, ItemCategory::Wand, "".into()),
+            ],
+        }
+    }
+
+    fn stocked(item_type: &ItemType, stock: Stock) -> StockedItem {
+        StockedItem {
+            identifier: item_type.identifier.clone(),
+            stock,
+            days_sold_out: 0,
+        }
+    }
 
     #[test]
     fn new_shop_is_empty() {
@@ -83,9 +361,147 @@ mod tests {
         assert_eq!(s.get_inventory().len(), 0)
     }
 
+    #[test]
+    fn short_names_fall_back_to_the_start_of_the_name() {
+        assert_eq!(Shop::new("Tina's".into()).short_name(), "Tina's");
+        assert_eq!(Shop::new("Schmiede Würfelglück am Markt".into()).short_name(), "Schmiede Würfelglück");
+        assert_eq!(Shop::new_with_shorthand("Harry's Hardware".into(), "HH".into()).short_name(), "HH");
+    }
+
     #[test]
     fn shop_offer_is_less_or_equal_to_demand() {
         let s = Shop::new("Tina's".to_string());
         assert!(s.produce_offer(3).len() <= 3)
     }
+
+    #[test]
+    fn selling_the_last_item_marks_it_sold() {
+        let two = Stock::Stocked(NonZero::new(2).unwrap());
+
+        assert_eq!(two.after_sale(), Stock::Stocked(NonZero::<u32>::MIN));
+        assert_eq!(two.after_sale().after_sale(), Stock::Sold);
+        assert_eq!(Stock::Infinite.after_sale(), Stock::Infinite);
+        assert_eq!(Stock::Sold.one_more().one_more(), two);
+    }
+
+    #[test]
+    fn items_are_listed_once() {
+        let registry = registry();
+        let [mantel, ring, _] = registry.items() else { unreachable!() };
+        let mut shop = Shop::new("Tina's".into());
+
+        assert!(shop.insert_item(5, stocked(mantel, Stock::Infinite)));
+        assert!(!shop.insert_item(0, stocked(mantel, Stock::Sold)));
+        assert!(shop.insert_item(0, stocked(ring, Stock::Sold)));
+
+        assert_eq!(shop.get_inventory()[0].identifier, "Ring");
+        assert_eq!(shop.remove_item(&"Mantel".into()).map(|(idx, _)| idx), Some(1));
+        assert_eq!(shop.remove_item(&"Mantel".into()), None);
+    }
+
+    #[test]
+    fn set_stock_returns_previous_stock() {
+        let registry = registry();
+        let mut shop = Shop::with_inventory(
+            "Tina's".into(),
+            None,
+            vec![stocked(&registry.items()[0], Stock::Infinite)],
+            RestockRules::default(),
+        );
+
+        assert_eq!(shop.set_stock(&"Mantel".into(), Stock::Sold), Some(Stock::Infinite));
+        assert_eq!(shop.stock_of(&"Mantel".into()), Some(&Stock::Sold));
+        assert_eq!(shop.set_stock(&"Ring".into(), Stock::Sold), None);
+    }
+
+    #[test]
+    fn sold_item_returns_after_configured_days() {
+        let registry = registry();
+        let rules = RestockRules {
+            return_after_days: Some(3),
+            ..Default::default()
+        };
+        let mut shop = Shop::with_inventory(
+            "Tina's".into(),
+            None,
+            vec![stocked(&registry.items()[0], Stock::Sold)],
+            rules,
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let log = shop.advance_days(2, &registry, &mut rng);
+        assert!(log.changes.is_empty());
+        assert!(!shop.get_inventory()[0].stock.is_available());
+
+        let log = shop.advance_days(1, &registry, &mut rng);
+        assert_eq!(log.changes, vec![InventoryChange::Returned("Mantel".into())]);
+        assert!(shop.get_inventory()[0].stock.is_available());
+    }
+
+    #[test]
+    fn sold_item_stays_sold_without_return_rule() {
+        let registry = registry();
+        let mut shop = Shop::with_inventory(
+            "Tina's".into(),
+            None,
+            vec![stocked(&registry.items()[0], Stock::Sold)],
+            RestockRules::default(),
+        );
+
+        let log = shop.advance_days(100, &registry, &mut StdRng::seed_from_u64(0));
+
+        assert!(log.changes.is_empty());
+        assert!(!shop.get_inventory()[0].stock.is_available());
+    }
+
+    #[test]
+    fn random_items_respect_rarity_and_cap() {
+        let registry = registry();
+        let rules = RestockRules {
+            new_item_chance: 1.0,
+            allowed_rarities: vec![Rarity::Common, Rarity::Legendary],
+            stock_cap: Some(2),
+            ..Default::default()
+        };
+        let mut shop = Shop::with_inventory("Tina's".into(), None, vec![], rules);
+
+        let log = shop.advance_days(10, &registry, &mut StdRng::seed_from_u64(7));
+
+        assert_eq!(log.changes.len(), 2);
+        assert_eq!(shop.get_inventory().len(), 2);
+        assert!(shop
+            .get_inventory()
+            .iter()
+            .all(|stocked| registry.get(&stocked.identifier).is_some_and(|item_type| item_type.rarity != Rarity::Rare)));
+    }
+
+    #[test]
+    fn surplus_above_cap_is_removed() {
+        let registry = registry();
+        let rules = RestockRules {
+            stock_cap: Some(1),
+            ..Default::default()
+        };
+        let mut shop = Shop::with_inventory(
+            "Tina's".into(),
+            None,
+            registry
+                .items()
+                .iter()
+                .map(|item_type| stocked(item_type, Stock::Stocked(NonZero::<u32>::MIN)))
+                .collect(),
+            rules,
+        );
+
+        let log = shop.advance_days(1, &registry, &mut StdRng::seed_from_u64(0));
+
+        assert_eq!(shop.get_inventory().len(), 1);
+        assert_eq!(
+            log.changes
+                .iter()
+                .filter(|change| matches!(change, InventoryChange::Removed(_)))
+                .count(),
+            2
+        );
+    }
 }
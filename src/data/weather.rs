@@ -0,0 +1,317 @@
+use std::fmt::Display;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::calendar::{Calendar, CampaignDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        };
+        f.write_str(s)
+    }
+}
+
+impl Season {
+    /// Derives the season from the position of `date` within its year.
+    /// The year starts in the middle of winter, like the Calendar of Harptos does.
+    pub fn from_date(calendar: &Calendar, date: &CampaignDate) -> Self {
+        let days_in_year = calendar.days_in_year(date.year).max(1) as f64;
+        let progress = date.day as f64 / days_in_year;
+
+        match progress {
+            p if p < 2.0 / 12.0 => Season::Winter,
+            p if p < 5.0 / 12.0 => Season::Spring,
+            p if p < 8.0 / 12.0 => Season::Summer,
+            p if p < 11.0 / 12.0 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherEvent {
+    pub name: String,
+    /// Chance (0.0 to 1.0) per day.
+    pub chance: f64,
+}
+
+/// Weather table for a single season of a climate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonTable {
+    /// Lowest and highest daily temperature in °C.
+    pub temperature: (i32, i32),
+    /// Chance (0.0 to 1.0) of any precipitation on a day.
+    pub precipitation_chance: f64,
+    /// Chance (0.0 to 1.0) that the wind is strong or worse.
+    pub strong_wind_chance: f64,
+    #[serde(default)]
+    pub events: Vec<WeatherEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Climate {
+    pub region: String,
+    pub spring: SeasonTable,
+    pub summer: SeasonTable,
+    pub autumn: SeasonTable,
+    pub winter: SeasonTable,
+    /// Fixes the season instead of deriving it from the campaign calendar.
+    #[serde(default)]
+    pub season: Option<Season>,
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Self::temperate()
+    }
+}
+
+impl Climate {
+    /// A temperate climate like the Sword Coast.
+    pub fn temperate() -> Self {
+        let event = |name: &str, chance: f64| WeatherEvent {
+            name: name.into(),
+            chance,
+        };
+
+        Self {
+            region: "Temperate".into(),
+            spring: SeasonTable {
+                temperature: (2, 18),
+                precipitation_chance: 0.4,
+                strong_wind_chance: 0.2,
+                events: vec![event("Thunderstorm", 0.05)],
+            },
+            summer: SeasonTable {
+                temperature: (14, 32),
+                precipitation_chance: 0.2,
+                strong_wind_chance: 0.1,
+                events: vec![event("Thunderstorm", 0.1), event("Heat wave", 0.05)],
+            },
+            autumn: SeasonTable {
+                temperature: (0, 16),
+                precipitation_chance: 0.45,
+                strong_wind_chance: 0.3,
+                events: vec![event("Thick fog", 0.1)],
+            },
+            winter: SeasonTable {
+                temperature: (-15, 4),
+                precipitation_chance: 0.35,
+                strong_wind_chance: 0.25,
+                events: vec![event("Blizzard", 0.05), event("Freezing fog", 0.05)],
+            },
+            season: None,
+        }
+    }
+
+    pub fn table(&self, season: Season) -> &SeasonTable {
+        match season {
+            Season::Spring => &self.spring,
+            Season::Summer => &self.summer,
+            Season::Autumn => &self.autumn,
+            Season::Winter => &self.winter,
+        }
+    }
+
+    pub fn season_at(&self, calendar: &Calendar, date: &CampaignDate) -> Season {
+        self.season
+            .unwrap_or_else(|| Season::from_date(calendar, date))
+    }
+
+    /// Rolls the weather of the day `date` falls on.
+    /// The same day always yields the same weather, so forecasts stay stable.
+    pub fn weather_on(&self, calendar: &Calendar, date: &CampaignDate) -> Weather {
+        let season = self.season_at(calendar, date);
+        let seed = ((date.year as i64 as u64) << 16) ^ date.day as u64;
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        Weather::roll(season, self.table(season), &mut rng)
+    }
+
+    /// Weather for `days` consecutive days, starting with the day of `date`.
+    pub fn forecast(&self, calendar: &Calendar, date: &CampaignDate, days: u32) -> Vec<(CampaignDate, Weather)> {
+        (0..days)
+            .map(|offset| {
+                let day = calendar.advance_days(date, offset);
+                (day, self.weather_on(calendar, &day))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precipitation {
+    None,
+    LightRain,
+    HeavyRain,
+    LightSnow,
+    HeavySnow,
+}
+
+impl Display for Precipitation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Precipitation::None => "Dry",
+            Precipitation::LightRain => "Light rain",
+            Precipitation::HeavyRain => "Heavy rain",
+            Precipitation::LightSnow => "Light snow",
+            Precipitation::HeavySnow => "Heavy snow",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wind {
+    Calm,
+    Breeze,
+    Strong,
+    Gale,
+}
+
+impl Display for Wind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Wind::Calm => "Calm",
+            Wind::Breeze => "Breeze",
+            Wind::Strong => "Strong wind",
+            Wind::Gale => "Gale",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    pub season: Season,
+    pub temperature: i32,
+    pub precipitation: Precipitation,
+    pub wind: Wind,
+    pub events: Vec<String>,
+}
+
+impl Weather {
+    pub fn roll<R: Rng>(season: Season, table: &SeasonTable, rng: &mut R) -> Self {
+        let (low, high) = table.temperature;
+        let temperature = rng.gen_range(low.min(high)..=high.max(low));
+
+        let precipitation = if rng.gen_bool(table.precipitation_chance.clamp(0.0, 1.0)) {
+            let heavy = rng.gen_bool(0.3);
+            match (temperature <= 0, heavy) {
+                (true, true) => Precipitation::HeavySnow,
+                (true, false) => Precipitation::LightSnow,
+                (false, true) => Precipitation::HeavyRain,
+                (false, false) => Precipitation::LightRain,
+            }
+        } else {
+            Precipitation::None
+        };
+
+        let wind = if rng.gen_bool(table.strong_wind_chance.clamp(0.0, 1.0)) {
+            if rng.gen_bool(0.2) {
+                Wind::Gale
+            } else {
+                Wind::Strong
+            }
+        } else if rng.gen_bool(0.5) {
+            Wind::Breeze
+        } else {
+            Wind::Calm
+        };
+
+        let events = table
+            .events
+            .iter()
+            .filter(|event| rng.gen_bool(event.chance.clamp(0.0, 1.0)))
+            .map(|event| event.name.clone())
+            .collect();
+
+        Self {
+            season,
+            temperature,
+            precipitation,
+            wind,
+            events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::calendar::{Calendar, CampaignDate};
+
+    use super::{Climate, Precipitation, Season};
+
+    #[test]
+    fn season_follows_the_calendar() {
+        let calendar = Calendar::harptos();
+        let date = |day| CampaignDate {
+            year: 1491,
+            day,
+            hour: 12,
+        };
+
+        assert_eq!(Season::from_date(&calendar, &date(0)), Season::Winter);
+        assert_eq!(Season::from_date(&calendar, &date(100)), Season::Spring);
+        assert_eq!(Season::from_date(&calendar, &date(190)), Season::Summer);
+        assert_eq!(Season::from_date(&calendar, &date(280)), Season::Autumn);
+        assert_eq!(Season::from_date(&calendar, &date(350)), Season::Winter);
+    }
+
+    #[test]
+    fn manual_season_overrides_calendar() {
+        let calendar = Calendar::harptos();
+        let climate = Climate {
+            season: Some(Season::Summer),
+            ..Climate::temperate()
+        };
+
+        let weather = climate.weather_on(&calendar, &CampaignDate::default());
+
+        assert_eq!(weather.season, Season::Summer);
+    }
+
+    #[test]
+    fn weather_is_stable_for_a_day() {
+        let calendar = Calendar::harptos();
+        let climate = Climate::temperate();
+        let morning = CampaignDate::default();
+        let evening = CampaignDate { hour: 22, ..morning };
+
+        assert_eq!(
+            climate.weather_on(&calendar, &morning),
+            climate.weather_on(&calendar, &evening)
+        );
+    }
+
+    #[test]
+    fn weather_stays_within_season_table() {
+        let calendar = Calendar::harptos();
+        let climate = Climate::temperate();
+
+        for (_, weather) in climate.forecast(&calendar, &CampaignDate::default(), 60) {
+            let (low, high) = climate.table(weather.season).temperature;
+            assert!((low..=high).contains(&weather.temperature));
+
+            let is_snow = matches!(
+                weather.precipitation,
+                Precipitation::LightSnow | Precipitation::HeavySnow
+            );
+            assert!(!is_snow || weather.temperature <= 0);
+        }
+    }
+}
@@ -0,0 +1,361 @@
+pub mod template;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use pulldown_cmark::{Event, Options, Parser, TagEnd};
+
+use crate::{
+    config::settings::{HandoutFormat, PricingStrategy},
+    data::{
+        campaign::Campaign,
+        character::Character,
+        gold::{roll_gold, GoldAmount},
+        item::{slugify, ItemIdentifier, ItemType},
+        shop::Shop,
+    },
+    ui::{
+        display::{category_name, rarity_name, stock_name},
+        translator::{I18nPhrase, I18ner},
+    },
+};
+
+use template::{Context, Template};
+
+pub const HANDOUTS_FOLDER: &str = "handouts";
+
+static MARKDOWN_TEMPLATE: &str = include_str!("../../assets/templates/handout.md");
+static HTML_TEMPLATE: &str = include_str!("../../assets/templates/handout.html");
+static TEXT_TEMPLATE: &str = include_str!("../../assets/templates/handout.txt");
+
+/// One item on a handout, already in the language of the handout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandoutEntry {
+    pub name: String,
+    /// `None` for items the registry does not know.
+    pub rarity: Option<String>,
+    pub category: Option<String>,
+    /// Markdown, like the details of an item.
+    pub details: String,
+    pub price: Option<String>,
+    pub stock: Option<String>,
+}
+
+impl HandoutEntry {
+    fn of(item_type: &ItemType, i18n: &dyn I18ner) -> Self {
+        Self {
+            name: item_type.name_in(i18n.language()).to_string(),
+            rarity: Some(i18n.i18n(rarity_name(&item_type.rarity))),
+            category: Some(i18n.i18n(category_name(&item_type.category))),
+            details: item_type.details_in(i18n.language()).to_string(),
+            price: None,
+            stock: None,
+        }
+    }
+
+    fn unknown(identifier: &ItemIdentifier, i18n: &dyn I18ner) -> Self {
+        Self {
+            name: i18n.i18n_with(I18nPhrase::UnknownItem, &[("item", identifier)]),
+            rarity: None,
+            category: None,
+            details: String::new(),
+            price: None,
+            stock: None,
+        }
+    }
+}
+
+/// A shop inventory, an offer or a wish list to hand out to the players.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handout {
+    pub title: String,
+    pub entries: Vec<HandoutEntry>,
+}
+
+impl Handout {
+    /// Every item the shop has in stock, with prices rolled on the pricing strategy.
+    pub fn inventory(campaign: &Campaign, shop: &Shop, pricing: PricingStrategy, i18n: &dyn I18ner) -> Self {
+        let entries = shop
+            .get_inventory()
+            .iter()
+            .filter(|stocked| stocked.stock.is_available())
+            .map(|stocked| match campaign.registry.get(&stocked.identifier) {
+                Some(item_type) => HandoutEntry {
+                    price: roll_gold(&pricing.cost_expression(item_type)).map(|price| price.to_string()),
+                    stock: Some(stock_name(&stocked.stock, i18n)),
+                    ..HandoutEntry::of(item_type, i18n)
+                },
+                None => HandoutEntry {
+                    stock: Some(stock_name(&stocked.stock, i18n)),
+                    ..HandoutEntry::unknown(&stocked.identifier, i18n)
+                },
+            })
+            .collect();
+
+        Self {
+            title: i18n.i18n_with(I18nPhrase::InventoryHandoutTitle, &[("shop", &shop.name)]),
+            entries,
+        }
+    }
+
+    /// The items of an offer, with the prices that were rolled for them.
+    pub fn offer(title: String, offer: &[(ItemType, Option<GoldAmount>)], i18n: &dyn I18ner) -> Self {
+        let entries = offer
+            .iter()
+            .map(|(item_type, price)| HandoutEntry {
+                price: price.map(|price| price.to_string()),
+                ..HandoutEntry::of(item_type, i18n)
+            })
+            .collect();
+
+        Self { title, entries }
+    }
+
+    pub fn wish_list(campaign: &Campaign, character: &Character, i18n: &dyn I18ner) -> Self {
+        let entries = character
+            .wish_list
+            .iter()
+            .map(|identifier| match campaign.registry.get(identifier) {
+                Some(item_type) => HandoutEntry::of(item_type, i18n),
+                None => HandoutEntry::unknown(identifier, i18n),
+            })
+            .collect();
+
+        Self {
+            title: i18n.i18n_with(I18nPhrase::WishListHandoutTitle, &[("name", &character.name)]),
+            entries,
+        }
+    }
+
+    /// Fills the template. Texts are escaped for HTML and the details are converted to the format.
+    pub fn render(&self, format: HandoutFormat, template: &Template, hide_prices: bool, i18n: &dyn I18ner) -> String {
+        let field = |text: &str| match format {
+            HandoutFormat::Html => escape_html(text),
+            HandoutFormat::Markdown | HandoutFormat::Text => text.to_string(),
+        };
+
+        let items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                Context::new()
+                    .text("name", field(&entry.name))
+                    .optional("rarity", entry.rarity.as_deref().map(field))
+                    .optional("category", entry.category.as_deref().map(field))
+                    .text("details", render_details(&entry.details, format))
+                    .optional("price", entry.price.as_deref().filter(|_| !hide_prices).map(field))
+                    .optional("stock", entry.stock.as_deref().map(field))
+            })
+            .collect();
+
+        let context = Context::new()
+            .text("title", field(&self.title))
+            .text("price_label", field(&i18n.i18n(I18nPhrase::Price)))
+            .text("stock_label", field(&i18n.i18n(I18nPhrase::Stock)))
+            .list("items", items);
+
+        template.render(&context)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Item details often come from the SRD as one paragraph per line, so every line but the rows
+/// of a table becomes its own paragraph, like in the item card.
+fn paragraphs(details: &str) -> String {
+    let mut blocks: Vec<String> = vec![];
+    let mut in_table = false;
+
+    for line in details.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let is_row = line.starts_with('|');
+        match blocks.last_mut() {
+            Some(table) if is_row && in_table => {
+                table.push('\n');
+                table.push_str(line);
+            }
+            _ => blocks.push(line.to_string()),
+        }
+        in_table = is_row;
+    }
+
+    blocks.join("\n\n")
+}
+
+fn render_details(details: &str, format: HandoutFormat) -> String {
+    let markdown = paragraphs(details);
+    let parser = Parser::new_ext(&markdown, Options::ENABLE_TABLES);
+
+    match format {
+        HandoutFormat::Markdown => markdown,
+        HandoutFormat::Html => {
+            let mut html = String::new();
+            pulldown_cmark::html::push_html(&mut html, parser);
+            html.trim_end().to_string()
+        }
+        HandoutFormat::Text => {
+            let mut text = String::new();
+            for event in parser {
+                match event {
+                    Event::Text(content) | Event::Code(content) => text.push_str(&content),
+                    Event::SoftBreak => text.push(' '),
+                    Event::HardBreak | Event::End(TagEnd::TableHead | TagEnd::TableRow) => text.push('\n'),
+                    Event::End(TagEnd::TableCell) => text.push_str("  "),
+                    Event::End(TagEnd::Paragraph | TagEnd::Table) => text.push_str("\n\n"),
+                    _ => {}
+                }
+            }
+            text.trim_end().to_string()
+        }
+    }
+}
+
+/// The template the user keeps in the config directory for the format, e.g. `templates/handout.html`.
+pub fn user_template_path(format: HandoutFormat) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("dnd-simp").join("templates").join(format!("handout.{}", format.extension())))
+}
+
+/// The template at `explicit_path`, else the one of the user, else the built-in one.
+pub fn load_template(format: HandoutFormat, explicit_path: Option<&Path>) -> anyhow::Result<Template> {
+    let path = explicit_path
+        .map(Path::to_path_buf)
+        .or_else(|| user_template_path(format).filter(|path| path.exists()));
+
+    match path {
+        Some(path) => {
+            let source = std::fs::read_to_string(&path).with_context(|| format!("Could not read template {}", path.display()))?;
+            Template::parse(&source).with_context(|| format!("Could not parse template {}", path.display()))
+        }
+        None => Template::parse(match format {
+            HandoutFormat::Markdown => MARKDOWN_TEMPLATE,
+            HandoutFormat::Html => HTML_TEMPLATE,
+            HandoutFormat::Text => TEXT_TEMPLATE,
+        }),
+    }
+}
+
+/// Writes the rendered handout into the handouts folder of the campaign, named after its title.
+/// An older export of the same handout is replaced.
+pub fn write_handout(campaign_folder: &Path, handout: &Handout, format: HandoutFormat, rendered: &str) -> anyhow::Result<PathBuf> {
+    let folder = campaign_folder.join(HANDOUTS_FOLDER);
+    std::fs::create_dir_all(&folder)?;
+
+    let stem = match slugify(&handout.title) {
+        stem if stem.is_empty() => "handout".to_string(),
+        stem => stem,
+    };
+    let path = folder.join(format!("{stem}.{}", format.extension()));
+    std::fs::write(&path, rendered)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::num::NonZero;
+
+    use crate::{
+        config::settings::{Language, Settings},
+        data::{
+            item::{ItemCategory, Rarity},
+            shop::{RestockRules, Stock, StockedItem},
+        },
+        ui::translator::Translator,
+        CampaignFolder,
+    };
+
+    fn handout() -> Handout {
+        let cape = ItemType::new(
+            "Cape <of> the Mountebank".into(),
+            Rarity::Rare,
+            ItemCategory::WondrousItem,
+            "Cast *dimension door*.\nOnce per dawn.".into(),
+        );
+
+        Handout::offer("Veys".into(), &[(cape, Some(GoldAmount::from_gold(750)))], &english())
+    }
+
+    fn english() -> Translator {
+        Translator::new(&Settings {
+            language: Language::English,
+            ..Settings::default()
+        })
+    }
+
+    #[test]
+    fn every_built_in_template_parses() {
+        for format in [HandoutFormat::Markdown, HandoutFormat::Html, HandoutFormat::Text] {
+            Template::parse(match format {
+                HandoutFormat::Markdown => MARKDOWN_TEMPLATE,
+                HandoutFormat::Html => HTML_TEMPLATE,
+                HandoutFormat::Text => TEXT_TEMPLATE,
+            })
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn details_are_converted_per_format() {
+        assert_eq!(render_details("Cast *this*.\nThen that.", HandoutFormat::Markdown), "Cast *this*.\n\nThen that.");
+        assert_eq!(
+            render_details("Cast *this*.\nThen that.", HandoutFormat::Html),
+            "<p>Cast <em>this</em>.</p>\n<p>Then that.</p>"
+        );
+        assert_eq!(render_details("Cast *this*.\nThen that.", HandoutFormat::Text), "Cast this.\n\nThen that.");
+    }
+
+    #[test]
+    fn html_is_escaped_and_prices_can_be_hidden() {
+        let template = Template::parse("{{#items}}{{name}}{{#price}}: {{price}}{{/price}}{{/items}}").unwrap();
+
+        assert_eq!(
+            handout().render(HandoutFormat::Html, &template, false, &english()),
+            "Cape &lt;of&gt; the Mountebank: 750 gp"
+        );
+        assert_eq!(
+            handout().render(HandoutFormat::Markdown, &template, true, &english()),
+            "Cape <of> the Mountebank"
+        );
+    }
+
+    #[test]
+    fn inventory_prices_are_rolled() {
+        let mut folder = CampaignFolder::empty("Test".into());
+        folder.item_registry.add(ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()));
+        let campaign = folder.into_campaign();
+        let shop = Shop::with_inventory(
+            "Tina's".into(),
+            None,
+            vec![StockedItem {
+                identifier: "Mantel".into(),
+                stock: Stock::Stocked(NonZero::<u32>::MIN),
+                days_sold_out: 0,
+            }],
+            RestockRules::default(),
+        );
+        let template = Template::parse("{{#items}}{{#price}}{{price}}{{/price}}{{/items}}").unwrap();
+
+        let rendered = Handout::inventory(&campaign, &shop, PricingStrategy::Xanathar, &english())
+            .render(HandoutFormat::Markdown, &template, false, &english());
+
+        assert!(rendered.ends_with(" gp"), "{rendered}");
+        assert!(!rendered.contains("d10"), "{rendered}");
+    }
+
+    #[test]
+    fn rarities_are_translated() {
+        let german = Translator::new(&Settings {
+            language: Language::German,
+            ..Settings::default()
+        });
+        let cape = ItemType::new("Cape".into(), Rarity::VeryRare, ItemCategory::WondrousItem, "".into());
+
+        assert_eq!(HandoutEntry::of(&cape, &german).rarity.as_deref(), Some("Sehr selten"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+
+/// What a template is filled with: texts for `{{name}}` and lists for `{{#name}}…{{/name}}` sections.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Context {
+    values: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Text(String),
+    List(Vec<Context>),
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.values.insert(name.to_string(), Value::Text(value.into()));
+        self
+    }
+
+    /// Leaves the name unset for `None`, so `{{#name}}` sections around it are skipped.
+    pub fn optional(self, name: &str, value: Option<impl Into<String>>) -> Self {
+        match value {
+            Some(value) => self.text(name, value),
+            None => self,
+        }
+    }
+
+    pub fn list(mut self, name: &str, entries: Vec<Context>) -> Self {
+        self.values.insert(name.to_string(), Value::List(entries));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    Section {
+        name: String,
+        inverted: bool,
+        children: Vec<Node>,
+    },
+}
+
+/// A section that is not closed yet: its name and whether it is inverted, `None` for the template itself.
+type OpenSection = (Option<(String, bool)>, Vec<Node>);
+
+/// A small subset of Mustache.
+///
+/// `{{name}}` is replaced by a text, `{{#name}}…{{/name}}` is repeated for every entry of a list
+/// or shown once if a text is set and not empty, `{{^name}}…{{/name}}` is shown only if it is not.
+/// Inside a list, names are looked up in the entry first and then outside of it. A line with
+/// nothing but a section tag disappears completely, so sections can sit on lines of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        // The innermost open section is last, the template itself is the first.
+        let mut open: Vec<OpenSection> = vec![(None, vec![])];
+        let mut rest = source;
+        let mut at_line_start = true;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(length) = rest[start + 2..].find("}}") else {
+                bail!("A tag is not closed: {}", &rest[start..]);
+            };
+
+            let tag = rest[start + 2..start + 2 + length].trim();
+            let mut before = &rest[..start];
+            let after = &rest[start + 2 + length + 2..];
+
+            let (line_before, starts_line) = match before.rsplit_once('\n') {
+                Some((_, line)) => (line, true),
+                None => (before, at_line_start),
+            };
+            let line_after = after.split_once('\n').map_or(after, |(line, _)| line);
+            let is_section = tag.starts_with(['#', '^', '/']);
+
+            if is_section && starts_line && line_before.trim().is_empty() && line_after.trim().is_empty() {
+                before = &before[..before.len() - line_before.len()];
+                rest = after.split_once('\n').map_or("", |(_, next)| next);
+                at_line_start = true;
+            } else {
+                rest = after;
+                at_line_start = false;
+            }
+
+            let nodes = &mut open.last_mut().expect("the template is always open").1;
+            if !before.is_empty() {
+                nodes.push(Node::Text(before.to_string()));
+            }
+
+            if let Some(name) = tag.strip_prefix('#') {
+                open.push((Some((name.trim().to_string(), false)), vec![]));
+            } else if let Some(name) = tag.strip_prefix('^') {
+                open.push((Some((name.trim().to_string(), true)), vec![]));
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let name = name.trim();
+                match open.pop() {
+                    Some((Some((open_name, inverted)), children)) if open_name == name => {
+                        let section = Node::Section {
+                            name: open_name,
+                            inverted,
+                            children,
+                        };
+                        open.last_mut().expect("the template is always open").1.push(section);
+                    }
+                    Some((Some((open_name, _)), _)) => bail!("{{{{/{name}}}}} closes {{{{#{open_name}}}}}"),
+                    _ => bail!("{{{{/{name}}}}} closes no section"),
+                }
+            } else if tag.is_empty() {
+                bail!("A tag has no name");
+            } else {
+                nodes.push(Node::Variable(tag.to_string()));
+            }
+        }
+
+        if !rest.is_empty() {
+            open.last_mut().expect("the template is always open").1.push(Node::Text(rest.to_string()));
+        }
+
+        match open.pop() {
+            Some((None, nodes)) if open.is_empty() => Ok(Self { nodes }),
+            Some((Some((name, _)), _)) => bail!("{{{{#{name}}}}} is never closed"),
+            _ => unreachable!("only sections are opened"),
+        }
+    }
+
+    pub fn render(&self, context: &Context) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, &mut vec![context], &mut output);
+        output
+    }
+}
+
+fn lookup<'c>(scopes: &[&'c Context], name: &str) -> Option<&'c Value> {
+    scopes.iter().rev().find_map(|scope| scope.values.get(name))
+}
+
+fn render_nodes(nodes: &[Node], scopes: &mut Vec<&Context>, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(name) => {
+                if let Some(Value::Text(text)) = lookup(scopes, name) {
+                    output.push_str(text);
+                }
+            }
+            Node::Section {
+                name,
+                inverted,
+                children,
+            } => {
+                let value = lookup(scopes, name);
+                let is_set = match value {
+                    Some(Value::Text(text)) => !text.is_empty(),
+                    Some(Value::List(entries)) => !entries.is_empty(),
+                    None => false,
+                };
+
+                match (value, inverted) {
+                    (_, true) if !is_set => render_nodes(children, scopes, output),
+                    (Some(Value::List(entries)), false) => {
+                        for entry in entries {
+                            scopes.push(entry);
+                            render_nodes(children, scopes, output);
+                            scopes.pop();
+                        }
+                    }
+                    (_, false) if is_set => render_nodes(children, scopes, output),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_repeat_and_see_the_outer_names() {
+        let template = Template::parse("# {{title}}\n{{#items}}- {{name}} ({{title}})\n{{/items}}").unwrap();
+        let context = Context::new().text("title", "Veys").list(
+            "items",
+            vec![Context::new().text("name", "Cape"), Context::new().text("name", "Ring")],
+        );
+
+        assert_eq!(template.render(&context), "# Veys\n- Cape (Veys)\n- Ring (Veys)\n");
+    }
+
+    #[test]
+    fn sections_depend_on_whether_a_value_is_set() {
+        let template = Template::parse("{{#price}}for {{price}}{{/price}}{{^price}}no price{{/price}}").unwrap();
+
+        assert_eq!(template.render(&Context::new().optional("price", Some("12 gp"))), "for 12 gp");
+        assert_eq!(template.render(&Context::new().optional("price", None::<String>)), "no price");
+        assert_eq!(template.render(&Context::new().text("price", "")), "no price");
+    }
+
+    #[test]
+    fn section_tags_on_their_own_line_leave_no_blank_lines() {
+        let template = Template::parse("Items:\n{{#items}}\n  - {{name}}\n{{/items}}\nDone").unwrap();
+        let context = Context::new().list("items", vec![Context::new().text("name", "Cape")]);
+
+        assert_eq!(template.render(&context), "Items:\n  - Cape\nDone");
+    }
+
+    #[test]
+    fn broken_templates_are_rejected() {
+        assert!(Template::parse("{{#items}}{{name}}").is_err());
+        assert!(Template::parse("{{#items}}{{/entries}}").is_err());
+        assert!(Template::parse("{{/items}}").is_err());
+        assert!(Template::parse("{{name").is_err());
+    }
+}
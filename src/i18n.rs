@@ -0,0 +1,404 @@
+use std::{collections::HashMap, env, sync::OnceLock};
+
+use strum::{EnumIter, IntoStaticStr};
+
+use crate::config::settings::Language;
+
+static ENGLISH: &str = include_str!("../assets/i18n/en.yaml");
+static GERMAN: &str = include_str!("../assets/i18n/de.yaml");
+
+/// Every text the user interface shows. The catalogs in `assets/i18n` hold one entry per phrase,
+/// keyed by the variant name in snake case.
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum I18nPhrase {
+    // App
+    AppTitle,
+    Save,
+    Undo,
+    Redo,
+    NextTab,
+    PreviousTab,
+    PlusOneHour,
+    PlusOneDay,
+    History,
+    Wishes,
+    AllKeys,
+    Quit,
+    RestockedShopsTitle,
+    ItemBackInStock,
+    ItemAddedToStock,
+    ItemRemovedFromStock,
+    HistoryTitle,
+    NoEditsSinceSave,
+    WishReportTitle,
+    WishedBy,
+    NotInStockAnywhere,
+    InStockFor,
+    NoWishesYet,
+    HelpTitle,
+    Everywhere,
+    InPopups,
+    ClosePopup,
+
+    // Shared
+    Confirm,
+    Cancel,
+    Rename,
+    Edit,
+    Delete,
+    Remove,
+    Name,
+    NewName,
+    Item,
+    Items,
+    Category,
+    WondrousItemCategory,
+    SimpleWeaponCategory,
+    WandCategory,
+    ArmorCategory,
+    WeaponCategory,
+    PotionCategory,
+    RingCategory,
+    RodCategory,
+    ScrollCategory,
+    StaffCategory,
+    CommonRarity,
+    UncommonRarity,
+    RareRarity,
+    VeryRareRarity,
+    LegendaryRarity,
+    ArtifactRarity,
+    SoldOut,
+    InfiniteStock,
+    Details,
+    UnknownItem,
+    NoItemSelected,
+    WantedBy,
+    WantedByColumn,
+    GoldPieces,
+    Price,
+    Roll,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    ScrollToTop,
+    ScrollToBottom,
+    ScrollDetails,
+    ScrollDetailsUp,
+
+    // Home
+    Home,
+    BiggerDie,
+    SmallerDie,
+
+    // Weather
+    Weather,
+    LongerForecast,
+    ShorterForecast,
+    Season,
+    Temperature,
+    Precipitation,
+    Wind,
+    Special,
+    SpringSeason,
+    SummerSeason,
+    AutumnSeason,
+    WinterSeason,
+    DryWeather,
+    LightRain,
+    HeavyRain,
+    LightSnow,
+    HeavySnow,
+    CalmWind,
+    Breeze,
+    StrongWind,
+    Gale,
+    Day,
+    TemperatureShort,
+    PrecipitationShort,
+    ForecastTitle,
+
+    // Items
+    NewItem,
+    PreviousItem,
+    NextItem,
+
+    // Item picker
+    Pick,
+    PreviousMatch,
+    NextMatch,
+    Search,
+    NoMatchingItems,
+
+    // Item editor
+    StoreItem,
+    NextField,
+    PreviousField,
+    NextChoice,
+    PreviousChoice,
+    ScrollPreview,
+    ScrollPreviewUp,
+    EditItemTitle,
+    NewItemTitle,
+    Identifier,
+    IdentifierFixed,
+    Rarity,
+    DetailsMarkdown,
+    Preview,
+    ItemNeedsName,
+    ItemNeedsIdentifier,
+    DuplicateIdentifier,
+    CouldNotWriteItem,
+
+    // Shops
+    Shops,
+    ShortName,
+    Restock,
+    ReturnAfterDays,
+    NewShopName,
+    CloseShopForGood,
+    BackToShops,
+    CloseTheShop,
+    KeepIt,
+    Visit,
+    NewShop,
+    CloseShop,
+    PreviousShop,
+    NextShop,
+
+    // Shop
+    BackToShop,
+    GenerateOffer,
+    SellOne,
+    AddItem,
+    AddItemTitle,
+    OneMore,
+    Infinite,
+    SwitchFocus,
+    SwitchFocusBack,
+    Stock,
+    RegistryLacksItem,
+
+    // Offer
+    OfferTitle,
+    NextOffer,
+    PreviousOffer,
+    RollPrices,
+    ShowPriceFormula,
+    ShowPlayers,
+    HideFromPlayers,
+    ShownToPlayers,
+    NoPlayerScreen,
+    CouldNotShowPlayers,
+    Scroll,
+
+    // Handouts
+    ExportHandout,
+    ExportedTo,
+    CouldNotExport,
+    NoCampaignFolder,
+    InventoryHandoutTitle,
+    WishListHandoutTitle,
+
+    // Player view
+    PlayerViewTitle,
+    WaitingForDungeonMaster,
+    CouldNotReadPlayerView,
+
+    // Import
+    ImportRowWithoutName,
+    ImportUnknownRarity,
+    ImportDuplicate,
+    ImportUnknownCategory,
+    ImportConflictKept,
+    ImportConflictReplaced,
+    ImportSummary,
+    ImportDryRun,
+    ImportSavedInto,
+    ImportWroteInto,
+    SyncProgress,
+    SyncFailed,
+    SrdImportedItem,
+    SrdImportFailed,
+    ImportFromSrd,
+    SrdPickerTitle,
+    ToggleSelection,
+    ImportSelected,
+    SrdSelectedCount,
+    CouldNotLoadSrd,
+    SrdImported,
+    SrdImportNotWritten,
+    SyncSummary,
+
+    // Characters
+    Characters,
+    CharactersHint,
+    WishList,
+    WishListHint,
+    RemoveTheCharacter,
+    KeepThem,
+    NewCharacter,
+    AddWish,
+    RemoveWish,
+    ResolveUnknownItem,
+    PreviousCharacter,
+    NextCharacter,
+    PreviousWish,
+    NextWish,
+    AddToWishList,
+    ResolveItemTitle,
+    KnownItemAlready,
+    WishCount,
+    InStockAt,
+    NoItemWithIdentifier,
+    UnknownWishHelp,
+    NewCharacterName,
+    RemoveCharacterForGood,
+
+    // Settings
+    Settings,
+    Setting,
+    Value,
+    DefaultCampaign,
+    Language,
+    MagicItemPrices,
+    ItemsPerOffer,
+    ColorTheme,
+    LogLevel,
+    NerdFontIcons,
+    DefaultCampaignHelp,
+    LanguageHelp,
+    MagicItemPricesHelp,
+    ItemsPerOfferHelp,
+    ColorThemeHelp,
+    LogLevelHelp,
+    NerdFontIconsHelp,
+    NoneSet,
+    ToggleOn,
+    ToggleOff,
+    Clear,
+    Next,
+    Previous,
+    Toggle,
+    PreviousSetting,
+    NextSetting,
+    SavedTo,
+    SettingsNotSaved,
+    CampaignFolderPath,
+    SystemLanguage,
+    XanatharsGuide,
+    DungeonMastersGuide,
+    DarkTheme,
+    LightTheme,
+    HighContrastTheme,
+    ColorBlindSafeTheme,
+    HandoutFormat,
+    HandoutFormatHelp,
+    PlainText,
+    HidePricesInHandouts,
+    HidePricesInHandoutsHelp,
+    AutosaveInterval,
+    AutosaveIntervalHelp,
+    AutosaveEvery,
+    AutosaveOff,
+}
+
+type Catalog = HashMap<String, String>;
+
+fn parse_catalog(yaml: &str) -> Catalog {
+    serde_yaml::from_str(yaml).unwrap_or_else(|e| {
+        log::error!("A translation catalog is broken. {e:?}");
+        Catalog::new()
+    })
+}
+
+fn catalog(language: Language) -> &'static Catalog {
+    static ENGLISH_CATALOG: OnceLock<Catalog> = OnceLock::new();
+    static GERMAN_CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+    match language.resolve() {
+        Language::German => GERMAN_CATALOG.get_or_init(|| parse_catalog(GERMAN)),
+        _ => ENGLISH_CATALOG.get_or_init(|| parse_catalog(ENGLISH)),
+    }
+}
+
+/// The phrase in the language, in English if the language's catalog lacks it.
+pub fn translate(language: Language, phrase: I18nPhrase) -> &'static str {
+    let key: &'static str = phrase.into();
+
+    catalog(language)
+        .get(key)
+        .or_else(|| catalog(Language::English).get(key))
+        .map_or(key, String::as_str)
+}
+
+/// Replaces every `{name}` placeholder of the text with its value.
+pub fn fill(text: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(text.to_string(), |text, (name, value)| text.replace(&format!("{{{name}}}"), value))
+}
+
+/// The language of the environment, judged by the usual locale variables like `LANG=de_DE.UTF-8`.
+pub fn system_language() -> Language {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|variable| env::var(variable).ok())
+        .find(|value| !value.is_empty());
+
+    match locale {
+        Some(locale) if locale.starts_with("de") => Language::German,
+        _ => Language::English,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<String> {
+        let mut names: Vec<String> = Regex::new(r"\{(\w+)\}")
+            .unwrap()
+            .captures_iter(text)
+            .map(|capture| capture[1].to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn english_catalog_has_every_phrase() {
+        let english = parse_catalog(ENGLISH);
+
+        for phrase in I18nPhrase::iter() {
+            let key: &str = phrase.into();
+            assert!(english.contains_key(key), "en.yaml lacks {key}");
+        }
+        assert_eq!(english.len(), I18nPhrase::iter().count(), "en.yaml has keys without a phrase");
+    }
+
+    #[test]
+    fn german_catalog_matches_the_english_one() {
+        let english = parse_catalog(ENGLISH);
+        let german = parse_catalog(GERMAN);
+
+        for (key, text) in &german {
+            let original = english.get(key).unwrap_or_else(|| panic!("de.yaml has unknown key {key}"));
+            assert_eq!(placeholders(text), placeholders(original), "placeholders of {key} differ");
+        }
+    }
+
+    #[test]
+    fn phrases_fall_back_to_english() {
+        assert_eq!(translate(Language::German, I18nPhrase::Shops), "Läden");
+        assert_eq!(translate(Language::English, I18nPhrase::Shops), "Shops");
+        assert_eq!(
+            fill(translate(Language::English, I18nPhrase::OfferTitle), &[("shop", "Tina's"), ("date", "today")]),
+            "Offer for Tina's on today"
+        );
+    }
+}
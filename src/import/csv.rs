@@ -0,0 +1,84 @@
+use anyhow::bail;
+
+/// Reads the records of a CSV file, like spreadsheets export them: fields are separated by
+/// `separator`, quoted fields may contain separators, line breaks and doubled quotes.
+pub fn read_records(source: &str, separator: char) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = source.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            c if in_quotes => field.push(c),
+            c if c == separator => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        bail!("A quoted field is never closed");
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    Ok(records)
+}
+
+/// Spreadsheets in German locales separate with semicolons, so the header decides.
+pub fn guess_separator(source: &str) -> char {
+    let header = source.lines().next().unwrap_or_default();
+    if header.matches(';').count() > header.matches(',').count() {
+        ';'
+    } else {
+        ','
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_keep_separators_quotes_and_line_breaks() {
+        let records = read_records("name,details\r\nRing,\"Glows, \"\"softly\"\".\nTwice.\"\r\n", ',').unwrap();
+
+        assert_eq!(
+            records,
+            vec![vec!["name", "details"], vec!["Ring", "Glows, \"softly\".\nTwice."]]
+        );
+    }
+
+    #[test]
+    fn empty_lines_and_a_missing_last_line_break_are_fine() {
+        let records = read_records("name;rarity\n\nRing;Rare", ';').unwrap();
+
+        assert_eq!(records, vec![vec!["name", "rarity"], vec!["Ring", "Rare"]]);
+    }
+
+    #[test]
+    fn the_separator_is_guessed_from_the_header() {
+        assert_eq!(guess_separator("name;rarity;details, long\n"), ';');
+        assert_eq!(guess_separator("name,rarity\n"), ',');
+        assert!(read_records("name\n\"Ring", ',').is_err());
+    }
+}
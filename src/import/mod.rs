@@ -0,0 +1,385 @@
+pub mod csv;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::settings::Language,
+    data::{
+        campaign::write_yaml,
+        item::{slugify, ItemCategory, ItemIdentifier, ItemTranslation, ItemType, Rarity},
+    },
+    registry::ItemRegistry,
+    ui::translator::{I18nPhrase, Message},
+};
+
+/// File formats a sheet of items can be exported from a spreadsheet in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SheetFormat {
+    Csv,
+    /// An array of objects, one per item.
+    Json,
+}
+
+impl SheetFormat {
+    /// Guessed from the extension of the file, CSV for anything but `.json`.
+    pub fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Columns are matched regardless of case and surrounding spaces.
+fn column_key(column: &str) -> String {
+    column.trim().to_lowercase()
+}
+
+/// One row of a sheet with its cells by column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// The row number a spreadsheet shows, or the position in a JSON array, starting at 1.
+    pub number: usize,
+    cells: HashMap<String, String>,
+}
+
+impl Row {
+    fn cell(&self, column: &str) -> Option<&str> {
+        self.cells
+            .get(&column_key(column))
+            .map(|cell| cell.trim())
+            .filter(|cell| !cell.is_empty())
+    }
+}
+
+pub fn read_rows(source: &str, format: SheetFormat) -> anyhow::Result<Vec<Row>> {
+    match format {
+        SheetFormat::Csv => {
+            let mut records = csv::read_records(source, csv::guess_separator(source))?.into_iter();
+            let Some(header) = records.next() else {
+                return Ok(vec![]);
+            };
+            let header: Vec<String> = header.iter().map(|column| column_key(column)).collect();
+
+            Ok(records
+                .enumerate()
+                .map(|(idx, record)| Row {
+                    // The header is the first row.
+                    number: idx + 2,
+                    cells: header.iter().cloned().zip(record).collect(),
+                })
+                .collect())
+        }
+        SheetFormat::Json => {
+            let entries: Vec<serde_json::Map<String, serde_json::Value>> =
+                serde_json::from_str(source).context("Expected an array of objects")?;
+
+            Ok(entries
+                .into_iter()
+                .enumerate()
+                .map(|(idx, entry)| Row {
+                    number: idx + 1,
+                    cells: entry
+                        .into_iter()
+                        .filter_map(|(column, value)| {
+                            let cell = match value {
+                                serde_json::Value::Null => return None,
+                                serde_json::Value::String(text) => text,
+                                other => other.to_string(),
+                            };
+                            Some((column_key(&column), cell))
+                        })
+                        .collect(),
+                })
+                .collect())
+        }
+    }
+}
+
+/// Which column of the sheet holds which field of an item, kept as YAML next to the sheet.
+/// Every field defaults to a column of the same name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    /// Rows without an identifier get one from their name.
+    pub identifier: String,
+    pub name: String,
+    pub rarity: String,
+    pub category: String,
+    pub details: String,
+    pub translations: BTreeMap<Language, TranslationColumns>,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            identifier: "identifier".into(),
+            name: "name".into(),
+            rarity: "rarity".into(),
+            category: "category".into(),
+            details: "details".into(),
+            translations: BTreeMap::new(),
+        }
+    }
+}
+
+/// Columns with the name and details in another language, e.g. `name_de`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranslationColumns {
+    pub name: Option<String>,
+    pub details: Option<String>,
+}
+
+impl ColumnMapping {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path).with_context(|| format!("Could not open column mapping {}", path.display()))?;
+        serde_yaml::from_reader(file).with_context(|| format!("Could not read column mapping {}", path.display()))
+    }
+}
+
+/// Something about a row the user should know, because it was skipped or read differently than written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportNote {
+    WithoutName { row: usize },
+    UnknownRarity { row: usize, rarity: String },
+    /// An earlier row of the sheet has the same identifier.
+    Duplicate { row: usize, identifier: ItemIdentifier },
+    /// The row is imported as a wondrous item.
+    UnknownCategory { row: usize, category: String },
+    /// The registry already has an item with the identifier.
+    Conflict { row: usize, identifier: ItemIdentifier, existing: String },
+}
+
+impl ImportNote {
+    /// `replace` tells whether conflicting items replace the known ones.
+    pub fn message(&self, replace: bool) -> Message {
+        match self {
+            Self::WithoutName { row } => Message::from(I18nPhrase::ImportRowWithoutName).with("row", row),
+            Self::UnknownRarity { row, rarity } => {
+                Message::from(I18nPhrase::ImportUnknownRarity).with("row", row).with("rarity", rarity)
+            }
+            Self::Duplicate { row, identifier } => {
+                Message::from(I18nPhrase::ImportDuplicate).with("row", row).with("identifier", identifier)
+            }
+            Self::UnknownCategory { row, category } => {
+                Message::from(I18nPhrase::ImportUnknownCategory).with("row", row).with("category", category)
+            }
+            Self::Conflict { row, identifier, existing } => {
+                let phrase = if replace { I18nPhrase::ImportConflictReplaced } else { I18nPhrase::ImportConflictKept };
+                Message::from(phrase).with("row", row).with("identifier", identifier).with("existing", existing)
+            }
+        }
+    }
+}
+
+/// The items read from a sheet, checked against the registry they are meant for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemSheet {
+    pub rows: usize,
+    pub items: Vec<ItemType>,
+    pub notes: Vec<ImportNote>,
+}
+
+impl ItemSheet {
+    pub fn read(rows: &[Row], mapping: &ColumnMapping, registry: &ItemRegistry) -> Self {
+        let mut items = vec![];
+        let mut notes = vec![];
+        let mut seen = HashSet::new();
+
+        for row in rows {
+            let Some(name) = row.cell(&mapping.name) else {
+                notes.push(ImportNote::WithoutName { row: row.number });
+                continue;
+            };
+
+            let identifier = slugify(row.cell(&mapping.identifier).unwrap_or(name));
+            if identifier.is_empty() {
+                notes.push(ImportNote::WithoutName { row: row.number });
+                continue;
+            }
+
+            let rarity_name = row.cell(&mapping.rarity).unwrap_or_default();
+            let Some(rarity) = Rarity::from_name(rarity_name) else {
+                notes.push(ImportNote::UnknownRarity {
+                    row: row.number,
+                    rarity: rarity_name.to_string(),
+                });
+                continue;
+            };
+
+            if !seen.insert(identifier.clone()) {
+                notes.push(ImportNote::Duplicate { row: row.number, identifier });
+                continue;
+            }
+
+            // Most homebrew items are wondrous items, so a sheet may leave the category out.
+            let category = match row.cell(&mapping.category) {
+                Some(category_name) => ItemCategory::from_name(category_name).unwrap_or_else(|| {
+                    notes.push(ImportNote::UnknownCategory {
+                        row: row.number,
+                        category: category_name.to_string(),
+                    });
+                    ItemCategory::WondrousItem
+                }),
+                None => ItemCategory::WondrousItem,
+            };
+
+            let details = row.cell(&mapping.details).unwrap_or_default().to_string();
+            let mut item_type = ItemType {
+                identifier,
+                ..ItemType::new(name.to_string(), rarity, category, details)
+            };
+
+            for (language, columns) in &mapping.translations {
+                let translation = ItemTranslation {
+                    name: columns.name.as_deref().and_then(|column| row.cell(column)).map(str::to_string),
+                    details: columns.details.as_deref().and_then(|column| row.cell(column)).map(str::to_string),
+                };
+                if translation.name.is_some() || translation.details.is_some() {
+                    item_type = item_type.with_translation(*language, translation);
+                }
+            }
+
+            if let Some(existing) = registry.get(&item_type.identifier) {
+                notes.push(ImportNote::Conflict {
+                    row: row.number,
+                    identifier: item_type.identifier.clone(),
+                    existing: existing.name.clone(),
+                });
+            }
+
+            items.push(item_type);
+        }
+
+        Self {
+            rows: rows.len(),
+            items,
+            notes,
+        }
+    }
+
+    /// The items to save. Items the registry knows are left out, unless `replace` is set: then
+    /// they are rebased on the known ones, so translations only kept locally stay.
+    pub fn merged(&self, registry: &ItemRegistry, replace: bool) -> Vec<ItemType> {
+        self.items
+            .iter()
+            .filter(|imported| replace || registry.get(&imported.identifier).is_none())
+            .map(|imported| registry.as_import(imported.clone()))
+            .collect()
+    }
+}
+
+/// Writes one `<identifier>.yaml` per item into `folder`, shaped like the items of a campaign.
+pub fn write_items(folder: &Path, items: &[ItemType]) -> anyhow::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(folder)?;
+
+    items
+        .iter()
+        .map(|item_type| {
+            let path = folder.join(format!("{}.yaml", item_type.identifier));
+            write_yaml(&path, item_type).with_context(|| format!("Could not write {}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ItemRegistry {
+        let mut registry = ItemRegistry::new();
+        registry.add(ItemType {
+            identifier: "ring-of-warmth".into(),
+            ..ItemType::new("Ring of Warmth".into(), Rarity::Uncommon, ItemCategory::WondrousItem, "".into())
+                .with_translation(
+                    Language::German,
+                    ItemTranslation {
+                        name: Some("Ring der Wärme".into()),
+                        details: None,
+                    },
+                )
+        });
+        registry
+    }
+
+    #[test]
+    fn rows_are_read_through_the_mapping() {
+        let sheet = "Title;Seltenheit;Type;Text;Name DE\nCloak of Stars;very rare;Wondrous Items;Twinkles.;Sternenmantel\n";
+        let mapping: ColumnMapping = serde_yaml::from_str(
+            "name: title\nrarity: seltenheit\ncategory: type\ndetails: text\ntranslations:\n  german:\n    name: name de\n",
+        )
+        .unwrap();
+
+        let rows = read_rows(sheet, SheetFormat::Csv).unwrap();
+        let sheet = ItemSheet::read(&rows, &mapping, &ItemRegistry::new());
+
+        assert_eq!(sheet.notes, vec![]);
+        assert_eq!(sheet.items.len(), 1);
+        let cloak = &sheet.items[0];
+        assert_eq!(cloak.identifier, "cloak-of-stars");
+        assert_eq!(cloak.rarity, Rarity::VeryRare);
+        assert_eq!(cloak.details, "Twinkles.");
+        assert_eq!(cloak.name_in(Language::German), "Sternenmantel");
+    }
+
+    #[test]
+    fn bad_rows_and_conflicts_are_reported() {
+        let sheet = r#"[
+            {"name": "Ring of Warmth", "rarity": "Rare"},
+            {"name": "Wand of Sparks", "rarity": "Mythic"},
+            {"rarity": "Rare"},
+            {"name": "Shiny Spoon", "rarity": "Common", "category": "Cutlery", "price": 5},
+            {"identifier": "Shiny Spoon", "name": "Spoon", "rarity": "Common"}
+        ]"#;
+
+        let rows = read_rows(sheet, SheetFormat::Json).unwrap();
+        let sheet = ItemSheet::read(&rows, &ColumnMapping::default(), &registry());
+
+        assert_eq!(
+            sheet.notes,
+            vec![
+                ImportNote::Conflict {
+                    row: 1,
+                    identifier: "ring-of-warmth".into(),
+                    existing: "Ring of Warmth".into()
+                },
+                ImportNote::UnknownRarity {
+                    row: 2,
+                    rarity: "Mythic".into()
+                },
+                ImportNote::WithoutName { row: 3 },
+                ImportNote::UnknownCategory {
+                    row: 4,
+                    category: "Cutlery".into()
+                },
+                ImportNote::Duplicate {
+                    row: 5,
+                    identifier: "shiny-spoon".into()
+                },
+            ]
+        );
+        assert_eq!(sheet.items.len(), 2);
+    }
+
+    #[test]
+    fn known_items_are_only_replaced_on_request_and_keep_their_translations() {
+        let rows = read_rows("name,rarity\nRing of Warmth,Rare\nShiny Spoon,Common\n", SheetFormat::Csv).unwrap();
+        let registry = registry();
+        let sheet = ItemSheet::read(&rows, &ColumnMapping::default(), &registry);
+
+        let kept: Vec<_> = sheet.merged(&registry, false).into_iter().map(|item| item.identifier).collect();
+        assert_eq!(kept, vec!["shiny-spoon"]);
+
+        let replaced = sheet.merged(&registry, true);
+        assert_eq!(replaced[0].rarity, Rarity::Rare);
+        assert_eq!(replaced[0].name_in(Language::German), "Ring der Wärme");
+    }
+}
@@ -1,97 +1,57 @@
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::format::Item;
 use clap::Parser;
 
 mod apis;
+mod cli;
+mod config;
 mod data;
+mod export;
+mod i18n;
+mod import;
 mod meta;
 mod registry;
 mod ui;
 
 mod state;
 
-use data::campaign::{Campaign, CampaignFolder, FileMeta};
-use data::character::{Character, FileCharacter};
+use config::keymap::{self, KeyBindings};
+use config::theme::{self, ThemeOverrides};
+use config::settings::{Settings, SettingsStore};
+use data::campaign::{load_campaign_folder, CampaignFolder};
 use data::item;
-use data::shop::{FileShop, Shop};
 use log::LevelFilter;
 use log4rs::append::file::FileAppender;
 use log4rs::config::{Appender, Config, Root};
 use log4rs::encode::pattern::PatternEncoder;
-use data::item::ItemType;
-use registry::ItemRegistry;
-use serde::Deserialize;
-use ui::translator::EngNerdI18n;
+use state::campaign::CampaignStore;
+use state::player_view::PlayerScreen;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct CliArgs {
     #[arg(short, long)]
     campaign_file: Option<String>,
-}
-
-fn load_object_vector<T: for<'a> Deserialize<'a>>(folder: PathBuf) -> anyhow::Result<Vec<T>> {
-    let entries = std::fs::read_dir(folder)?;
-
-    let objects = entries
-        .filter_map(|maybe_entry| 
-            match maybe_entry {
-                Ok(entry) => load_object(&entry.path()).ok(),
-                Err(_) => None
-            })
-        .collect();
-
-    Ok(objects)
-}
-
-fn load_object<T: for<'a> Deserialize<'a>>(path: &PathBuf) -> anyhow::Result<T>{
-    let file = std::fs::File::open(path)
-        .inspect_err(|e| 
-            log::warn!("Could not open file at {path:?}. Problem was: {e:?}"))?;
 
-    let object = serde_yaml::from_reader(&file)
-        .inspect_err(|e| 
-            log::warn!("Deserialization of object in file {path:?} failed because of {e:?}"))?;
+    /// Keymap file that puts actions on other keys, defaults to keymap.yaml in the config directory
+    #[arg(long)]
+    keymap: Option<PathBuf>,
 
-    Ok(object)
-}
-
-fn load_characters(character_folder: PathBuf) -> anyhow::Result<Vec<FileCharacter>> {
-    load_object_vector(character_folder)
-}
-
-fn load_items(items_folder: PathBuf) -> anyhow::Result<Vec<ItemType>> {
-    load_object_vector(items_folder)
-}
+    /// Seconds between automatic saves, 0 turns auto-save off. Overrides the auto-save setting for this session
+    #[arg(long)]
+    autosave_interval: Option<u64>,
 
-fn load_campaign_folder(folder_path: PathBuf) -> anyhow::Result<CampaignFolder> {
-    let items_path = folder_path.join("items"); // @todo make this some global const
-    let items = load_items(items_path)
-        .unwrap_or(vec![]);
-    let item_registry = ItemRegistry { items };
-    
-    let character_path = folder_path.join("characters"); // @todo make this some global const
-    let characters = load_characters(character_path) 
-        .unwrap_or(vec![]);
-
-    let f = std::fs::File::open(folder_path.join("simp.yaml"))?;
-    let meta: FileMeta = serde_yaml::from_reader(f)?;
-    
-    let cf = CampaignFolder {
-        characters,
-        meta,
-        item_registry,
-        shops: vec![],
-    };
-    
-    Ok(cf)
+    /// Shows only what the dungeon master shows the players from another instance on the same campaign
+    #[arg(long)]
+    player_view: bool,
 
+    #[command(subcommand)]
+    command: Option<cli::Command>,
 }
 
-
-
 pub fn setup_logger() -> anyhow::Result<()> {
     let logfile = FileAppender::builder()
         .encoder(Box::new(PatternEncoder::new(
@@ -104,7 +64,8 @@ pub fn setup_logger() -> anyhow::Result<()> {
         .build(
             Root::builder()
                 .appender("logfile")
-                .build(LevelFilter::Debug),
+                // The level of the settings applies through `log::set_max_level`, so it can change at runtime.
+                .build(LevelFilter::Trace),
         )?;
 
     log4rs::init_config(config)?;
@@ -112,6 +73,56 @@ pub fn setup_logger() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Logs panics before the default hook prints them. `ratatui::init` wraps this hook
+/// with one that restores the terminal first.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("The application panicked: {info}");
+        default_hook(info);
+    }));
+}
+
+/// Installs the keymap of the user, the default keys stay if there is none or it is broken.
+fn install_key_bindings(explicit_path: Option<&Path>) {
+    let path = match explicit_path {
+        Some(path) => path.to_path_buf(),
+        None => match KeyBindings::default_path() {
+            Some(path) if path.exists() => path,
+            _ => return,
+        },
+    };
+
+    match KeyBindings::load(&path) {
+        Ok(bindings) => keymap::install(bindings),
+        Err(e) => log::error!("Using the default keys. {:?}", e),
+    }
+}
+
+/// Installs the color changes of the user, the built-in themes stay as they are if there is none or it is broken.
+fn install_theme_overrides() {
+    let Some(path) = ThemeOverrides::default_path().filter(|path| path.exists()) else {
+        return;
+    };
+
+    match ThemeOverrides::load(&path) {
+        Ok(overrides) => theme::install_overrides(overrides),
+        Err(e) => log::error!("Using the built-in themes. {:?}", e),
+    }
+}
+
+/// Opens the settings of the user. Broken settings are not overwritten, the defaults are used without saving.
+fn open_settings() -> SettingsStore {
+    let Some(path) = SettingsStore::default_path() else {
+        return SettingsStore::in_memory(Settings::default());
+    };
+
+    SettingsStore::open(&path).unwrap_or_else(|e| {
+        log::error!("Using the default settings. {:?}", e);
+        SettingsStore::in_memory(Settings::default())
+    })
+}
+
 fn main() {
     match setup_logger() {
         Ok(()) => {},
@@ -120,38 +131,65 @@ fn main() {
 
     log::info!("Hi there!");
 
+    install_panic_hook();
+
     let args = CliArgs::parse();
 
-    let result_work_campaign = if let Some(path) = args.campaign_file {
-        load_campaign_folder(PathBuf::from(path))
-    } else {
-        Err(anyhow::format_err!("{}", "some error"))
-    };
+    install_key_bindings(args.keymap.as_deref());
 
-    // let result_work_campaign = load_campaign_folder(camp_file);
+    let settings = open_settings();
+    log::set_max_level(settings.settings().log_level.filter());
 
-    let campaign_folder = match result_work_campaign {
-        Ok(campaign) => campaign,
-        Err(e) => {
-            log::error!("Could not load campaign. {:?}", e);
-            CampaignFolder::empty("Tina's Kampagne".into())
-        }
-    };
-
-    let boxed = Box::new(campaign_folder);
-    let persistent_folder: &'static CampaignFolder = Box::leak(boxed);
+    install_theme_overrides();
+    theme::apply(settings.settings().theme);
 
-    let (mut campaign, item_registry) = persistent_folder.destructure();
+    let campaign_path = args
+        .campaign_file
+        .map(PathBuf::from)
+        .or_else(|| settings.settings().default_campaign.clone());
 
-    // let boxed = Box::new(campaign);
-    // let persistent_campaign: &'static mut Campaign = Box::leak(boxed);
+    if let Some(command) = args.command {
+        if let Err(e) = cli::run(command, campaign_path.as_deref(), settings.settings()) {
+            eprintln!("{e:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.player_view {
+        let Some(path) = &campaign_path else {
+            eprintln!("The player view needs the folder of the campaign the dungeon master has open.");
+            return;
+        };
+
+        let watcher = PlayerScreen::new(path).watch();
+        if let Err(e) = ui::player_view::run_player_view(watcher, settings.settings().clone()) {
+            log::error!("The player view stopped with an error. {:?}", e);
+        }
+        return;
+    }
 
-    let inter = EngNerdI18n {};
-    
-    let boxed = Box::new(inter);
-    let persistent_inter: &'static mut EngNerdI18n = Box::leak(boxed);
+    let result_work_campaign = if let Some(path) = &campaign_path {
+        load_campaign_folder(path)
+    } else {
+        Err(anyhow::format_err!("{}", "some error"))
+    };
 
-    let s = ui::app::run_app(&mut campaign, persistent_inter);
+    let store = match (result_work_campaign, &campaign_path) {
+        (Ok(campaign_folder), Some(path)) => {
+            CampaignStore::open(campaign_folder.into_campaign(), path).into_shared()
+        }
+        (result, _) => {
+            if let Err(e) = result {
+                log::error!("Could not load campaign. {:?}", e);
+            }
+            CampaignStore::shared(CampaignFolder::empty("Tina's Kampagne".into()).into_campaign())
+        }
+    };
 
+    let autosave_override = args.autosave_interval.map(Duration::from_secs);
 
+    if let Err(e) = ui::app::run_app(store, settings.into_shared(), autosave_override) {
+        log::error!("The application stopped with an error. {:?}", e);
+    }
 }
@@ -6,7 +6,7 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{data::{character::{Character, FileCharacter}, item::{Item, ItemCategory, ItemIdentifier, ItemType, Rarity}}, ui::display::AsRatatuiSpan};
+use crate::data::item::{Item, ItemCategory, ItemIdentifier, ItemType, Rarity};
 
 pub type CostExpressionFunction = dyn Fn(&ItemType) -> String;
 
@@ -21,22 +21,25 @@ pub fn xanathar_magic_item_cost(item: &ItemType) -> String {
     }
 }
 
+/// Price ranges of the Dungeon Master's Guide, rolled in even steps within each range.
+pub fn dmg_magic_item_cost(item: &ItemType) -> String {
+    match item.rarity {
+        Rarity::Common => "(1d10 + 10) * 5".into(),
+        Rarity::Uncommon => "(1d80 + 20) * 5".into(),
+        Rarity::Rare => "(1d90 + 10) * 50".into(),
+        Rarity::VeryRare => "(1d90 + 10) * 500".into(),
+        Rarity::Legendary => "(1d90 + 10) * 5000".into(),
+        Rarity::Artifact => "(1d90 + 10) * 5000".into(),
+    }
+}
 
 
-#[derive(Debug, Deserialize, Serialize)]
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ItemRegistry {
     pub items: Vec<ItemType>
 }
 
-impl ItemRegistry {
-    pub fn link_character<'a>(&'a self, character: FileCharacter) -> Character<'a> {
-        Character {
-            state: character,
-            registry: &self
-        }
-    }
-}
-
 fn link_wishlist(items: &Vec<ItemType>, wish_list: Vec<String>) -> Vec<Item<'_>> {
     wish_list
         .into_iter()
@@ -60,6 +63,32 @@ impl ItemRegistry {
         self.items.push(item_type);
     }
 
+    /// Adds an item from an import like the dnd5eapi. An item that is already known gets the
+    /// imported text as its new base, its translations stay.
+    pub fn import(&mut self, imported: ItemType) {
+        match self.get_mut(imported.identifier.clone()) {
+            Some(known) => known.rebase(imported),
+            None => self.add(imported),
+        }
+    }
+
+    /// What [`Self::import`] would store for the item, without storing it.
+    pub fn as_import(&self, imported: ItemType) -> ItemType {
+        match self.get(&imported.identifier) {
+            Some(known) => {
+                let mut rebased = known.clone();
+                rebased.rebase(imported);
+                rebased
+            }
+            None => imported,
+        }
+    }
+
+    pub fn remove(&mut self, key: &ItemIdentifier) -> Option<ItemType> {
+        let idx = self.items.iter().position(|item| item.identifier == *key)?;
+        Some(self.items.remove(idx))
+    }
+
     pub fn items(&self) -> &[ItemType] {
         self.items.as_slice()
     }
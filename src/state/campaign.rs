@@ -0,0 +1,871 @@
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{
+    calendar::{CampaignDate, Stamped},
+    campaign::{Campaign, META_FILE},
+    character::Character,
+    item::{ItemIdentifier, ItemType},
+    shop::{Shop, ShopChangelog, Stock, StockedItem},
+};
+
+use super::{journal::Journal, player_view::PlayerScreen};
+
+pub type SharedCampaign = Rc<RefCell<CampaignStore>>;
+
+/// Every change to a campaign is expressed as one of these commands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CampaignCommand {
+    AdvanceTime { hours: u32 },
+    SetStock { shop: usize, item: ItemIdentifier, stock: Stock },
+    InsertStock { shop: usize, index: usize, item: StockedItem },
+    RemoveStock { shop: usize, item: ItemIdentifier },
+    InsertShop { index: usize, shop: Shop },
+    RemoveShop { shop: usize },
+    RenameShop { shop: usize, name: String },
+    InsertCharacter { index: usize, character: Character },
+    RemoveCharacter { character: usize },
+    RenameCharacter { character: usize, name: String },
+    AddWish { character: usize, item: ItemIdentifier },
+    RemoveWish { character: usize, item: ItemIdentifier },
+    /// Swaps a wish for another item in place, e.g. to fix an identifier the registry does not know.
+    ReplaceWish { character: usize, item: ItemIdentifier, with: ItemIdentifier },
+    UpsertItem(ItemType),
+    RemoveItem(ItemIdentifier),
+    /// Puts the clock and all shops back to a recorded state. Used to undo and redo
+    /// [`CampaignCommand::AdvanceTime`], whose restocks are random.
+    RestoreClock { date: CampaignDate, shops: Vec<Shop>, ledger_len: usize },
+}
+
+/// An applied command together with the commands that revert and repeat it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub description: String,
+    pub undo: CampaignCommand,
+    pub redo: CampaignCommand,
+}
+
+/// Tells subscribers which part of the campaign a command touched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CampaignChange {
+    Clock { restocks: Vec<Stamped<ShopChangelog>> },
+    Shop(usize),
+    ShopList,
+    Character(usize),
+    CharacterList,
+    Registry,
+}
+
+/// Position in the change log of a store up to which a subscriber has seen all changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChangeCursor(usize);
+
+/// Owns the campaign while the app runs. Pages read from it and change it only
+/// through [`CampaignStore::apply`], so every other page learns about the change.
+#[derive(Debug)]
+pub struct CampaignStore {
+    campaign: Campaign,
+    changes: Vec<CampaignChange>,
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    rng: StdRng,
+
+    folder: Option<PathBuf>,
+    journal: Option<Journal>,
+    unsaved: bool,
+}
+
+impl CampaignStore {
+    pub fn new(campaign: Campaign) -> Self {
+        Self {
+            campaign,
+            changes: vec![],
+            undo_stack: vec![],
+            redo_stack: vec![],
+            rng: StdRng::from_entropy(),
+            folder: None,
+            journal: None,
+            unsaved: false,
+        }
+    }
+
+    /// A store for a campaign loaded from `folder`. Commands from the journal that are newer
+    /// than the last save are replayed and can be undone like any other edit.
+    pub fn open(campaign: Campaign, folder: &Path) -> Self {
+        let journal = Journal::new(folder);
+        let last_save = std::fs::metadata(folder.join(META_FILE))
+            .and_then(|meta| meta.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut store = Self::new(campaign);
+
+        let commands = journal.read_since(last_save).unwrap_or_else(|e| {
+            log::error!("Could not read the journal: {e:?}");
+            vec![]
+        });
+
+        for command in commands {
+            match store.execute(command) {
+                Ok(entry) => {
+                    store.undo_stack.push(entry);
+                    store.unsaved = true;
+                }
+                Err(e) => log::warn!("Skipped journal entry that no longer applies: {e:?}"),
+            }
+        }
+
+        if store.unsaved {
+            log::info!("Replayed {} journal entries", store.undo_stack.len());
+        }
+
+        store.folder = Some(folder.to_path_buf());
+        store.journal = Some(journal);
+
+        store
+    }
+
+    pub fn shared(campaign: Campaign) -> SharedCampaign {
+        Rc::new(RefCell::new(Self::new(campaign)))
+    }
+
+    pub fn into_shared(self) -> SharedCampaign {
+        Rc::new(RefCell::new(self))
+    }
+
+    pub fn campaign(&self) -> &Campaign {
+        &self.campaign
+    }
+
+    /// The folder the campaign was opened from.
+    pub fn folder(&self) -> Option<&Path> {
+        self.folder.as_deref()
+    }
+
+    /// Writes one item type into the folder right away, without saving the rest of the campaign.
+    pub fn save_item(&mut self, identifier: &ItemIdentifier) -> anyhow::Result<PathBuf> {
+        let Some(folder) = &self.folder else {
+            anyhow::bail!("Campaign {} was not opened from a folder", self.campaign.name);
+        };
+
+        self.campaign.save_item(folder, identifier)
+    }
+
+    /// Stored items are written right away, see [`Self::save_item`], so undoing or redoing them
+    /// rewrites or deletes their file as well. Otherwise an undone item would be back on the next load.
+    fn sync_item_file(&mut self, command: &CampaignCommand) {
+        let identifier = match command {
+            CampaignCommand::UpsertItem(item_type) => &item_type.identifier,
+            CampaignCommand::RemoveItem(identifier) => identifier,
+            _ => return,
+        };
+        let Some(folder) = &self.folder else {
+            return;
+        };
+
+        let result = if self.campaign.registry.get(identifier).is_some() {
+            self.campaign.save_item(folder, identifier).map(|_| ())
+        } else {
+            self.campaign.remove_item_file(folder, identifier)
+        };
+
+        if let Err(e) = result {
+            log::error!("Could not update the file of item {identifier}: {e:?}");
+        }
+    }
+
+    /// Where to show things to the players, only campaigns opened from a folder have one.
+    pub fn player_screen(&self) -> Option<PlayerScreen> {
+        self.folder.as_deref().map(PlayerScreen::new)
+    }
+
+    /// A cursor that only reports changes applied from now on.
+    pub fn subscribe(&self) -> ChangeCursor {
+        ChangeCursor(self.changes.len())
+    }
+
+    /// All changes since the cursor was last used, moving the cursor to the end.
+    pub fn changes_since(&self, cursor: &mut ChangeCursor) -> Vec<CampaignChange> {
+        let changes = self.changes[cursor.0.min(self.changes.len())..].to_vec();
+        cursor.0 = self.changes.len();
+        changes
+    }
+
+    /// Applies a command and records it in the undo history.
+    pub fn apply(&mut self, command: CampaignCommand) -> anyhow::Result<()> {
+        log::info!("Applying campaign command {command:?}");
+
+        let entry = self.execute(command)?;
+        self.record(&entry.redo);
+        self.undo_stack.push(entry);
+        self.redo_stack.clear();
+
+        Ok(())
+    }
+
+    /// Reverts the last applied command. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> anyhow::Result<bool> {
+        let Some(entry) = self.undo_stack.pop() else {
+            return Ok(false);
+        };
+
+        log::info!("Undoing {}", entry.description);
+
+        if let Err(e) = self.execute(entry.undo.clone()) {
+            self.undo_stack.push(entry);
+            return Err(e);
+        }
+
+        self.record(&entry.undo);
+        self.sync_item_file(&entry.undo);
+        self.redo_stack.push(entry);
+        Ok(true)
+    }
+
+    /// Repeats the last undone command. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> anyhow::Result<bool> {
+        let Some(entry) = self.redo_stack.pop() else {
+            return Ok(false);
+        };
+
+        log::info!("Redoing {}", entry.description);
+
+        if let Err(e) = self.execute(entry.redo.clone()) {
+            self.redo_stack.push(entry);
+            return Err(e);
+        }
+
+        self.record(&entry.redo);
+        self.sync_item_file(&entry.redo);
+        self.undo_stack.push(entry);
+        Ok(true)
+    }
+
+    /// Applied commands, oldest first.
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.undo_stack
+    }
+
+    /// Undone commands that can be redone, the next one last.
+    pub fn redo_history(&self) -> &[HistoryEntry] {
+        &self.redo_stack
+    }
+
+    /// Forgets the history once the campaign has been saved.
+    pub fn mark_saved(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.unsaved = false;
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    /// Writes the campaign back into the folder it was opened from and starts a fresh journal.
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        self.write()?;
+        self.mark_saved();
+        Ok(())
+    }
+
+    /// Like [`Self::save`], but keeps the history, so auto-saving does not take away undo and redo.
+    pub fn autosave(&mut self) -> anyhow::Result<()> {
+        self.write()?;
+        self.unsaved = false;
+        Ok(())
+    }
+
+    fn write(&mut self) -> anyhow::Result<()> {
+        let Some(folder) = &self.folder else {
+            anyhow::bail!("Campaign {} was not opened from a folder", self.campaign.name);
+        };
+
+        self.campaign.save(folder)?;
+
+        if let Some(journal) = &self.journal {
+            journal.clear()?;
+        }
+
+        Ok(())
+    }
+
+    /// Appends an executed command to the journal and marks the campaign as changed.
+    fn record(&mut self, command: &CampaignCommand) {
+        self.unsaved = true;
+
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.append(command) {
+                log::error!("Could not write to the journal: {e:?}");
+            }
+        }
+    }
+
+    fn execute(&mut self, command: CampaignCommand) -> anyhow::Result<HistoryEntry> {
+        let (change, entry) = match command {
+            CampaignCommand::AdvanceTime { hours } => {
+                let before = self.clock_snapshot();
+                let restocks = self.campaign.advance_hours_with_rng(hours, &mut self.rng);
+
+                let entry = HistoryEntry {
+                    description: format!("Advance time by {hours} hours"),
+                    undo: before,
+                    redo: self.clock_snapshot(),
+                };
+
+                (CampaignChange::Clock { restocks }, entry)
+            }
+            CampaignCommand::RestoreClock { date, shops, ledger_len } => {
+                let entry = HistoryEntry {
+                    description: format!("Set the clock to {}", self.campaign.calendar.format(&date)),
+                    undo: self.clock_snapshot(),
+                    redo: CampaignCommand::RestoreClock { date, shops: shops.clone(), ledger_len },
+                };
+
+                self.campaign.date = date;
+                self.campaign.shops = shops;
+                self.campaign.ledger.truncate(ledger_len);
+
+                (CampaignChange::Clock { restocks: vec![] }, entry)
+            }
+            CampaignCommand::SetStock { shop, item, stock } => {
+                let target = self.shop_mut(shop)?;
+
+                let Some(previous) = target.set_stock(&item, stock.clone()) else {
+                    anyhow::bail!("Shop {} does not list {item}", target.name);
+                };
+
+                let entry = HistoryEntry {
+                    description: format!("Set stock of {item} in {} to {stock}", target.name),
+                    undo: CampaignCommand::SetStock { shop, item: item.clone(), stock: previous },
+                    redo: CampaignCommand::SetStock { shop, item, stock },
+                };
+
+                (CampaignChange::Shop(shop), entry)
+            }
+            CampaignCommand::InsertStock { shop, index, item } => {
+                let target = self.shop_mut(shop)?;
+                let identifier = item.identifier.clone();
+
+                if !target.insert_item(index, item.clone()) {
+                    anyhow::bail!("Shop {} already lists {identifier}", target.name);
+                }
+
+                let entry = HistoryEntry {
+                    description: format!("Add {identifier} to {}", target.name),
+                    undo: CampaignCommand::RemoveStock { shop, item: identifier },
+                    redo: CampaignCommand::InsertStock { shop, index, item },
+                };
+
+                (CampaignChange::Shop(shop), entry)
+            }
+            CampaignCommand::RemoveStock { shop, item } => {
+                let target = self.shop_mut(shop)?;
+
+                let Some((index, removed)) = target.remove_item(&item) else {
+                    anyhow::bail!("Shop {} does not list {item}", target.name);
+                };
+
+                let entry = HistoryEntry {
+                    description: format!("Remove {item} from {}", target.name),
+                    undo: CampaignCommand::InsertStock { shop, index, item: removed },
+                    redo: CampaignCommand::RemoveStock { shop, item },
+                };
+
+                (CampaignChange::Shop(shop), entry)
+            }
+            CampaignCommand::InsertShop { index, shop } => {
+                self.ensure_unique_shop_name(&shop.name, None)?;
+
+                let index = index.min(self.campaign.shops.len());
+                let entry = HistoryEntry {
+                    description: format!("Open shop {}", shop.name),
+                    undo: CampaignCommand::RemoveShop { shop: index },
+                    redo: CampaignCommand::InsertShop { index, shop: shop.clone() },
+                };
+
+                self.campaign.shops.insert(index, shop);
+
+                (CampaignChange::ShopList, entry)
+            }
+            CampaignCommand::RemoveShop { shop } => {
+                self.shop_mut(shop)?;
+                let removed = self.campaign.shops.remove(shop);
+
+                let entry = HistoryEntry {
+                    description: format!("Close shop {}", removed.name),
+                    undo: CampaignCommand::InsertShop { index: shop, shop: removed },
+                    redo: CampaignCommand::RemoveShop { shop },
+                };
+
+                (CampaignChange::ShopList, entry)
+            }
+            CampaignCommand::RenameShop { shop, name } => {
+                self.ensure_unique_shop_name(&name, Some(shop))?;
+                let target = self.shop_mut(shop)?;
+                let previous = std::mem::replace(&mut target.name, name.clone());
+
+                let entry = HistoryEntry {
+                    description: format!("Rename shop {previous} to {name}"),
+                    undo: CampaignCommand::RenameShop { shop, name: previous },
+                    redo: CampaignCommand::RenameShop { shop, name },
+                };
+
+                (CampaignChange::Shop(shop), entry)
+            }
+            CampaignCommand::InsertCharacter { index, character } => {
+                self.ensure_unique_character_name(&character.name, None)?;
+
+                let index = index.min(self.campaign.characters.len());
+                let entry = HistoryEntry {
+                    description: format!("Add character {}", character.name),
+                    undo: CampaignCommand::RemoveCharacter { character: index },
+                    redo: CampaignCommand::InsertCharacter { index, character: character.clone() },
+                };
+
+                self.campaign.characters.insert(index, character);
+
+                (CampaignChange::CharacterList, entry)
+            }
+            CampaignCommand::RemoveCharacter { character } => {
+                self.character_mut(character)?;
+                let removed = self.campaign.characters.remove(character);
+
+                let entry = HistoryEntry {
+                    description: format!("Remove character {}", removed.name),
+                    undo: CampaignCommand::InsertCharacter { index: character, character: removed },
+                    redo: CampaignCommand::RemoveCharacter { character },
+                };
+
+                (CampaignChange::CharacterList, entry)
+            }
+            CampaignCommand::RenameCharacter { character, name } => {
+                self.ensure_unique_character_name(&name, Some(character))?;
+                let target = self.character_mut(character)?;
+                let previous = std::mem::replace(&mut target.name, name.clone());
+
+                let entry = HistoryEntry {
+                    description: format!("Rename character {previous} to {name}"),
+                    undo: CampaignCommand::RenameCharacter { character, name: previous },
+                    redo: CampaignCommand::RenameCharacter { character, name },
+                };
+
+                (CampaignChange::Character(character), entry)
+            }
+            CampaignCommand::AddWish { character, item } => {
+                let target = self.character_mut(character)?;
+                let already_wished = target.wish_list.contains(&item);
+                if !already_wished {
+                    target.wish_list.push(item.clone());
+                }
+
+                let entry = HistoryEntry {
+                    description: format!("Add {item} to the wish list of {}", target.name),
+                    undo: if already_wished {
+                        CampaignCommand::AddWish { character, item: item.clone() }
+                    } else {
+                        CampaignCommand::RemoveWish { character, item: item.clone() }
+                    },
+                    redo: CampaignCommand::AddWish { character, item },
+                };
+
+                (CampaignChange::Character(character), entry)
+            }
+            CampaignCommand::RemoveWish { character, item } => {
+                let target = self.character_mut(character)?;
+                let was_wished = target.wish_list.contains(&item);
+                target.wish_list.retain(|wish| *wish != item);
+
+                let entry = HistoryEntry {
+                    description: format!("Remove {item} from the wish list of {}", target.name),
+                    undo: if was_wished {
+                        CampaignCommand::AddWish { character, item: item.clone() }
+                    } else {
+                        CampaignCommand::RemoveWish { character, item: item.clone() }
+                    },
+                    redo: CampaignCommand::RemoveWish { character, item },
+                };
+
+                (CampaignChange::Character(character), entry)
+            }
+            CampaignCommand::ReplaceWish { character, item, with } => {
+                let target = self.character_mut(character)?;
+
+                if target.wish_list.contains(&with) {
+                    anyhow::bail!("{} already wishes for {with}", target.name);
+                }
+                let Some(wish) = target.wish_list.iter_mut().find(|wish| **wish == item) else {
+                    anyhow::bail!("{} does not wish for {item}", target.name);
+                };
+                *wish = with.clone();
+
+                let entry = HistoryEntry {
+                    description: format!("Replace {item} with {with} on the wish list of {}", target.name),
+                    undo: CampaignCommand::ReplaceWish { character, item: with.clone(), with: item.clone() },
+                    redo: CampaignCommand::ReplaceWish { character, item, with },
+                };
+
+                (CampaignChange::Character(character), entry)
+            }
+            CampaignCommand::UpsertItem(item_type) => {
+                let identifier = item_type.identifier.clone();
+                let previous = match self.campaign.registry.get_mut(identifier.clone()) {
+                    Some(existing) => Some(std::mem::replace(existing, item_type.clone())),
+                    None => {
+                        self.campaign.registry.add(item_type.clone());
+                        None
+                    }
+                };
+
+                let entry = HistoryEntry {
+                    description: format!("Save item {}", item_type.name),
+                    undo: match previous {
+                        Some(previous) => CampaignCommand::UpsertItem(previous),
+                        None => CampaignCommand::RemoveItem(identifier),
+                    },
+                    redo: CampaignCommand::UpsertItem(item_type),
+                };
+
+                (CampaignChange::Registry, entry)
+            }
+            CampaignCommand::RemoveItem(identifier) => {
+                let removed = self
+                    .campaign
+                    .registry
+                    .remove(&identifier)
+                    .ok_or_else(|| anyhow::format_err!("There is no item {identifier}"))?;
+
+                let entry = HistoryEntry {
+                    description: format!("Delete item {}", removed.name),
+                    undo: CampaignCommand::UpsertItem(removed),
+                    redo: CampaignCommand::RemoveItem(identifier),
+                };
+
+                (CampaignChange::Registry, entry)
+            }
+        };
+
+        self.changes.push(change);
+
+        Ok(entry)
+    }
+
+    fn clock_snapshot(&self) -> CampaignCommand {
+        CampaignCommand::RestoreClock {
+            date: self.campaign.date,
+            shops: self.campaign.shops.clone(),
+            ledger_len: self.campaign.ledger.len(),
+        }
+    }
+
+    fn shop_mut(&mut self, shop: usize) -> anyhow::Result<&mut Shop> {
+        self.campaign
+            .shops
+            .get_mut(shop)
+            .ok_or_else(|| anyhow::format_err!("There is no shop #{shop}"))
+    }
+
+    /// Shops are saved to files named after them, so names must be unique and not empty.
+    fn ensure_unique_shop_name(&self, name: &str, except: Option<usize>) -> anyhow::Result<()> {
+        ensure_unique_name("shop", self.campaign.shops.iter().map(|shop| &shop.name), name, except)
+    }
+
+    /// Characters are saved to files named after them, just like shops.
+    fn ensure_unique_character_name(&self, name: &str, except: Option<usize>) -> anyhow::Result<()> {
+        ensure_unique_name(
+            "character",
+            self.campaign.characters.iter().map(|character| &character.name),
+            name,
+            except,
+        )
+    }
+
+    fn character_mut(&mut self, character: usize) -> anyhow::Result<&mut Character> {
+        self.campaign
+            .characters
+            .get_mut(character)
+            .ok_or_else(|| anyhow::format_err!("There is no character #{character}"))
+    }
+}
+
+fn ensure_unique_name<'n>(
+    kind: &str,
+    names: impl Iterator<Item = &'n String>,
+    name: &str,
+    except: Option<usize>,
+) -> anyhow::Result<()> {
+    if name.trim().is_empty() {
+        anyhow::bail!("A {kind} needs a name");
+    }
+
+    if names.enumerate().any(|(idx, existing)| Some(idx) != except && existing == name) {
+        anyhow::bail!("There already is a {kind} called {name}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use crate::data::{
+        character::Character,
+        item::{ItemCategory, ItemType, Rarity},
+        shop::{RestockRules, Shop, Stock, StockedItem},
+    };
+    use crate::data::campaign::load_campaign_folder;
+    use crate::CampaignFolder;
+
+    use super::{CampaignChange, CampaignCommand, CampaignStore};
+
+    fn store() -> CampaignStore {
+        let mut folder = CampaignFolder::empty("Test".into());
+        folder.item_registry.add(ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into()));
+        folder.shops = vec![Shop::with_inventory(
+            "Tina's".into(),
+            None,
+            vec![StockedItem {
+                identifier: "Mantel".into(),
+                stock: Stock::Stocked(NonZero::<u32>::MIN),
+                days_sold_out: 0,
+            }],
+            RestockRules::default(),
+        )];
+        folder.characters = vec![Character {
+            name: "Juri".into(),
+            wish_list: vec![],
+        }];
+
+        CampaignStore::new(folder.into_campaign())
+    }
+
+    #[test]
+    fn commands_change_the_campaign() -> anyhow::Result<()> {
+        let mut store = store();
+
+        store.apply(CampaignCommand::SetStock { shop: 0, item: "Mantel".into(), stock: Stock::Sold })?;
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+
+        assert_eq!(store.campaign().shops[0].stock_of(&"Mantel".into()), Some(&Stock::Sold));
+        assert_eq!(store.campaign().characters[0].wish_list, vec!["Mantel".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_commands_are_rejected() {
+        let mut store = store();
+
+        assert!(store.apply(CampaignCommand::SetStock { shop: 3, item: "Mantel".into(), stock: Stock::Sold }).is_err());
+        assert!(store.apply(CampaignCommand::SetStock { shop: 0, item: "Ring".into(), stock: Stock::Sold }).is_err());
+        assert!(store.apply(CampaignCommand::RemoveWish { character: 1, item: "Mantel".into() }).is_err());
+    }
+
+    #[test]
+    fn subscribers_see_each_change_once() -> anyhow::Result<()> {
+        let mut store = store();
+        store.apply(CampaignCommand::AdvanceTime { hours: 1 })?;
+
+        let mut cursor = store.subscribe();
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+
+        assert_eq!(store.changes_since(&mut cursor), vec![CampaignChange::Character(0)]);
+        assert!(store.changes_since(&mut cursor).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn undo_and_redo_revert_and_repeat_commands() -> anyhow::Result<()> {
+        let mut store = store();
+        let stocked = store.campaign().shops[0].clone();
+
+        store.apply(CampaignCommand::SetStock { shop: 0, item: "Mantel".into(), stock: Stock::Sold })?;
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+
+        assert!(store.undo()?);
+        assert!(store.undo()?);
+        assert!(!store.undo()?);
+        assert_eq!(store.campaign().shops[0], stocked);
+        assert!(store.campaign().characters[0].wish_list.is_empty());
+
+        assert!(store.redo()?);
+        assert_eq!(store.campaign().shops[0].stock_of(&"Mantel".into()), Some(&Stock::Sold));
+        assert_eq!(store.history().len(), 1);
+        assert_eq!(store.redo_history().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn redo_of_advanced_time_repeats_the_same_restock() -> anyhow::Result<()> {
+        let mut store = store();
+        store.campaign.shops[0].restock.new_item_chance = 1.0;
+        store.apply(CampaignCommand::UpsertItem(ItemType::new("Ring".into(), Rarity::Common, ItemCategory::WondrousItem, "".into())))?;
+
+        let start = store.campaign().date;
+        store.apply(CampaignCommand::AdvanceTime { hours: 72 })?;
+        let restocked = store.campaign().shops.clone();
+
+        store.undo()?;
+        assert_eq!(store.campaign().date, start);
+        assert_eq!(store.campaign().ledger.len(), 0);
+
+        store.redo()?;
+        assert_eq!(store.campaign().shops, restocked);
+        Ok(())
+    }
+
+    #[test]
+    fn shops_can_be_edited_and_restored() -> anyhow::Result<()> {
+        let mut store = store();
+        let original = store.campaign().shops.clone();
+
+        store.apply(CampaignCommand::InsertShop { index: 0, shop: Shop::new("Harry's".into()) })?;
+        store.apply(CampaignCommand::RenameShop { shop: 1, name: "Tina's Tailor".into() })?;
+        store.apply(CampaignCommand::RemoveStock { shop: 1, item: "Mantel".into() })?;
+        store.apply(CampaignCommand::InsertStock {
+            shop: 0,
+            index: 0,
+            item: StockedItem { identifier: "Mantel".into(), stock: Stock::Infinite, days_sold_out: 0 },
+        })?;
+        store.apply(CampaignCommand::RemoveShop { shop: 1 })?;
+
+        assert_eq!(store.campaign().shops.len(), 1);
+        assert_eq!(store.campaign().shops[0].stock_of(&"Mantel".into()), Some(&Stock::Infinite));
+
+        while store.undo()? {}
+        assert_eq!(store.campaign().shops, original);
+        Ok(())
+    }
+
+    #[test]
+    fn shop_names_stay_unique() {
+        let mut store = store();
+
+        assert!(store.apply(CampaignCommand::InsertShop { index: 1, shop: Shop::new("Tina's".into()) }).is_err());
+        assert!(store.apply(CampaignCommand::InsertShop { index: 1, shop: Shop::new(" ".into()) }).is_err());
+        assert!(store.apply(CampaignCommand::RenameShop { shop: 0, name: "Tina's".into() }).is_ok());
+    }
+
+    #[test]
+    fn characters_and_wishes_can_be_edited_and_restored() -> anyhow::Result<()> {
+        let mut store = store();
+        let original = store.campaign().characters.clone();
+
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantle".into() })?;
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Ring".into() })?;
+        store.apply(CampaignCommand::ReplaceWish { character: 0, item: "Mantle".into(), with: "Mantel".into() })?;
+        assert_eq!(store.campaign().characters[0].wish_list, vec!["Mantel".to_string(), "Ring".to_string()]);
+        assert!(store.apply(CampaignCommand::ReplaceWish { character: 0, item: "Ring".into(), with: "Mantel".into() }).is_err());
+
+        store.apply(CampaignCommand::InsertCharacter {
+            index: 0,
+            character: Character { name: "Frodo".into(), wish_list: vec![] },
+        })?;
+        assert!(store.apply(CampaignCommand::RenameCharacter { character: 1, name: "Frodo".into() }).is_err());
+        store.apply(CampaignCommand::RenameCharacter { character: 1, name: "Gandalf the White".into() })?;
+        store.apply(CampaignCommand::RemoveCharacter { character: 0 })?;
+
+        assert_eq!(store.campaign().characters.len(), 1);
+        assert_eq!(store.campaign().characters[0].name, "Gandalf the White");
+
+        while store.undo()? {}
+        assert_eq!(store.campaign().characters, original);
+        Ok(())
+    }
+
+    #[test]
+    fn journal_is_replayed_until_the_campaign_is_saved() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-journal-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut saved = store();
+        saved.campaign.save(&dir)?;
+        let reload = || -> anyhow::Result<CampaignStore> {
+            Ok(CampaignStore::open(load_campaign_folder(&dir)?.into_campaign(), &dir))
+        };
+
+        let mut crashed = reload()?;
+        crashed.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+        crashed.apply(CampaignCommand::AdvanceTime { hours: 30 })?;
+        let expected = crashed.campaign().clone();
+        drop(crashed);
+
+        let mut recovered = reload()?;
+        assert!(recovered.has_unsaved_changes());
+        assert_eq!(recovered.campaign().characters, expected.characters);
+        assert_eq!(recovered.campaign().date, expected.date);
+        assert_eq!(recovered.history().len(), 2);
+
+        recovered.save()?;
+        let reloaded = reload()?;
+        std::fs::remove_dir_all(&dir)?;
+
+        assert!(!reloaded.has_unsaved_changes());
+        assert_eq!(reloaded.campaign().date, expected.date);
+        Ok(())
+    }
+
+    #[test]
+    fn autosave_keeps_the_history() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-autosave-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut saved = store();
+        saved.campaign.save(&dir)?;
+        let mut store = CampaignStore::open(load_campaign_folder(&dir)?.into_campaign(), &dir);
+
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+        store.autosave()?;
+        assert!(!store.has_unsaved_changes());
+
+        assert!(store.undo()?);
+        std::fs::remove_dir_all(&dir)?;
+
+        assert!(store.campaign().characters[0].wish_list.is_empty());
+        assert!(store.has_unsaved_changes());
+        assert_eq!(store.redo_history().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn undoing_a_stored_item_removes_its_file() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-item-file-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut store = CampaignStore::open(store().campaign, &dir);
+        let ring = ItemType::new("Ring".into(), Rarity::Common, ItemCategory::WondrousItem, "".into());
+        store.apply(CampaignCommand::UpsertItem(ring))?;
+        let path = store.save_item(&"Ring".into())?;
+
+        store.undo()?;
+        let undone = path.exists();
+        store.redo()?;
+        let redone = path.exists();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert!(!undone);
+        assert!(redone);
+        Ok(())
+    }
+
+    #[test]
+    fn a_new_command_discards_undone_commands() -> anyhow::Result<()> {
+        let mut store = store();
+
+        store.apply(CampaignCommand::AddWish { character: 0, item: "Mantel".into() })?;
+        store.undo()?;
+        store.apply(CampaignCommand::AdvanceTime { hours: 1 })?;
+
+        assert!(store.redo_history().is_empty());
+        store.mark_saved();
+        assert!(store.history().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::campaign::CampaignCommand;
+
+pub const JOURNAL_FILE: &str = "journal.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Milliseconds since the unix epoch.
+    pub written_at: u128,
+    pub command: CampaignCommand,
+}
+
+/// Append-only log of the commands executed since the last save, one JSON object per line.
+///
+/// Every line is flushed right away, so after a crash the next start can replay it.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+fn millis_since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis())
+}
+
+impl Journal {
+    pub fn new(campaign_folder: &Path) -> Self {
+        Self {
+            path: campaign_folder.join(JOURNAL_FILE),
+        }
+    }
+
+    pub fn append(&self, command: &CampaignCommand) -> anyhow::Result<()> {
+        let entry = JournalEntry {
+            written_at: millis_since_epoch(SystemTime::now()),
+            command: command.clone(),
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+
+        Ok(())
+    }
+
+    /// Commands written at or after `since`, oldest first.
+    ///
+    /// A line that cannot be read, e.g. because the app crashed while writing it, ends the replay.
+    pub fn read_since(&self, since: SystemTime) -> anyhow::Result<Vec<CampaignCommand>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+
+        let since = millis_since_epoch(since);
+        let mut commands = vec![];
+
+        for line in BufReader::new(std::fs::File::open(&self.path)?).lines() {
+            match serde_json::from_str::<JournalEntry>(&line?) {
+                Ok(entry) if entry.written_at >= since => commands.push(entry.command),
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("Stopped reading journal {:?} at a broken entry: {e:?}", self.path);
+                    break;
+                }
+            }
+        }
+
+        Ok(commands)
+    }
+
+    pub fn clear(&self) -> anyhow::Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1 +1,4 @@
+pub mod campaign;
+pub mod journal;
+pub mod player_view;
 pub mod shop;
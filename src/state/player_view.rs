@@ -0,0 +1,168 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{campaign::write_yaml, gold::GoldAmount, item::ItemType};
+
+pub const PLAYER_VIEW_FILE: &str = "player-view.yaml";
+
+/// An item the dungeon master shows, with its price once it is rolled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerCard {
+    pub item_type: ItemType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<GoldAmount>,
+}
+
+/// Everything the players may see. Stock, price formulas and notes never end up in here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerView {
+    /// Name of the shop whose offer is shown, if it is an offer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shop: Option<String>,
+    pub cards: Vec<PlayerCard>,
+    /// The card the dungeon master is talking about.
+    #[serde(default)]
+    pub focused: usize,
+}
+
+impl PlayerView {
+    pub fn single(item_type: ItemType) -> Self {
+        Self {
+            shop: None,
+            cards: vec![PlayerCard { item_type, price: None }],
+            focused: 0,
+        }
+    }
+}
+
+/// The file in the campaign folder through which the instance of the dungeon master
+/// tells a player view instance what to show.
+#[derive(Debug, Clone)]
+pub struct PlayerScreen {
+    path: PathBuf,
+}
+
+impl PlayerScreen {
+    pub fn new(campaign_folder: &Path) -> Self {
+        Self {
+            path: campaign_folder.join(PLAYER_VIEW_FILE),
+        }
+    }
+
+    pub fn show(&self, view: &PlayerView) -> anyhow::Result<()> {
+        write_yaml(&self.path, view)
+    }
+
+    /// Shows nothing anymore.
+    pub fn clear(&self) -> anyhow::Result<()> {
+        self.show(&PlayerView::default())
+    }
+
+    pub fn read(&self) -> anyhow::Result<PlayerView> {
+        if !self.path.exists() {
+            return Ok(PlayerView::default());
+        }
+
+        Ok(serde_yaml::from_reader(std::fs::File::open(&self.path)?)?)
+    }
+
+    /// Watches the file, the first [`PlayerScreenWatcher::poll`] reads what is shown right now.
+    pub fn watch(self) -> PlayerScreenWatcher {
+        PlayerScreenWatcher {
+            screen: self,
+            last_seen: None,
+        }
+    }
+
+    fn version(&self) -> Option<(SystemTime, u64)> {
+        let meta = std::fs::metadata(&self.path).ok()?;
+        Some((meta.modified().ok()?, meta.len()))
+    }
+}
+
+/// Notices when the dungeon master shows something new by checking the modification time of the file.
+#[derive(Debug)]
+pub struct PlayerScreenWatcher {
+    screen: PlayerScreen,
+    last_seen: Option<Option<(SystemTime, u64)>>,
+}
+
+impl PlayerScreenWatcher {
+    /// The new view if the file changed since the last poll.
+    pub fn poll(&mut self) -> Option<anyhow::Result<PlayerView>> {
+        let version = self.screen.version();
+        if self.last_seen == Some(version) {
+            return None;
+        }
+
+        self.last_seen = Some(version);
+        Some(self.screen.read())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{
+        gold::AsGoldCurrency,
+        item::{ItemCategory, Rarity},
+    };
+
+    use super::*;
+
+    fn temp_folder(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn offer() -> PlayerView {
+        PlayerView {
+            shop: Some("Veys".into()),
+            cards: vec![PlayerCard {
+                item_type: ItemType::new(
+                    "Cape of the Mountebank".into(),
+                    Rarity::Rare,
+                    ItemCategory::WondrousItem,
+                    "Smells of brimstone.".into(),
+                ),
+                price: Some(750.gold()),
+            }],
+            focused: 0,
+        }
+    }
+
+    #[test]
+    fn the_players_see_what_was_shown() {
+        let dir = temp_folder("player-view");
+        let screen = PlayerScreen::new(&dir);
+
+        screen.show(&offer()).unwrap();
+
+        assert_eq!(screen.read().unwrap(), offer());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn the_watcher_reports_only_changes() {
+        let dir = temp_folder("player-view-watch");
+        let screen = PlayerScreen::new(&dir);
+        let mut watcher = screen.clone().watch();
+
+        assert_eq!(watcher.poll().unwrap().unwrap(), PlayerView::default());
+        assert!(watcher.poll().is_none());
+
+        screen.show(&offer()).unwrap();
+        assert_eq!(watcher.poll().unwrap().unwrap(), offer());
+        assert!(watcher.poll().is_none());
+
+        screen.clear().unwrap();
+        assert_eq!(watcher.poll().unwrap().unwrap(), PlayerView::default());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
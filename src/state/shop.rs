@@ -1,9 +1,9 @@
-use crate::data::{item::ItemType, shop::{Shop, StockedItem}};
+use crate::data::shop::{Shop, StockedItem};
 
 pub struct ShopState<'a> {
-    shop: &'a Shop<'a>,
+    shop: &'a Shop,
 
-    offers: Vec<&'a StockedItem<'a>>,
+    offers: Vec<&'a StockedItem>,
     num_offers: u8,
 }
 
@@ -20,7 +20,7 @@ impl<'a> ShopState<'a> {
         self.offers.as_slice()
     }
 
-    pub fn regenerate_offers(&mut self) -> &[&StockedItem<'a>] {
+    pub fn regenerate_offers(&mut self) -> &[&'a StockedItem] {
         self.offers = self.shop.produce_offer(self.num_offers);
         self.offers.as_slice()
     }
@@ -1,138 +1,171 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 use layout::Flex;
 use ratatui::prelude::*;
 use ratatui::widgets::canvas::Shape;
 use ratatui::widgets::{Borders, Padding};
 use ratatui::{
-    crossterm::{
-        event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
-        style::Color,
-    },
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     style::Style,
-    widgets::{Block, Clear, Paragraph, TableState},
+    widgets::{Block, Cell, Clear, Paragraph, Row, Table},
     DefaultTerminal, Frame,
 };
-use style::palette::material::{self, AccentedPalette, BLACK, BLUE, GRAY as SLATE, GREEN, RED, WHITE, YELLOW};
 
-use crate::data::campaign::Campaign;
-use crate::data::shop::Shop;
+use crate::data::calendar::{Calendar, Stamped, HOURS_PER_DAY};
+use crate::data::campaign::WishedItem;
+use crate::data::item::{ItemIdentifier, ItemType};
+use crate::data::shop::{InventoryChange, ShopChangelog};
+use crate::config::settings::{PricingStrategy, SharedSettings};
+use crate::config::theme;
+use crate::state::campaign::{CampaignChange, CampaignCommand, ChangeCursor, HistoryEntry, SharedCampaign};
 
 use super::characters::CharactersPage;
+use super::flow::{KeyHandler, KeyMap};
 use super::home::HomePage;
+use super::items::ItemsPage;
+use super::key::KeyMenu;
 use super::page::RenderablePage;
 use super::settings::SettingsPage;
 use super::shops::ShopsPage;
-use crate::ui::translator::I18ner;
+use super::weather::WeatherPage;
+use crate::ui::translator::{I18nPhrase, I18ner, Translator};
 
 enum AppPopup {
     WhatToDoWithShop { index: usize },
 }
 
 struct App<'a> {
-    // registry: ItemRegistry,
-    // campaign: &'a mut Campaign<'a>,
-    name: &'a String,
+    store: SharedCampaign,
+    changes: ChangeCursor,
+
+    /// Set by `--autosave-interval`, otherwise the settings decide.
+    autosave_override: Option<Duration>,
+    last_save: Instant,
 
-    registry_state: TableState,
     is_running: bool,
 
     overlay: Option<Box<dyn AppScreen>>,
 
-    pages: Vec<Box<dyn RenderablePage>>,
     tabs: Vec<Tab<'a>>,
 
     selected_tab: usize,
 
     messages: Vec<AppMessage>,
 
-    i18n: &'a dyn I18ner
+    settings: SharedSettings,
 }
 
-#[derive(Debug, Clone)]
-pub enum AppCategory {
-    Shops,
-    Weather,
-    Settings,
-}
-
-// const TABS: [AppCategory; 3] = [AppCategory::Shops, AppCategory::Weather, AppCategory::Settings];
-
-const APP_TITLE: &str = "DnD Simp";
-
 #[derive(Debug, Clone)]
 pub enum AppMessage {
-    SwitchCategory(AppCategory),
     PreviousCategory,
     NextCategory,
+    AdvanceTime { hours: u32 },
+    Undo,
+    Redo,
+    ShowHistory,
+    ShowWishReport,
+    ShowHelp,
+    Save,
+    Quit,
 }
 
+/// How long to wait for input before the main loop checks for due auto-saves.
+const EVENT_POLL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A tab owns its page, so drawing and input always reach the same page.
 pub struct Tab<'a> {
-    title: String,
-    page: Box<&'a dyn RenderablePage>,
-    color: AccentedPalette
+    page: Box<dyn RenderablePage + 'a>,
 }
 
 impl<'a> Tab<'a> {
-    pub fn new(title: String,
-        page: Box<&'a dyn RenderablePage>,
-        color: AccentedPalette) -> Self {
-            Self {
-                title, page, color
-            }
-        }
+    pub fn new(page: Box<dyn RenderablePage + 'a>) -> Self {
+        Self { page }
+    }
 }
 
-// pub fn home_wrapper<'a>(camp: &'a mut Campaign) -> Box<dyn RenderablePage > {
-//     Box::new(HomePage::new())
-// }
-
-// pub fn char_wrapper<'a>(camp: &'a mut Campaign) -> Box<dyn RenderablePage + 'a> {
-//     Box::new(CharactersPage::new(camp.characters.iter().collect()))
-// }
-
-// pub fn shop_wrapper<'a>(camp: &'a mut Campaign) -> Box<dyn RenderablePage + 'a> {
-//     Box::new(ShopsPage::new(camp.shops.iter().collect()))
-// }
-
-
 impl<'a> App<'a> {
-    pub fn new(campaign: &'a mut Campaign<'a>, i18n: &'a dyn I18ner) -> anyhow::Result<Self> {
+    pub fn new(store: SharedCampaign, settings: SharedSettings, autosave_override: Option<Duration>) -> anyhow::Result<Self> {
+        let changes = store.borrow().subscribe();
+
         Ok(Self {
-            name: &campaign.name,
-            registry_state: TableState::default().with_selected(Some(0)),
             is_running: true,
             overlay: None,
             tabs: vec![
-                Tab::new("Home".into(), Box::new(&HomePage::new()), BLUE),
-                Tab::new("Characters".into(), Box::new(&CharactersPage::new(campaign.characters.iter().collect())), RED),
-                // Tab::new("Shops".into(), Box::new(ShopsPage::new(campaign.shops.iter().collect())), YELLOW),
-            ],
-            pages: vec![
-                Box::new(HomePage::new()),
-                // @todo fix this
-                // Box::new(ShopsPage::new(campaign.shops.clone())),
-                // Box::new(CharactersPage::new(
-                //     campaign.characters.iter().map(
-                //         |ch| ch
-                //     ).collect()
-                // )),
-                Box::new(SettingsPage::new(),)
+                Tab::new(Box::new(HomePage::new())),
+                Tab::new(Box::new(CharactersPage::new(store.clone(), settings.clone()))),
+                Tab::new(Box::new(ShopsPage::new(store.clone(), settings.clone()))),
+                Tab::new(Box::new(ItemsPage::new(store.clone()))),
+                Tab::new(Box::new(WeatherPage::new(store.clone()))),
+                Tab::new(Box::new(SettingsPage::new(settings.clone()))),
             ],
             selected_tab: 0,
             messages: vec![],
-            i18n
+            store,
+            changes,
+            autosave_override,
+            last_save: Instant::now(),
+            settings,
         })
     }
 
-    pub fn name(&self) -> &String {
-        self.name
+    /// The campaign name, marked with `*` while there are unsaved changes.
+    pub fn name(&self) -> String {
+        let store = self.store.borrow();
+        if store.has_unsaved_changes() {
+            format!("{} *", store.campaign().name)
+        } else {
+            store.campaign().name.clone()
+        }
     }
 
     pub fn exit(&mut self) {
+        if self.autosave_interval().is_some() && self.store.borrow().has_unsaved_changes() {
+            self.autosave();
+        }
+
         self.is_running = false;
     }
 
-    pub fn current_overlay(&mut self) -> &mut Box<dyn RenderablePage> {
-        &mut self.pages[self.selected_tab]
+    fn save(&mut self) {
+        match self.store.borrow_mut().save() {
+            Ok(()) => log::info!("Campaign saved"),
+            Err(e) => log::error!("Could not save the campaign: {e:?}"),
+        }
+
+        self.last_save = Instant::now();
+    }
+
+    /// Saving on its own keeps the history, so undo still works after it.
+    fn autosave(&mut self) {
+        match self.store.borrow_mut().autosave() {
+            Ok(()) => log::info!("Campaign auto-saved"),
+            Err(e) => log::error!("Could not auto-save the campaign: {e:?}"),
+        }
+
+        self.last_save = Instant::now();
+    }
+
+    /// Read on every check, so changing the setting takes effect right away.
+    fn autosave_interval(&self) -> Option<Duration> {
+        match self.autosave_override {
+            Some(interval) => (!interval.is_zero()).then_some(interval),
+            None => self.settings.borrow().settings().autosave_interval(),
+        }
+    }
+
+    fn autosave_if_due(&mut self) {
+        let Some(interval) = self.autosave_interval() else {
+            return;
+        };
+
+        if self.last_save.elapsed() >= interval && self.store.borrow().has_unsaved_changes() {
+            self.autosave();
+        }
+    }
+
+    pub fn current_page(&mut self) -> &mut Box<dyn RenderablePage + 'a> {
+        &mut self.tabs[self.selected_tab].page
     }
 
     /// runs the application's main loop until the user quits
@@ -149,7 +182,7 @@ impl<'a> App<'a> {
         while let Some(msg) = self.messages.pop() {
             match &msg {
                 AppMessage::NextCategory => {
-                    if self.selected_tab < self.pages.len() - 1 {
+                    if self.selected_tab < self.tabs.len() - 1 {
                         self.selected_tab += 1;
                     }
                 }
@@ -158,16 +191,92 @@ impl<'a> App<'a> {
                         self.selected_tab -= 1;
                     }
                 }
-                AppMessage::SwitchCategory(cat) => match cat {
-                    AppCategory::Shops => self.selected_tab = 0,
-                    AppCategory::Weather => self.selected_tab = 1,
-                    AppCategory::Settings => self.selected_tab = 2,
-                },
+                AppMessage::AdvanceTime { hours } => {
+                    let result = self.store
+                        .borrow_mut()
+                        .apply(CampaignCommand::AdvanceTime { hours: *hours });
+
+                    if let Err(e) = result {
+                        log::error!("Could not advance time: {e:?}");
+                    }
+                }
+                AppMessage::Undo => {
+                    if let Err(e) = self.store.borrow_mut().undo() {
+                        log::error!("Could not undo: {e:?}");
+                    }
+                }
+                AppMessage::Redo => {
+                    if let Err(e) = self.store.borrow_mut().redo() {
+                        log::error!("Could not redo: {e:?}");
+                    }
+                }
+                AppMessage::Save => self.save(),
+                AppMessage::Quit => self.exit(),
+                AppMessage::ShowHelp => {
+                    let i18n = Translator::new(self.settings.borrow().settings());
+                    let page = &self.tabs[self.selected_tab].page;
+                    self.overlay = Some(Box::new(HelpPopup::new(
+                        page.title(&i18n),
+                        page.get_handlers(),
+                        self.global_handlers(),
+                        popup_keymap(None).handlers(),
+                    )));
+                }
+                AppMessage::ShowHistory => {
+                    let store = self.store.borrow();
+                    self.overlay = Some(Box::new(HistoryPopup::new(
+                        store.history().to_vec(),
+                        store.redo_history().to_vec(),
+                    )));
+                }
+                AppMessage::ShowWishReport => {
+                    let report = self.store.borrow().campaign().wish_report();
+                    let pricing = self.settings.borrow().settings().pricing;
+                    self.overlay = Some(Box::new(WishReport::new(report, pricing)));
+                }
             }
         }
+
+        self.collect_changes();
+        self.autosave_if_due();
+    }
+
+    /// Opens a report if shops restocked since the last update.
+    fn collect_changes(&mut self) {
+        let store = self.store.borrow();
+        let restocks: Vec<_> = store
+            .changes_since(&mut self.changes)
+            .into_iter()
+            .flat_map(|change| match change {
+                CampaignChange::Clock { restocks } => restocks,
+                _ => vec![],
+            })
+            .collect();
+
+        if !restocks.is_empty() && self.overlay.is_none() {
+            let registry = &store.campaign().registry;
+            let item_types = restocks
+                .iter()
+                .flat_map(|restock| &restock.value.changes)
+                .filter_map(|change| {
+                    let (InventoryChange::Returned(id) | InventoryChange::Added(id) | InventoryChange::Removed(id)) = change;
+                    registry.get(id).map(|item_type| (id.clone(), item_type.clone()))
+                })
+                .collect();
+
+            self.overlay = Some(Box::new(RestockReport::new(
+                store.campaign().calendar.clone(),
+                restocks,
+                item_types,
+            )));
+        }
     }
 
     fn draw(&mut self, frame: &mut Frame) {
+        let i18n = Translator::new(self.settings.borrow().settings());
+        let palette = theme::palette();
+        let app_title = i18n.i18n(I18nPhrase::AppTitle);
+
         let [title_area, subline_area, border_area] = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
@@ -180,7 +289,7 @@ impl<'a> App<'a> {
         let [app_name_area, _, campaign_name_area, _, object_ident_area] = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![
-                Constraint::Length(APP_TITLE.len() as u16 + 2),
+                Constraint::Length(app_title.chars().count() as u16 + 2),
                 Constraint::Length(1),
                 Constraint::Length(self.name().len() as u16 + 2),
                 Constraint::Length(1),
@@ -188,30 +297,33 @@ impl<'a> App<'a> {
             ])
             .areas(title_area);
 
-        let app_name = ratatui::widgets::Paragraph::new(APP_TITLE)
+        let app_name = ratatui::widgets::Paragraph::new(app_title)
             .alignment(Alignment::Center)
-            .bg(Color::Blue);
+            .bg(palette.app_title);
         frame.render_widget(app_name, app_name_area);
 
-        let campaign_name = ratatui::widgets::Paragraph::new(self.name().as_str())
+        let campaign_name = ratatui::widgets::Paragraph::new(self.name())
             .alignment(Alignment::Center)
             .bold()
-            .bg(Color::Yellow);
+            .bg(palette.campaign_title);
         frame.render_widget(campaign_name, campaign_name_area);
 
-        let object_ident = ratatui::widgets::Paragraph::new(self.name().as_str())
+        let date = self.store.borrow().campaign().formatted_date();
+        let object_ident = ratatui::widgets::Paragraph::new(date)
             .alignment(Alignment::Center)
-            .bg(Color::Grey);
+            .bg(palette.date);
         frame.render_widget(object_ident, object_ident_area);
 
         let page_tabs = ratatui::widgets::Tabs::new(
             self.tabs
                 .iter()
                 .enumerate()
-                .map(|(idx, tab)| ratatui::text::Line::raw(format!("  {}  ", tab.title))
-                    .bg(if idx == self.selected_tab {
-                        tab.color.a100 } else { tab.color.c900 })
-        ))
+                .map(|(idx, tab)| {
+                    let colors = palette.tabs[idx % palette.tabs.len()];
+                    ratatui::text::Line::raw(format!("  {}  ", tab.page.title(&i18n)))
+                        .bg(if idx == self.selected_tab { colors.selected } else { colors.idle })
+                }),
+        )
         .select(self.selected_tab)
         .highlight_style(Style::default())
         .padding("", "")
@@ -227,16 +339,24 @@ impl<'a> App<'a> {
             .border_set(symbols::border::PROPORTIONAL_TALL)
             .borders(Borders::ALL)
             .padding(Padding::horizontal(3))
-            .border_style(RED.a100);
+            .border_style(palette.border);
 
-        let content_area = block.inner(border_area);
+        let [content_area, hint_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(3)])
+            .areas(block.inner(border_area));
 
         frame.render_widget(page_tabs, tab_area);
         frame.render_widget(block, border_area);
 
-        // self.pages[self.selected_tab].draw(frame, content_area, self.i18n);
+        self.current_page().draw(frame, content_area, &i18n);
 
-        self.tabs[self.selected_tab].page.draw(frame, content_area, self.i18n);
+        let hints = KeyMenu {
+            i18n: &i18n,
+            handles: self.tabs[self.selected_tab].page.get_handlers(),
+            global_handles: self.global_handlers(),
+        };
+        frame.render_widget(hints, hint_area);
 
         // let l = ratatui::widgets::Table::new(
         //     self.campaign
@@ -255,74 +375,173 @@ impl<'a> App<'a> {
         // frame.render_stateful_widget(l, content_area, &mut self.registry_state);
 
         if let Some(over) = &self.overlay {
-            over.draw(frame, popup_area(border_area, 50, 50));
+            let (percent_x, percent_y) = over.size();
+            over.draw(frame, popup_area(border_area, percent_x, percent_y), &i18n);
         }
     }
 
     fn handle_events(&mut self) -> anyhow::Result<()> {
-        let ev = event::read()?;
-        self.current_overlay().handle_and_transact(&ev);
+        if !event::poll(EVENT_POLL_TIMEOUT)? {
+            return Ok(());
+        }
 
-        match ev {
+        match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(key_event)
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self.handle_key_event(key_event),
+            Event::Key(_) => {}
+            other => {
+                if self.overlay.is_none() {
+                    self.current_page().handle_and_transact(&other);
+                }
             }
-            _ => {}
         };
 
         Ok(())
     }
 
+    /// Keys that work on every page. While a page takes text input, only shortcuts with Ctrl or Alt remain.
+    fn global_keymap(&self, page_captures_input: bool) -> KeyMap<AppMessage> {
+        KeyMap::new("global")
+            .bind_ctrl("save", KeyCode::Char('s'), I18nPhrase::Save, AppMessage::Save)
+            .bind_ctrl("undo", KeyCode::Char('z'), I18nPhrase::Undo, AppMessage::Undo)
+            .bind_ctrl("redo", KeyCode::Char('y'), I18nPhrase::Redo, AppMessage::Redo)
+            .bind("next-tab", KeyCode::Tab, I18nPhrase::NextTab, AppMessage::NextCategory)
+            .bind_hidden("previous-tab", KeyCode::BackTab, I18nPhrase::PreviousTab, AppMessage::PreviousCategory)
+            .bind("next-hour", KeyCode::Char('t'), I18nPhrase::PlusOneHour, AppMessage::AdvanceTime { hours: 1 })
+            .bind("next-day", KeyCode::Char('T'), I18nPhrase::PlusOneDay, AppMessage::AdvanceTime { hours: HOURS_PER_DAY })
+            .bind("history", KeyCode::Char('h'), I18nPhrase::History, AppMessage::ShowHistory)
+            .bind("wishes", KeyCode::Char('w'), I18nPhrase::Wishes, AppMessage::ShowWishReport)
+            .bind("help", KeyCode::Char('?'), I18nPhrase::AllKeys, AppMessage::ShowHelp)
+            .bind("quit", KeyCode::Char('q'), I18nPhrase::Quit, AppMessage::Quit)
+            .retain(|handler| {
+                !page_captures_input || handler.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            })
+    }
+
+    /// Global handlers that are not shadowed by a binding of the current page.
+    fn global_handlers(&self) -> Vec<KeyHandler> {
+        let page = &self.tabs[self.selected_tab].page;
+        let page_handlers = page.get_handlers();
+
+        self.global_keymap(page.captures_input())
+            .handlers()
+            .into_iter()
+            .filter(|global| {
+                !page_handlers
+                    .iter()
+                    .any(|handler| handler.code == global.code && handler.modifiers == global.modifiers)
+            })
+            .collect()
+    }
+
+    /// The current page gets the first pick of every key, then the global bindings apply.
+    /// Keys bound by neither still reach the page, which may be typing text.
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.overlay.is_some() && matches!(self.global_keymap(false).action(&key_event), Some(AppMessage::Quit)) {
+            self.exit();
+            return;
+        }
+
         if let Some(over) = &mut self.overlay {
-            let ctrl = over.handle_key_event(key_event);
 
-            match ctrl {
+            match over.handle_key_event(key_event) {
                 FlowControl::ClosePopup => self.overlay = None,
                 FlowControl::NoOperation => {}
             }
-        } else {
-            //     let idx = self.registry_state.selected();
-
-            //     let i = idx.unwrap();
-
-            //     let shop = &self.campaign.get_shops()[i];
-
-            match key_event.code {
-                //         KeyCode::Enter => {
-                //             self.overlay = Some(Box::new(ShopSelectMenuPopup::new(
-                //                 shop.name.clone(),
-                //                 shop.clone(),
-                //             )))
-                //         }
-
-                //         KeyCode::Char('q') => self.exit(),
-                //         KeyCode::Esc => self.overlay = None,
-                // KeyCode::Up => self.registry_state.scroll_up_by(1),
-                // KeyCode::Down => self.registry_state.scroll_down_by(1),
-                KeyCode::Tab => self.messages.push(AppMessage::NextCategory),
-                KeyCode::BackTab => self.messages.push(AppMessage::PreviousCategory),
-                _ => {}
-            }
+            return;
         }
 
-        // Always allow to quit
-        if let KeyCode::Char('q') = key_event.code {
-            self.exit()
+        let page = &self.tabs[self.selected_tab].page;
+        let page_binds_key = page.get_handlers().iter().any(|handler| handler.matches(&key_event));
+        let global_message = self.global_keymap(page.captures_input()).action(&key_event);
+
+        match global_message {
+            Some(message) if !page_binds_key => self.messages.push(message),
+            _ => self.current_page().handle_and_transact(&Event::Key(key_event)),
         }
     }
 }
 
+#[derive(Clone)]
 enum FlowControl {
     ClosePopup,
     NoOperation,
 }
 
+/// Keys of every popup. `toggle` is the key that opened the popup, it closes it again.
+fn popup_keymap(toggle: Option<KeyCode>) -> KeyMap<FlowControl> {
+    let keymap = KeyMap::new("popup")
+        .bind("close", KeyCode::Esc, I18nPhrase::ClosePopup, FlowControl::ClosePopup)
+        .bind_hidden("close", KeyCode::Enter, I18nPhrase::ClosePopup, FlowControl::ClosePopup);
+
+    match toggle {
+        Some(code) => keymap.bind_hidden("close", code, I18nPhrase::ClosePopup, FlowControl::ClosePopup),
+        None => keymap,
+    }
+}
+
 trait AppScreen {
-    fn draw(&self, frame: &mut Frame, area: Rect);
+    fn draw(&self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner);
     fn handle_key_event(&mut self, key_event: KeyEvent) -> FlowControl;
+
+    /// Width and height of the popup in percent of the window.
+    fn size(&self) -> (u16, u16) {
+        (50, 50)
+    }
+}
+
+/// Lists what changed in the shops after time passed.
+struct RestockReport {
+    calendar: Calendar,
+    restocks: Vec<Stamped<ShopChangelog>>,
+    /// The changed items the registry knows, to show their translated names.
+    item_types: HashMap<ItemIdentifier, ItemType>,
+}
+
+impl RestockReport {
+    fn new(calendar: Calendar, restocks: Vec<Stamped<ShopChangelog>>, item_types: HashMap<ItemIdentifier, ItemType>) -> Self {
+        Self { calendar, restocks, item_types }
+    }
+
+    fn change_line(&self, change: &InventoryChange, i18n: &dyn I18ner) -> String {
+        let (phrase, id) = match change {
+            InventoryChange::Returned(id) => (I18nPhrase::ItemBackInStock, id),
+            InventoryChange::Added(id) => (I18nPhrase::ItemAddedToStock, id),
+            InventoryChange::Removed(id) => (I18nPhrase::ItemRemovedFromStock, id),
+        };
+        let name = match self.item_types.get(id) {
+            Some(item_type) => item_type.name_in(i18n.language()).to_string(),
+            None => id.to_string(),
+        };
+
+        format!("  {}", i18n.i18n_with(phrase, &[("item", &name)]))
+    }
+}
+
+impl AppScreen for RestockReport {
+    fn draw(&self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        let mut lines = vec![];
+
+        for restock in &self.restocks {
+            lines.push(ratatui::text::Line::from(vec![
+                ratatui::text::Span::raw(restock.value.shop_name.clone()).bold(),
+                ratatui::text::Span::raw(format!(" ({})", self.calendar.format(&restock.date))).italic(),
+            ]));
+            lines.extend(restock.value.changes.iter().map(|change| ratatui::text::Line::raw(self.change_line(change, i18n))));
+        }
+
+        let report = Paragraph::new(lines)
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(Block::bordered().title(i18n.i18n(I18nPhrase::RestockedShopsTitle)));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(report, area);
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> FlowControl {
+        popup_keymap(None).action(&key_event).unwrap_or(FlowControl::NoOperation)
+    }
 }
 
 // // struct WelcomeScreen {}
@@ -427,6 +646,53 @@ trait AppScreen {
 // // // //     }
 // // // // }
 
+/// Shows what can be undone and redone, the most recent edits on top.
+struct HistoryPopup {
+    done: Vec<HistoryEntry>,
+    undone: Vec<HistoryEntry>,
+}
+
+impl HistoryPopup {
+    fn new(done: Vec<HistoryEntry>, undone: Vec<HistoryEntry>) -> Self {
+        Self { done, undone }
+    }
+}
+
+impl AppScreen for HistoryPopup {
+    fn draw(&self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        let mut lines: Vec<ratatui::text::Line> = self
+            .undone
+            .iter()
+            .map(|entry| ratatui::text::Line::raw(format!("  {}", entry.description)).fg(theme::palette().muted).italic())
+            .collect();
+
+        lines.extend(
+            self.done
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(idx, entry)| {
+                    let line = ratatui::text::Line::raw(format!("  {}", entry.description));
+                    if idx == 0 { line.bold() } else { line }
+                }),
+        );
+
+        if lines.is_empty() {
+            lines.push(ratatui::text::Line::raw(i18n.i18n(I18nPhrase::NoEditsSinceSave)).italic());
+        }
+
+        let history = Paragraph::new(lines)
+            .block(Block::bordered().title(i18n.i18n(I18nPhrase::HistoryTitle)));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(history, area);
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> FlowControl {
+        popup_keymap(Some(KeyCode::Char('h'))).action(&key_event).unwrap_or(FlowControl::NoOperation)
+    }
+}
+
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
 fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
@@ -436,10 +702,137 @@ fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     area
 }
 
-pub fn run_app<'a>(campaign: &'a mut Campaign<'a>, i18n: &'a dyn I18ner) -> anyhow::Result<()> {
+pub fn run_app(store: SharedCampaign, settings: SharedSettings, autosave_override: Option<Duration>) -> anyhow::Result<()> {
     let mut terminal = ratatui::init();
-    let mut app = App::new(campaign, i18n)?;
+    let mut app = App::new(store, settings, autosave_override)?;
     let app_result = app.run(&mut terminal);
     ratatui::restore();
     app_result
 }
+
+/// Lists every wished-for item with the characters who want it and where it can be bought.
+struct WishReport {
+    report: Vec<WishedItem>,
+    pricing: PricingStrategy,
+}
+
+impl WishReport {
+    fn new(report: Vec<WishedItem>, pricing: PricingStrategy) -> Self {
+        Self { report, pricing }
+    }
+}
+
+impl AppScreen for WishReport {
+    fn draw(&self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        let mut lines = vec![];
+        let palette = theme::palette();
+
+        for wished in &self.report {
+            let name = match &wished.item_type {
+                Some(item_type) => Span::raw(item_type.name_in(i18n.language()).to_string()).bold(),
+                None => Span::raw(format!("⚠ {}", i18n.i18n_with(I18nPhrase::UnknownItem, &[("item", &wished.identifier.to_string())]))).fg(palette.warning).bold(),
+            };
+            lines.push(Line::from(vec![
+                name,
+                Span::raw(format!(" {}", i18n.i18n_with(I18nPhrase::WishedBy, &[("names", &wished.wished_by.join(", "))]))).italic(),
+            ]));
+
+            if wished.stocked_in.is_empty() {
+                lines.push(Line::raw(format!("  {}", i18n.i18n(I18nPhrase::NotInStockAnywhere))).fg(palette.muted));
+            }
+
+            for (shop_name, stock) in &wished.stocked_in {
+                let price = wished
+                    .item_type
+                    .as_ref()
+                    .map_or_else(|| "?".to_string(), |item_type| {
+                    i18n.i18n_with(I18nPhrase::GoldPieces, &[("amount", &self.pricing.cost_expression(item_type))])
+                });
+                let stocked = i18n.i18n_with(
+                    I18nPhrase::InStockFor,
+                    &[("shop", shop_name), ("stock", &stock.to_string()), ("price", &price)],
+                );
+
+                lines.push(Line::raw(format!("  {stocked}")).fg(palette.highlight));
+            }
+        }
+
+        if lines.is_empty() {
+            lines.push(Line::raw(i18n.i18n(I18nPhrase::NoWishesYet)).italic());
+        }
+
+        let report = Paragraph::new(lines)
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(Block::bordered().title(i18n.i18n(I18nPhrase::WishReportTitle)));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(report, area);
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> FlowControl {
+        popup_keymap(Some(KeyCode::Char('w'))).action(&key_event).unwrap_or(FlowControl::NoOperation)
+    }
+}
+
+/// Lists every key binding of the current page, all global ones and those of popups.
+struct HelpPopup {
+    page_title: String,
+    page: Vec<KeyHandler>,
+    global: Vec<KeyHandler>,
+    popup: Vec<KeyHandler>,
+}
+
+impl HelpPopup {
+    fn new(page_title: String, page: Vec<KeyHandler>, global: Vec<KeyHandler>, popup: Vec<KeyHandler>) -> Self {
+        Self { page_title, page, global, popup }
+    }
+
+    fn binding_table<'a>(title: &'a str, handlers: &[KeyHandler], i18n: &dyn I18ner) -> Table<'a> {
+        let rows = handlers.iter().map(|handler| {
+            Row::new(vec![
+                Cell::from(Line::raw(handler.key_label()).right_aligned().bold()),
+                Cell::from(i18n.i18n(handler.command)),
+                // The name to use in the keymap file.
+                Cell::from(format!("{}.{}", handler.context, handler.action).fg(theme::palette().muted)),
+            ])
+        });
+
+        Table::new(rows, [Constraint::Length(8), Constraint::Fill(1), Constraint::Fill(1)])
+            .block(Block::new().title(title.underlined()))
+    }
+}
+
+impl AppScreen for HelpPopup {
+    fn draw(&self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        frame.render_widget(Clear, area);
+
+        let block = Block::bordered().title(i18n.i18n(I18nPhrase::HelpTitle));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [page_area, global_area] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Fill(1); 2])
+            .areas(inner);
+
+        let [global_area, popup_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(self.popup.len() as u16 + 1),
+        ])
+        .areas(global_area);
+
+        frame.render_widget(Self::binding_table(&self.page_title, &self.page, i18n), page_area);
+        let everywhere = i18n.i18n(I18nPhrase::Everywhere);
+        frame.render_widget(Self::binding_table(&everywhere, &self.global, i18n), global_area);
+        let in_popups = i18n.i18n(I18nPhrase::InPopups);
+        frame.render_widget(Self::binding_table(&in_popups, &self.popup, i18n), popup_area);
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> FlowControl {
+        popup_keymap(Some(KeyCode::Char('?'))).action(&key_event).unwrap_or(FlowControl::NoOperation)
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (90, 70)
+    }
+}
@@ -1,75 +1,571 @@
-use ratatui::{layout::{Constraint, Layout}, widgets::{Row, Table, TableState}};
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Cell, Paragraph, Row, Table, TableState, Wrap},
+};
 
-use crate::data::{character::Character, item::Item};
+use crate::{
+    config::{settings::SharedSettings, theme},
+    data::{character::Character, item::{Item, ItemIdentifier}},
+    export::Handout,
+    state::campaign::{CampaignChange, CampaignCommand, ChangeCursor, SharedCampaign},
+};
 
-use super::{flow::KeyHandler, key::KeyMenu, page::RenderablePage};
+use super::{
+    display::rarity_symbol,
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    handout::{export_handout, export_status_line, ExportStatus},
+    input::TextInput,
+    item_card::ItemCard,
+    item_picker::{ItemPicker, PickerOutcome},
+    page::RenderablePage,
+    scroll::ScrollView,
+    translator::{I18nPhrase, I18ner, Message, Translator},
+};
 
-pub struct CharactersPage<'a> {
-    pub characters: Vec<&'a Character<'a>>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharactersFocus {
+    Characters,
+    WishList,
+}
+
+enum CharactersPrompt {
+    Create(TextInput),
+    Rename(usize, TextInput),
+    ConfirmDelete(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CharactersAction {
+    Up,
+    Down,
+    FocusCharacters,
+    FocusWishList,
+    New,
+    Rename,
+    Delete,
+    AddWish,
+    RemoveWish,
+    Resolve,
+    ExportWishList,
+    DetailsUp,
+    DetailsDown,
+    Confirm,
+    Cancel,
+}
+
+/// What the picked item is used for.
+enum PickerPurpose {
+    AddWish,
+    Resolve(ItemIdentifier),
+}
+
+pub struct CharactersPage {
+    store: SharedCampaign,
+    /// Characters are known by their index, so inserting or removing one moves the others.
+    changes: ChangeCursor,
+    settings: SharedSettings,
     character_table_state: TableState,
+    wish_list_table_state: TableState,
+    wish_detail_scroll: ScrollView,
+
+    focus: CharactersFocus,
+    prompt: Option<CharactersPrompt>,
+    picker: Option<(ItemPicker, PickerPurpose)>,
+    error: Option<Message>,
+    export_status: ExportStatus,
 }
 
-impl<'a> CharactersPage<'a> {
-    pub fn new(characters: Vec<&'a Character>) -> Self {
+impl CharactersPage {
+    pub fn new(store: SharedCampaign, settings: SharedSettings) -> Self {
+        let has_characters = !store.borrow().campaign().characters.is_empty();
+        let changes = store.borrow().subscribe();
+
         Self {
-            character_table_state: TableState::default().with_selected(if characters.len() > 0 { Some(1) } else { None }),
-            characters,
+            character_table_state: TableState::default().with_selected(if has_characters { Some(0) } else { None }),
+            wish_list_table_state: TableState::default().with_selected(Some(0)),
+            wish_detail_scroll: ScrollView::new(),
+            store,
+            changes,
+            settings,
+            focus: CharactersFocus::Characters,
+            prompt: None,
+            picker: None,
+            error: None,
+            export_status: None,
+        }
+    }
+
+    fn selected_character(&self) -> Option<usize> {
+        let count = self.store.borrow().campaign().characters.len();
+        self.character_table_state.selected().filter(|idx| *idx < count)
+    }
+
+    fn selected_wish(&self) -> Option<ItemIdentifier> {
+        let character = self.selected_character()?;
+        let idx = self.wish_list_table_state.selected()?;
+
+        self.store.borrow().campaign().characters[character].wish_list.get(idx).cloned()
+    }
+
+    fn apply(&mut self, command: CampaignCommand) -> bool {
+        match self.store.borrow_mut().apply(command) {
+            Ok(()) => true,
+            Err(e) => {
+                self.error = Some(Message::Verbatim(e.to_string()));
+                false
+            }
+        }
+    }
+
+    fn select_character(&mut self, idx: usize) {
+        let count = self.store.borrow().campaign().characters.len();
+        self.character_table_state.select(count.checked_sub(1).map(|last| idx.min(last)));
+        self.wish_list_table_state.select(Some(0));
+        self.wish_detail_scroll.reset();
+    }
+
+    /// Closes prompts and the item picker about a character once characters were inserted or
+    /// removed in the meantime, e.g. by an undo, as their index may point to someone else now.
+    fn close_moved_characters(&mut self) {
+        let list_changed = self
+            .store
+            .borrow()
+            .changes_since(&mut self.changes)
+            .contains(&CampaignChange::CharacterList);
+
+        if list_changed {
+            self.picker = None;
+            if matches!(self.prompt, Some(CharactersPrompt::Rename(..) | CharactersPrompt::ConfirmDelete(_))) {
+                self.prompt = None;
+            }
         }
     }
 
-    pub fn selected_character(&self) -> Option<&'a Character> {
-        Some(self.characters[self.character_table_state.selected()?])
+    /// Keeps both selections valid after characters or wishes vanished, e.g. by an undo.
+    fn clamp_selection(&mut self) {
+        let store = self.store.borrow();
+        let characters = &store.campaign().characters;
+
+        let selected = self.character_table_state.selected().unwrap_or(0);
+        self.character_table_state.select(characters.len().checked_sub(1).map(|last| selected.min(last)));
+
+        let wish_count = self
+            .character_table_state
+            .selected()
+            .map_or(0, |idx| characters[idx].wish_list.len());
+
+        if self.wish_list_table_state.selected().is_some_and(|idx| idx >= wish_count) {
+            self.wish_list_table_state.select(Some(wish_count.saturating_sub(1)));
+        }
+    }
+
+    fn keymap(&self) -> KeyMap<CharactersAction> {
+        match (&self.prompt, self.focus) {
+            (Some(CharactersPrompt::Create(_) | CharactersPrompt::Rename(..)), _) => KeyMap::for_text_input("characters")
+                .bind("confirm", KeyCode::Enter, I18nPhrase::Confirm, CharactersAction::Confirm)
+                .bind("cancel", KeyCode::Esc, I18nPhrase::Cancel, CharactersAction::Cancel),
+            (Some(CharactersPrompt::ConfirmDelete(_)), _) => KeyMap::new("characters")
+                .bind("confirm", KeyCode::Char('y'), I18nPhrase::RemoveTheCharacter, CharactersAction::Confirm)
+                .bind("cancel", KeyCode::Char('n'), I18nPhrase::KeepThem, CharactersAction::Cancel)
+                .bind_hidden("cancel", KeyCode::Esc, I18nPhrase::KeepThem, CharactersAction::Cancel),
+            (None, CharactersFocus::Characters) => KeyMap::new("characters")
+                .bind("wish-list", KeyCode::Right, I18nPhrase::WishListHint, CharactersAction::FocusWishList)
+                .bind_hidden("wish-list", KeyCode::Enter, I18nPhrase::WishListHint, CharactersAction::FocusWishList)
+                .bind("new", KeyCode::Char('n'), I18nPhrase::NewCharacter, CharactersAction::New)
+                .bind("rename", KeyCode::Char('r'), I18nPhrase::Rename, CharactersAction::Rename)
+                .bind("delete", KeyCode::Char('d'), I18nPhrase::Delete, CharactersAction::Delete)
+                .bind_hidden("delete", KeyCode::Delete, I18nPhrase::Delete, CharactersAction::Delete)
+                .bind("add-wish", KeyCode::Char('a'), I18nPhrase::AddWish, CharactersAction::AddWish)
+                .bind("export", KeyCode::Char('E'), I18nPhrase::ExportHandout, CharactersAction::ExportWishList)
+                .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousCharacter, CharactersAction::Up)
+                .bind_hidden("down", KeyCode::Down, I18nPhrase::NextCharacter, CharactersAction::Down)
+                .bind_hidden("page-down", KeyCode::PageDown, I18nPhrase::ScrollDetails, CharactersAction::DetailsDown)
+                .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::ScrollDetailsUp, CharactersAction::DetailsUp),
+            (None, CharactersFocus::WishList) => KeyMap::new("characters")
+                .bind("characters", KeyCode::Left, I18nPhrase::CharactersHint, CharactersAction::FocusCharacters)
+                .bind_hidden("characters", KeyCode::Esc, I18nPhrase::CharactersHint, CharactersAction::FocusCharacters)
+                .bind("add-wish", KeyCode::Char('a'), I18nPhrase::AddWish, CharactersAction::AddWish)
+                .bind("remove-wish", KeyCode::Char('x'), I18nPhrase::RemoveWish, CharactersAction::RemoveWish)
+                .bind_hidden("remove-wish", KeyCode::Delete, I18nPhrase::RemoveWish, CharactersAction::RemoveWish)
+                .bind("resolve", KeyCode::Char('f'), I18nPhrase::ResolveUnknownItem, CharactersAction::Resolve)
+                .bind("export", KeyCode::Char('E'), I18nPhrase::ExportHandout, CharactersAction::ExportWishList)
+                .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousWish, CharactersAction::Up)
+                .bind_hidden("down", KeyCode::Down, I18nPhrase::NextWish, CharactersAction::Down)
+                .bind("page-down", KeyCode::PageDown, I18nPhrase::ScrollDetails, CharactersAction::DetailsDown)
+                .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::ScrollDetailsUp, CharactersAction::DetailsUp),
+        }
+    }
+
+    fn focus_block(&self, focus: CharactersFocus, title: String) -> Block<'static> {
+        let block = Block::bordered().title(title);
+
+        if self.focus == focus {
+            block.border_type(BorderType::Thick)
+        } else {
+            block
+        }
+    }
+
+    fn handle_picker_key(&mut self, key_event: &KeyEvent) {
+        let Some((picker, _)) = &mut self.picker else {
+            return;
+        };
+
+        let outcome = picker.handle_key(key_event, &self.store.borrow().campaign().registry);
+
+        match outcome {
+            PickerOutcome::Searching => {}
+            PickerOutcome::Cancelled => self.picker = None,
+            PickerOutcome::Picked(identifier) => {
+                let Some((_, purpose)) = self.picker.take() else {
+                    return;
+                };
+                let Some(character) = self.selected_character() else {
+                    return;
+                };
+
+                let command = match purpose {
+                    PickerPurpose::AddWish => CampaignCommand::AddWish { character, item: identifier },
+                    PickerPurpose::Resolve(item) => CampaignCommand::ReplaceWish { character, item, with: identifier },
+                };
+                self.apply(command);
+            }
+        }
+    }
+
+    fn confirm_prompt(&mut self) {
+        match self.prompt.take() {
+            Some(CharactersPrompt::Create(input)) => {
+                let character = Character { name: input.value().trim().to_string(), wish_list: vec![] };
+                let index = self.store.borrow().campaign().characters.len();
+
+                if self.apply(CampaignCommand::InsertCharacter { index, character }) {
+                    self.select_character(index);
+                } else {
+                    self.prompt = Some(CharactersPrompt::Create(input));
+                }
+            }
+            Some(CharactersPrompt::Rename(character, input)) => {
+                let command = CampaignCommand::RenameCharacter { character, name: input.value().trim().to_string() };
+
+                if !self.apply(command) {
+                    self.prompt = Some(CharactersPrompt::Rename(character, input));
+                }
+            }
+            Some(CharactersPrompt::ConfirmDelete(character)) => {
+                let removed = self.apply(CampaignCommand::RemoveCharacter { character });
+                if removed {
+                    self.select_character(character);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn export_wish_list(&mut self) {
+        let Some(idx) = self.selected_character() else {
+            return;
+        };
+
+        let store = self.store.borrow();
+        let settings = self.settings.borrow();
+        let i18n = Translator::new(settings.settings());
+        let handout = Handout::wish_list(store.campaign(), &store.campaign().characters[idx], &i18n);
+
+        self.export_status = Some(export_handout(&store, settings.settings(), &handout, &i18n));
+    }
+
+    fn perform(&mut self, action: CharactersAction) {
+        self.error = None;
+        let selected = self.selected_character();
+
+        match (self.focus, action) {
+            (_, CharactersAction::Confirm) => self.confirm_prompt(),
+            (_, CharactersAction::Cancel) => self.prompt = None,
+            (_, CharactersAction::AddWish) => {
+                if selected.is_some() {
+                    self.picker = Some((ItemPicker::new(I18nPhrase::AddToWishList), PickerPurpose::AddWish));
+                }
+            }
+            (_, CharactersAction::DetailsUp) => self.wish_detail_scroll.page_up(),
+            (_, CharactersAction::DetailsDown) => self.wish_detail_scroll.page_down(),
+            (_, CharactersAction::FocusWishList) => {
+                if selected.is_some() {
+                    self.focus = CharactersFocus::WishList;
+                }
+            }
+            (_, CharactersAction::FocusCharacters) => self.focus = CharactersFocus::Characters,
+            (_, CharactersAction::New) => self.prompt = Some(CharactersPrompt::Create(TextInput::default())),
+            (_, CharactersAction::Rename) => {
+                if let Some(idx) = selected {
+                    let name = self.store.borrow().campaign().characters[idx].name.clone();
+                    self.prompt = Some(CharactersPrompt::Rename(idx, TextInput::new(&name)));
+                }
+            }
+            (_, CharactersAction::Delete) => {
+                if let Some(idx) = selected {
+                    self.prompt = Some(CharactersPrompt::ConfirmDelete(idx));
+                }
+            }
+
+            (CharactersFocus::Characters, CharactersAction::Up) => {
+                self.character_table_state.scroll_up_by(1);
+                self.wish_list_table_state.select(Some(0));
+                self.wish_detail_scroll.reset();
+            }
+            (CharactersFocus::Characters, CharactersAction::Down) => {
+                self.character_table_state.scroll_down_by(1);
+                self.wish_list_table_state.select(Some(0));
+                self.wish_detail_scroll.reset();
+            }
+            (CharactersFocus::WishList, CharactersAction::Up) => {
+                self.wish_list_table_state.scroll_up_by(1);
+                self.wish_detail_scroll.reset();
+            }
+            (CharactersFocus::WishList, CharactersAction::Down) => {
+                self.wish_list_table_state.scroll_down_by(1);
+                self.wish_detail_scroll.reset();
+            }
+
+            (_, CharactersAction::RemoveWish) => {
+                if let (Some(character), Some(item)) = (selected, self.selected_wish()) {
+                    self.apply(CampaignCommand::RemoveWish { character, item });
+                }
+            }
+            (_, CharactersAction::ExportWishList) => self.export_wish_list(),
+            (_, CharactersAction::Resolve) => {
+                let Some(item) = self.selected_wish() else {
+                    return;
+                };
+
+                if self.store.borrow().campaign().registry.get(&item).is_some() {
+                    self.error = Some(Message::from(I18nPhrase::KnownItemAlready).with("item", &item));
+                } else {
+                    let title = Message::from(I18nPhrase::ResolveItemTitle).with("item", &item);
+                    self.picker = Some((ItemPicker::new(title), PickerPurpose::Resolve(item)));
+                }
+            }
+        }
     }
 }
 
-impl<'a> RenderablePage for CharactersPage<'a> {
-    fn title(&self) -> String {
-        "Characters".into()
+impl HandlesKeyEvents for CharactersPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        match &self.picker {
+            Some((picker, _)) => picker.get_handlers(),
+            None => self.keymap().handlers(),
+        }
     }
+}
 
-    fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn super::translator::I18ner) {
-        let [content_area, menu_area] = Layout::default()
-            .direction(ratatui::layout::Direction::Vertical)
-            .constraints([Constraint::Fill(1), Constraint::Length(3)])
-            .areas(area);
+impl RenderablePage for CharactersPage {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        i18n.i18n(I18nPhrase::Characters)
+    }
 
-        let [char_list_area, char_info_area] = Layout::default()
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn I18ner) {
+        self.close_moved_characters();
+        self.clamp_selection();
+
+        let [char_column_area, char_info_area] = Layout::default()
             .direction(ratatui::layout::Direction::Horizontal)
             .constraints([Constraint::Fill(1); 2])
-            .areas(content_area);
+            .areas(area);
+
+        let status_height = match (&self.prompt, &self.error) {
+            (Some(_), _) => 3,
+            (None, Some(_)) => 1,
+            (None, None) => 0,
+        };
+
+        let [char_list_area, status_area] = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(status_height)])
+            .areas(char_column_area);
+
+        let [wish_list_area, wish_detail_area] = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([Constraint::Fill(1); 2])
+            .areas(char_info_area);
+
+        let store = self.store.borrow();
+        let campaign = store.campaign();
+        let palette = theme::palette();
 
         let table = Table::new(
-            self.characters
+            campaign.characters
                 .iter()
-                .map(|ch| Row::new(vec![ch.name().as_str()]))
-                .collect::<Vec<_>>(),
-                [Constraint::Fill(1)]
-        );
+                .map(|ch| Row::new(vec![ch.name().clone(), i18n.i18n_with(I18nPhrase::WishCount, &[("count", &ch.wish_list.len().to_string())])])),
+            [Constraint::Fill(1), Constraint::Length(10)],
+        )
+        .block({
+            let block = self.focus_block(CharactersFocus::Characters, i18n.i18n(I18nPhrase::Characters));
+            match export_status_line(&self.export_status, i18n) {
+                Some(status) => block.title_bottom(status),
+                None => block,
+            }
+        })
+        .highlight_symbol(">> ")
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
 
-        let wish_list = match self.selected_character() {
-            Some(char) => { char.wish_list()
-                .iter()
-                .filter_map(|item|
-                    if let Item::Concrete(item_type) = item {
-                        Some(Row::new(vec![item_type.name.clone()]))
-                    } else {
-                        None
-                    })
-                .collect() },
-            None => vec![],
-        };
+        let selected_character = self.character_table_state
+            .selected()
+            .and_then(|idx| campaign.characters.get(idx));
+
+        let wished_items = selected_character.map_or_else(Vec::new, |ch| ch.wish_list(&campaign.registry));
 
         let wish_list_table = Table::new(
-            wish_list, [Constraint::Fill(1)]
-        );
+            wished_items.iter().map(|item| match item {
+                Item::Concrete(item_type) => {
+                    let stocking: Vec<String> = campaign
+                        .shops_stocking(&item_type.identifier)
+                        .iter()
+                        .map(|shop| shop.short_name())
+                        .collect();
+
+                    let row = Row::new(vec![
+                        Cell::from(rarity_symbol(&item_type.rarity)),
+                        Cell::from(item_type.name_in(i18n.language()).to_string()),
+                        Cell::from(stocking.join(", ")),
+                    ]);
+                    if stocking.is_empty() {
+                        row
+                    } else {
+                        row.style(Style::new().fg(palette.highlight).bold())
+                    }
+                }
+                Item::Unresolved(identifier) => {
+                    Row::new(vec!["⚠".to_string(), i18n.i18n_with(I18nPhrase::UnknownItem, &[("item", identifier)]), String::new()])
+                        .style(Style::new().fg(palette.warning))
+                }
+            }),
+            [Constraint::Length(1), Constraint::Fill(2), Constraint::Fill(1)],
+        )
+        .header(Row::new(vec![String::new(), i18n.i18n(I18nPhrase::Item), i18n.i18n(I18nPhrase::InStockAt)]).bold())
+        .block(self.focus_block(CharactersFocus::WishList, i18n.i18n(I18nPhrase::WishList)))
+        .highlight_symbol(">> ")
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        let selected_wish = self.wish_list_table_state
+            .selected()
+            .and_then(|idx| wished_items.get(idx));
+
+        match selected_wish {
+            Some(Item::Concrete(item_type)) => {
+                self.wish_detail_scroll.render(frame, wish_detail_area, ItemCard::new(item_type, i18n).language(i18n.language()).paragraph(), Block::bordered());
+            }
+            Some(Item::Unresolved(identifier)) => {
+                let warning = Paragraph::new(vec![
+                    Line::raw(format!("⚠ {}", i18n.i18n_with(I18nPhrase::NoItemWithIdentifier, &[("item", identifier)]))).fg(palette.warning).bold(),
+                    Line::raw(""),
+                    Line::raw(i18n.i18n(I18nPhrase::UnknownWishHelp)),
+                ])
+                .wrap(Wrap { trim: true });
+
+                frame.render_widget(warning.block(Block::bordered()), wish_detail_area);
+            }
+            None => frame.render_widget(Block::bordered(), wish_detail_area),
+        }
 
         frame.render_stateful_widget(table, char_list_area, &mut self.character_table_state);
-        frame.render_widget(wish_list_table, char_info_area);
-        frame.render_widget(KeyMenu { handles: vec![] }, menu_area);
+        frame.render_stateful_widget(wish_list_table, wish_list_area, &mut self.wish_list_table_state);
+
+        let title = |phrase| match &self.error {
+            Some(error) => Line::raw(i18n.message(error)).fg(palette.error),
+            None => Line::raw(i18n.i18n(phrase)),
+        };
+
+        match &self.prompt {
+            Some(CharactersPrompt::Create(input)) => {
+                input.render(frame, status_area, Block::bordered().title(title(I18nPhrase::NewCharacterName)), true);
+            }
+            Some(CharactersPrompt::Rename(_, input)) => {
+                input.render(frame, status_area, Block::bordered().title(title(I18nPhrase::NewName)), true);
+            }
+            Some(CharactersPrompt::ConfirmDelete(idx)) => {
+                let name = campaign.characters.get(*idx).map_or("", |ch| ch.name.as_str());
+                frame.render_widget(
+                    Paragraph::new(Line::raw(i18n.i18n_with(I18nPhrase::RemoveCharacterForGood, &[("name", name)])).bold().centered())
+                        .block(Block::bordered()),
+                    status_area,
+                );
+            }
+            None => {
+                if let Some(error) = &self.error {
+                    frame.render_widget(Line::raw(i18n.message(error)).fg(palette.error).bold(), status_area);
+                }
+            }
+        }
+
+        if let Some((picker, _)) = &mut self.picker {
+            picker.render(frame, area, &campaign.registry, i18n);
+        }
     }
 
-    fn handle_and_transact(&mut self, event: &ratatui::crossterm::event::Event) {
-        
+    fn handle_and_transact(&mut self, event: &Event) {
+        self.close_moved_characters();
+
+        let Event::Key(key_event) = event else {
+            return;
+        };
+
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+
+        self.clamp_selection();
+
+        if self.picker.is_some() {
+            self.handle_picker_key(key_event);
+        } else if let Some(action) = self.keymap().action(key_event) {
+            self.perform(action);
+        } else if let Some(CharactersPrompt::Create(input) | CharactersPrompt::Rename(_, input)) = &mut self.prompt {
+            input.handle_key(key_event);
+            self.error = None;
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.picker.is_some() || matches!(self.prompt, Some(CharactersPrompt::Create(_) | CharactersPrompt::Rename(..)))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyModifiers;
+
+    use crate::{
+        config::settings::{Settings, SettingsStore},
+        data::campaign::CampaignFolder,
+        state::campaign::CampaignStore,
+    };
+
+    use super::*;
+
+    fn press(page: &mut CharactersPage, code: KeyCode) {
+        page.handle_and_transact(&Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn an_undo_that_moves_characters_closes_their_prompts() -> anyhow::Result<()> {
+        let mut folder = CampaignFolder::empty("Test".into());
+        folder.characters = ["Anna", "Bert", "Carla"].map(|name| Character { name: name.into(), wish_list: vec![] }).to_vec();
+        let store = CampaignStore::shared(folder.into_campaign());
+        let mut page = CharactersPage::new(store.clone(), SettingsStore::in_memory(Settings::default()).into_shared());
+
+        press(&mut page, KeyCode::Char('d'));
+        press(&mut page, KeyCode::Char('y'));
+        press(&mut page, KeyCode::Char('d'));
+        assert!(matches!(page.prompt, Some(CharactersPrompt::ConfirmDelete(0))));
+
+        store.borrow_mut().undo()?;
+        press(&mut page, KeyCode::Char('y'));
+
+        assert!(page.prompt.is_none());
+        assert_eq!(store.borrow().campaign().characters.len(), 3);
+        Ok(())
+    }
+}
@@ -1,23 +1,73 @@
-use ratatui::{style::{Style, Stylize as _}, text::Span};
+use ratatui::{style::{Color, Style, Stylize as _}, text::Span};
 
-use crate::data::item::Rarity;
+use crate::{
+    config::theme,
+    data::{
+        item::{ItemCategory, Rarity},
+        shop::Stock,
+    },
+};
 
-pub trait AsRatatuiSpan {
-    fn as_span(&self) -> ratatui::text::Span;
+use super::translator::{I18nPhrase, I18ner};
+
+fn rarity_color(rarity: &Rarity) -> Color {
+    let colors = theme::palette().rarity;
+    match rarity {
+        Rarity::Common => colors.common,
+        Rarity::Uncommon => colors.uncommon,
+        Rarity::Rare => colors.rare,
+        Rarity::VeryRare => colors.very_rare,
+        Rarity::Legendary => colors.legendary,
+        Rarity::Artifact => colors.artifact,
+    }
 }
 
+/// The letter of the rarity in its color, so it can be told apart without the color as well.
+pub fn rarity_symbol(rarity: &Rarity) -> Span<'static> {
+    Span::styled(rarity.as_symbol(), Style::default().fg(rarity_color(rarity)))
+}
 
-// @todo maybe move this into its own file?
-impl AsRatatuiSpan for Rarity {
-    fn as_span(&self) -> Span {
-        let base_span = Span::raw(self.to_string());
-        match self {
-            Rarity::Common => base_span.style(Style::default().gray().italic()),
-            Rarity::Uncommon => base_span.style(Style::default().white().italic()),
-            Rarity::Rare => base_span.style(Style::default().green().italic()),
-            Rarity::VeryRare => base_span.style(Style::default().magenta().italic()),
-            Rarity::Legendary => base_span.style(Style::default().red().italic()),
-            Rarity::Artifact => base_span.style(Style::default().red().underlined().italic()),
-        }
+pub fn category_name(category: &ItemCategory) -> I18nPhrase {
+    match category {
+        ItemCategory::WondrousItem => I18nPhrase::WondrousItemCategory,
+        ItemCategory::SimpleWeapon => I18nPhrase::SimpleWeaponCategory,
+        ItemCategory::Wand => I18nPhrase::WandCategory,
+        ItemCategory::Armor => I18nPhrase::ArmorCategory,
+        ItemCategory::Weapon => I18nPhrase::WeaponCategory,
+        ItemCategory::Potion => I18nPhrase::PotionCategory,
+        ItemCategory::Ring => I18nPhrase::RingCategory,
+        ItemCategory::Rod => I18nPhrase::RodCategory,
+        ItemCategory::Scroll => I18nPhrase::ScrollCategory,
+        ItemCategory::Staff => I18nPhrase::StaffCategory,
     }
-}
\ No newline at end of file
+}
+
+pub fn rarity_name(rarity: &Rarity) -> I18nPhrase {
+    match rarity {
+        Rarity::Common => I18nPhrase::CommonRarity,
+        Rarity::Uncommon => I18nPhrase::UncommonRarity,
+        Rarity::Rare => I18nPhrase::RareRarity,
+        Rarity::VeryRare => I18nPhrase::VeryRareRarity,
+        Rarity::Legendary => I18nPhrase::LegendaryRarity,
+        Rarity::Artifact => I18nPhrase::ArtifactRarity,
+    }
+}
+
+/// Counts are shown as they are, only sold out and unlimited stock is translated.
+pub fn stock_name(stock: &Stock, i18n: &dyn I18ner) -> String {
+    match stock {
+        Stock::Stocked(count) => count.to_string(),
+        Stock::Sold => i18n.i18n(I18nPhrase::SoldOut),
+        Stock::Infinite => i18n.i18n(I18nPhrase::InfiniteStock),
+    }
+}
+
+/// The translated rarity in its color, artifacts are underlined as well.
+pub fn rarity_span(rarity: &Rarity, i18n: &dyn I18ner) -> Span<'static> {
+    let base_span = Span::raw(i18n.i18n(rarity_name(rarity)));
+    let style = Style::default().fg(rarity_color(rarity)).italic();
+    match rarity {
+        Rarity::Artifact => base_span.style(style.underlined()),
+        _ => base_span.style(style),
+    }
+}
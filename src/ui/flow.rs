@@ -1,12 +1,177 @@
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+use crate::config::keymap::{self, KeyBindings};
+
+use super::translator::I18nPhrase;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct KeyHandler {
     pub code: KeyCode,
     pub modifiers: KeyModifiers,
-    pub command: String,
+    pub command: I18nPhrase,
+    /// Only listed in the help popup, not in the hint bar.
+    pub hidden: bool,
+    /// Where the binding comes from, the keymap file refers to it as `context.action`.
+    pub context: &'static str,
+    pub action: &'static str,
 }
 
+impl KeyHandler {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers, command: I18nPhrase) -> Self {
+        Self {
+            code,
+            modifiers,
+            command,
+            hidden: false,
+            context: "",
+            action: "",
+        }
+    }
+
+    /// Short name of the key combination, e.g. `Ctrl+s` or `→`.
+    pub fn key_label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            code => code.to_string(),
+        };
+
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        label.push_str(&key);
+        label
+    }
+
+    /// Whether the key event triggers this handler.
+    pub fn matches(&self, key_event: &KeyEvent) -> bool {
+        // Shift is already part of the character ('T') and of Back Tab, terminals differ in reporting it.
+        let modifiers = match key_event.code {
+            KeyCode::Char(_) | KeyCode::BackTab => key_event.modifiers - KeyModifiers::SHIFT,
+            _ => key_event.modifiers,
+        };
+
+        self.code == key_event.code && self.modifiers == modifiers
+    }
+}
 
 pub trait HandlesKeyEvents {
     fn get_handlers(&self) -> Vec<KeyHandler>;
-}
\ No newline at end of file
+}
+
+/// The key bindings of one screen: every handler together with the action it triggers.
+///
+/// Pages build their hints and dispatch their keys from the same map, so both always agree.
+/// Every action has a name that is unique within the context of the map, the keymap file
+/// of the user refers to it as `context.action` to put it on other keys.
+#[derive(Debug, Clone)]
+pub struct KeyMap<A> {
+    context: &'static str,
+    text_input: bool,
+    overrides: &'static KeyBindings,
+    bindings: Vec<(KeyHandler, A)>,
+}
+
+impl<A: Clone> KeyMap<A> {
+    pub fn new(context: &'static str) -> Self {
+        Self::with_overrides(context, keymap::installed())
+    }
+
+    /// Key map of a screen that takes text. Overrides that would take a printable character are
+    /// ignored, that character is typed instead.
+    pub fn for_text_input(context: &'static str) -> Self {
+        Self {
+            text_input: true,
+            ..Self::new(context)
+        }
+    }
+
+    pub fn with_overrides(context: &'static str, overrides: &'static KeyBindings) -> Self {
+        Self {
+            context,
+            text_input: false,
+            overrides,
+            bindings: vec![],
+        }
+    }
+
+    pub fn bind(self, action_name: &'static str, code: KeyCode, command: I18nPhrase, action: A) -> Self {
+        self.bind_with(action_name, KeyHandler::new(code, KeyModifiers::NONE, command), action)
+    }
+
+    pub fn bind_ctrl(self, action_name: &'static str, code: KeyCode, command: I18nPhrase, action: A) -> Self {
+        self.bind_with(action_name, KeyHandler::new(code, KeyModifiers::CONTROL, command), action)
+    }
+
+    /// Binds a key that works but is too obvious to take up space in the hint bar.
+    pub fn bind_hidden(self, action_name: &'static str, code: KeyCode, command: I18nPhrase, action: A) -> Self {
+        self.bind_with(
+            action_name,
+            KeyHandler {
+                hidden: true,
+                ..KeyHandler::new(code, KeyModifiers::NONE, command)
+            },
+            action,
+        )
+    }
+
+    /// Binds the handler's key to the action, unless the user moved the action to other keys.
+    /// Binding the same action name again adds an alternative key, which an override replaces as well.
+    pub fn bind_with(mut self, action_name: &'static str, handler: KeyHandler, action: A) -> Self {
+        let handler = KeyHandler {
+            context: self.context,
+            action: action_name,
+            ..handler
+        };
+
+        let Some(chords) = self.overrides.keys_for(self.context, action_name) else {
+            self.bindings.push((handler, action));
+            return self;
+        };
+
+        let already_bound = self.bindings.iter().any(|(bound, _)| bound.action == action_name);
+        if already_bound {
+            return self;
+        }
+
+        let chords = chords.iter().filter(|chord| !(self.text_input && chord.is_typed()));
+        for (nth, chord) in chords.enumerate() {
+            let rebound = KeyHandler {
+                code: chord.code,
+                modifiers: chord.modifiers,
+                // Further keys of an action are alternatives like the hidden defaults.
+                hidden: handler.hidden || nth > 0,
+                ..handler.clone()
+            };
+            self.bindings.push((rebound, action.clone()));
+        }
+
+        self
+    }
+
+    /// Drops every binding whose handler does not pass the filter.
+    pub fn retain(mut self, keep: impl Fn(&KeyHandler) -> bool) -> Self {
+        self.bindings.retain(|(handler, _)| keep(handler));
+        self
+    }
+
+    /// The action of the first binding that matches the key event.
+    pub fn action(&self, key_event: &KeyEvent) -> Option<A> {
+        self.bindings
+            .iter()
+            .find(|(handler, _)| handler.matches(key_event))
+            .map(|(_, action)| action.clone())
+    }
+
+    pub fn handlers(&self) -> Vec<KeyHandler> {
+        self.bindings.iter().map(|(handler, _)| handler.clone()).collect()
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use ratatui::{style::Stylize, text::Line};
+
+use crate::{
+    config::{settings::Settings, theme},
+    export::{load_template, write_handout, Handout},
+    state::campaign::CampaignStore,
+};
+
+use super::translator::{I18nPhrase, I18ner, Message};
+
+/// Outcome of the last export of a page, shown until the next one.
+pub type ExportStatus = Option<Result<PathBuf, Message>>;
+
+/// Writes the handout into the campaign folder, in the format and with the template the settings ask for.
+pub fn export_handout(store: &CampaignStore, settings: &Settings, handout: &Handout, i18n: &dyn I18ner) -> Result<PathBuf, Message> {
+    let Some(folder) = store.folder() else {
+        return Err(I18nPhrase::NoCampaignFolder.into());
+    };
+
+    let format = settings.handout_format;
+    let result = load_template(format, None).and_then(|template| {
+        let rendered = handout.render(format, &template, settings.hide_prices_in_handouts, i18n);
+        write_handout(folder, handout, format, &rendered)
+    });
+
+    result.map_err(|e| {
+        log::error!("Could not export {}: {e:?}", handout.title);
+        Message::from(I18nPhrase::CouldNotExport).with("error", format!("{e:#}"))
+    })
+}
+
+pub fn export_status_line(status: &ExportStatus, i18n: &dyn I18ner) -> Option<Line<'static>> {
+    let palette = theme::palette();
+    match status {
+        Some(Ok(path)) => Some(Line::raw(i18n.i18n_with(I18nPhrase::ExportedTo, &[("path", &path.display().to_string())])).fg(palette.highlight)),
+        Some(Err(error)) => Some(Line::raw(i18n.message(error)).fg(palette.error)),
+        None => None,
+    }
+}
@@ -3,7 +3,7 @@ use std::cmp::min;
 use ratatui::{
     crossterm::event::{Event, KeyCode, KeyEventKind},
     layout::{Constraint, Direction, Flex, Layout, Rect},
-    style::{palette::material::WHITE, Color},
+    style::Color,
     symbols::Marker,
     widgets::{
         canvas::{Canvas, Context, Line},
@@ -12,26 +12,47 @@ use ratatui::{
     Frame,
 };
 
-use super::{page::RenderablePage, translator::I18ner};
+use crate::config::theme;
 
-pub struct HomePage {
-    color: Color,
+use super::{
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    page::RenderablePage,
+    translator::{I18nPhrase, I18ner},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum HomeAction {
+    Grow,
+    Shrink,
+}
 
+pub struct HomePage {
     scale: f32,
 }
 
 impl HomePage {
     pub fn new() -> Self {
-        Self {
-            color: WHITE,
-            scale: 10.0,
-        }
+        Self { scale: 10.0 }
+    }
+}
+
+impl HomePage {
+    fn keymap(&self) -> KeyMap<HomeAction> {
+        KeyMap::new("home")
+            .bind("grow", KeyCode::Up, I18nPhrase::BiggerDie, HomeAction::Grow)
+            .bind("shrink", KeyCode::Down, I18nPhrase::SmallerDie, HomeAction::Shrink)
+    }
+}
+
+impl HandlesKeyEvents for HomePage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
     }
 }
 
 impl<'a> RenderablePage for HomePage {
-    fn title(&self) -> String {
-        "Home".into()
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        i18n.i18n(I18nPhrase::Home)
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
@@ -47,11 +68,12 @@ impl<'a> RenderablePage for HomePage {
             .flex(Flex::Center)
             .areas(inner_area);
 
+        let color = theme::palette().die;
         let c = Canvas::default()
             .block(Block::bordered())
             .marker(Marker::Braille)
             .paint(|ctx| {
-                render_d20(ctx, self.scale.into(), self.color);
+                render_d20(ctx, self.scale.into(), color);
             })
             .x_bounds([-20.0, 20.0])
             .y_bounds([-20.0, 20.0]);
@@ -65,15 +87,15 @@ impl<'a> RenderablePage for HomePage {
                 return;
             }
 
-            match key_event.code {
-                KeyCode::Up => self.scale += 1.0,
-                KeyCode::Down => {
+            match self.keymap().action(key_event) {
+                Some(HomeAction::Grow) => self.scale += 1.0,
+                Some(HomeAction::Shrink) => {
                     self.scale -= 1.0;
                     if self.scale < 1.0 {
                         self.scale = 1.0;
                     }
                 }
-                _ => {}
+                None => {}
             }
         }
     }
@@ -0,0 +1,233 @@
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Position, Rect},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+use strum::IntoEnumIterator;
+
+/// Byte offset of the `char_idx`-th character, or the length if it is past the end.
+fn byte_offset(text: &str, char_idx: usize) -> usize {
+    text.char_indices().nth(char_idx).map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Steps through all values of an enum, wrapping around at both ends.
+pub fn cycle<T: IntoEnumIterator + PartialEq>(current: &T, forward: bool) -> T {
+    let values: Vec<T> = T::iter().collect();
+    let idx = values.iter().position(|value| value == current).unwrap_or(0);
+    let next = if forward {
+        (idx + 1) % values.len()
+    } else {
+        (idx + values.len() - 1) % values.len()
+    };
+
+    values.into_iter().nth(next).expect("index is within bounds")
+}
+
+fn is_typing(key_event: &KeyEvent) -> bool {
+    !key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+/// Single line text field with a cursor.
+#[derive(Debug, Default, Clone)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            cursor: value.chars().count(),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        *self = Self::new(value);
+    }
+
+    /// Handles typing, Backspace, Delete, Left, Right, Home and End. Returns whether the key was used.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        let len = self.value.chars().count();
+
+        match key_event.code {
+            KeyCode::Char(c) if is_typing(key_event) => {
+                self.value.insert(byte_offset(&self.value, self.cursor), c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(byte_offset(&self.value, self.cursor));
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.value.remove(byte_offset(&self.value, self.cursor));
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Renders the value inside `block` and places the terminal cursor if `focused`.
+    pub fn render(&self, frame: &mut Frame, area: Rect, block: Block, focused: bool) {
+        let inner = block.inner(area);
+        let width = inner.width.max(1) as usize;
+        let skip = self.cursor.saturating_sub(width - 1);
+
+        let visible: String = self.value.chars().skip(skip).take(width).collect();
+        frame.render_widget(Paragraph::new(visible).block(block), area);
+
+        if focused {
+            frame.set_cursor_position(Position::new(inner.x + (self.cursor - skip) as u16, inner.y));
+        }
+    }
+}
+
+/// Multi-line text editor with a cursor, e.g. for markdown.
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    column: usize,
+    scroll: usize,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl TextArea {
+    pub fn new(text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        Self {
+            lines,
+            row: 0,
+            column: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    /// Handles typing, Enter, Backspace, Delete and the arrow, Home and End keys.
+    /// Returns whether the key was used.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Char(c) if is_typing(key_event) => {
+                let offset = byte_offset(&self.lines[self.row], self.column);
+                self.lines[self.row].insert(offset, c);
+                self.column += 1;
+            }
+            KeyCode::Enter => {
+                let offset = byte_offset(&self.lines[self.row], self.column);
+                let rest = self.lines[self.row].split_off(offset);
+                self.lines.insert(self.row + 1, rest);
+                self.row += 1;
+                self.column = 0;
+            }
+            KeyCode::Backspace => {
+                if self.column > 0 {
+                    self.column -= 1;
+                    let offset = byte_offset(&self.lines[self.row], self.column);
+                    self.lines[self.row].remove(offset);
+                } else if self.row > 0 {
+                    let line = self.lines.remove(self.row);
+                    self.row -= 1;
+                    self.column = self.line_len(self.row);
+                    self.lines[self.row].push_str(&line);
+                }
+            }
+            KeyCode::Delete => {
+                if self.column < self.line_len(self.row) {
+                    let offset = byte_offset(&self.lines[self.row], self.column);
+                    self.lines[self.row].remove(offset);
+                } else if self.row + 1 < self.lines.len() {
+                    let line = self.lines.remove(self.row + 1);
+                    self.lines[self.row].push_str(&line);
+                }
+            }
+            KeyCode::Left => {
+                if self.column > 0 {
+                    self.column -= 1;
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.column = self.line_len(self.row);
+                }
+            }
+            KeyCode::Right => {
+                if self.column < self.line_len(self.row) {
+                    self.column += 1;
+                } else if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.column = 0;
+                }
+            }
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.column = self.column.min(self.line_len(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.column = self.column.min(self.line_len(self.row));
+            }
+            KeyCode::Home => self.column = 0,
+            KeyCode::End => self.column = self.line_len(self.row),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Renders the text inside `block`, scrolled so that the cursor stays visible.
+    /// Long lines are cut off rather than wrapped to keep the cursor position exact.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, block: Block, focused: bool) {
+        let inner = block.inner(area);
+        let height = inner.height.max(1) as usize;
+        let width = inner.width.max(1) as usize;
+
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if self.row >= self.scroll + height {
+            self.scroll = self.row + 1 - height;
+        }
+
+        let skip_columns = self.column.saturating_sub(width - 1);
+        let visible: Vec<String> = self
+            .lines
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|line| line.chars().skip(skip_columns).take(width).collect())
+            .collect();
+
+        frame.render_widget(Paragraph::new(visible.join("\n")).block(block), area);
+
+        if focused {
+            frame.set_cursor_position(Position::new(
+                inner.x + (self.column - skip_columns) as u16,
+                inner.y + (self.row - self.scroll) as u16,
+            ));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::{config::settings::Language, data::item::ItemType};
+
+use super::{
+    display::{category_name, rarity_span},
+    markdown::markdown_to_text,
+    translator::I18ner,
+};
+
+/// Shows an item with its name, category, rarity and markdown rendered details.
+pub struct ItemCard<'a> {
+    item_type: &'a ItemType,
+    i18n: &'a dyn I18ner,
+    language: Option<Language>,
+}
+
+impl<'a> ItemCard<'a> {
+    pub fn new(item_type: &'a ItemType, i18n: &'a dyn I18ner) -> Self {
+        Self {
+            item_type,
+            i18n,
+            language: None,
+        }
+    }
+
+    /// Shows the translation for `language` where the item has one, otherwise the base text.
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    pub fn text(&self) -> Text<'a> {
+        let (name, details) = match self.language {
+            Some(language) => (self.item_type.name_in(language), self.item_type.details_in(language)),
+            None => (self.item_type.name.as_str(), self.item_type.details.as_str()),
+        };
+
+        let mut text = Text::from(vec![
+            Line::raw(name.to_string()).bold(),
+            Line::from(vec![
+                Span::raw(self.i18n.i18n(category_name(&self.item_type.category))).italic(),
+                Span::raw(", "),
+                rarity_span(&self.item_type.rarity, self.i18n),
+            ]),
+            Line::default(),
+        ]);
+
+        text.lines
+            .extend(markdown_to_text(details).lines);
+
+        text
+    }
+
+    /// The wrapped content without block or scroll offset, e.g. for a [`super::scroll::ScrollView`].
+    pub fn paragraph(&self) -> Paragraph<'a> {
+        Paragraph::new(self.text()).wrap(Wrap { trim: false })
+    }
+}
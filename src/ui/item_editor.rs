@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType},
+    Frame,
+};
+
+use crate::{
+    config::{settings::Language, theme},
+    data::item::{slugify, ApiReference, ItemCategory, ItemIdentifier, ItemTranslation, ItemType, Rarity},
+    state::campaign::{CampaignCommand, SharedCampaign},
+};
+
+use super::{
+    display::{category_name, rarity_span},
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    input::{cycle, TextArea, TextInput},
+    item_card::ItemCard,
+    page::RenderablePage,
+    scroll::ScrollView,
+    translator::{I18nPhrase, I18ner, Message},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditorField {
+    Name,
+    Identifier,
+    Rarity,
+    Category,
+    Details,
+}
+
+impl EditorField {
+    fn next(&self) -> Self {
+        match self {
+            Self::Name => Self::Identifier,
+            Self::Identifier => Self::Rarity,
+            Self::Rarity => Self::Category,
+            Self::Category => Self::Details,
+            Self::Details => Self::Name,
+        }
+    }
+
+    fn previous(&self) -> Self {
+        match self {
+            Self::Name => Self::Details,
+            Self::Identifier => Self::Name,
+            Self::Rarity => Self::Identifier,
+            Self::Category => Self::Rarity,
+            Self::Details => Self::Category,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EditorAction {
+    NextField,
+    PreviousField,
+    NextChoice,
+    PreviousChoice,
+    Save,
+    PreviewUp,
+    PreviewDown,
+}
+
+/// Form to create a new item type or edit an existing one.
+///
+/// Ctrl+S puts the item into the registry and writes its file into `items/` of the campaign folder.
+#[derive(Debug)]
+pub struct ItemEditor {
+    store: SharedCampaign,
+    editing: Option<ItemIdentifier>,
+
+    name: TextInput,
+    identifier: TextInput,
+    identifier_edited: bool,
+    rarity: Rarity,
+    category: ItemCategory,
+    details: TextArea,
+    /// Kept as they are, the editor changes the base text.
+    translations: BTreeMap<Language, ItemTranslation>,
+    /// Kept as it is, so an imported item can still be synced after editing it.
+    source: Option<ApiReference>,
+
+    focus: EditorField,
+    preview_scroll: ScrollView,
+    error: Option<Message>,
+    finished: bool,
+}
+
+impl ItemEditor {
+    pub fn create(store: SharedCampaign) -> Self {
+        Self {
+            store,
+            editing: None,
+            name: TextInput::default(),
+            identifier: TextInput::default(),
+            identifier_edited: false,
+            rarity: Rarity::Common,
+            category: ItemCategory::WondrousItem,
+            details: TextArea::default(),
+            translations: BTreeMap::new(),
+            source: None,
+            focus: EditorField::Name,
+            preview_scroll: ScrollView::new(),
+            error: None,
+            finished: false,
+        }
+    }
+
+    pub fn edit(store: SharedCampaign, item_type: &ItemType) -> Self {
+        Self {
+            editing: Some(item_type.identifier.clone()),
+            name: TextInput::new(&item_type.name),
+            identifier: TextInput::new(&item_type.identifier),
+            identifier_edited: true,
+            rarity: item_type.rarity.clone(),
+            category: item_type.category.clone(),
+            details: TextArea::new(&item_type.details),
+            translations: item_type.translations.clone(),
+            source: item_type.source.clone(),
+            ..Self::create(store)
+        }
+    }
+
+    /// Whether the item was stored and the editor can be closed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn draft(&self) -> ItemType {
+        ItemType {
+            identifier: self.identifier.value().trim().to_string(),
+            name: self.name.value().trim().to_string(),
+            details: self.details.text(),
+            rarity: self.rarity.clone(),
+            category: self.category.clone(),
+            translations: self.translations.clone(),
+            source: self.source.clone(),
+        }
+    }
+
+    fn validate(&self, draft: &ItemType) -> Result<(), Message> {
+        if draft.name.is_empty() {
+            return Err(I18nPhrase::ItemNeedsName.into());
+        }
+        if draft.identifier.is_empty() {
+            return Err(I18nPhrase::ItemNeedsIdentifier.into());
+        }
+        if self.editing.is_none() && self.store.borrow().campaign().registry.get(&draft.identifier).is_some() {
+            return Err(Message::from(I18nPhrase::DuplicateIdentifier).with("item", &draft.identifier));
+        }
+
+        Ok(())
+    }
+
+    fn commit(&mut self) {
+        let draft = self.draft();
+
+        if let Err(message) = self.validate(&draft) {
+            self.error = Some(message);
+            return;
+        }
+
+        let identifier = draft.identifier.clone();
+        let mut store = self.store.borrow_mut();
+        if let Err(e) = store.apply(CampaignCommand::UpsertItem(draft)) {
+            self.error = Some(Message::Verbatim(e.to_string()));
+            return;
+        }
+        // Saving again only has to retry the file, the item is in the registry now.
+        self.editing = Some(identifier.clone());
+
+        if store.folder().is_some() {
+            if let Err(e) = store.save_item(&identifier) {
+                log::error!("Could not write item {identifier}: {e:?}");
+                self.error = Some(Message::from(I18nPhrase::CouldNotWriteItem).with("error", format!("{e:#}")));
+                return;
+            }
+        }
+
+        self.finished = true;
+    }
+
+    fn keymap(&self) -> KeyMap<EditorAction> {
+        let keymap = KeyMap::for_text_input("item-editor")
+            .bind_ctrl("save", KeyCode::Char('s'), I18nPhrase::StoreItem, EditorAction::Save)
+            .bind("next-field", KeyCode::Tab, I18nPhrase::NextField, EditorAction::NextField)
+            .bind_hidden("previous-field", KeyCode::BackTab, I18nPhrase::PreviousField, EditorAction::PreviousField);
+
+        let keymap = match self.focus {
+            EditorField::Rarity | EditorField::Category => keymap
+                .bind("next-choice", KeyCode::Right, I18nPhrase::NextChoice, EditorAction::NextChoice)
+                .bind_hidden("next-choice", KeyCode::Char(' '), I18nPhrase::NextChoice, EditorAction::NextChoice)
+                .bind("previous-choice", KeyCode::Left, I18nPhrase::PreviousChoice, EditorAction::PreviousChoice),
+            _ => keymap,
+        };
+
+        keymap
+            .bind("page-down", KeyCode::PageDown, I18nPhrase::ScrollPreview, EditorAction::PreviewDown)
+            .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::ScrollPreviewUp, EditorAction::PreviewUp)
+    }
+
+    fn handle_key(&mut self, key_event: &KeyEvent) {
+        match self.keymap().action(key_event) {
+            Some(EditorAction::Save) => {
+                self.commit();
+                return;
+            }
+            Some(EditorAction::PreviewUp) => self.preview_scroll.page_up(),
+            Some(EditorAction::PreviewDown) => self.preview_scroll.page_down(),
+            Some(EditorAction::NextField) => self.focus = self.focus.next(),
+            Some(EditorAction::PreviousField) => self.focus = self.focus.previous(),
+            Some(EditorAction::NextChoice) => match self.focus {
+                EditorField::Rarity => self.rarity = cycle(&self.rarity, true),
+                EditorField::Category => self.category = cycle(&self.category, true),
+                _ => {}
+            },
+            Some(EditorAction::PreviousChoice) => match self.focus {
+                EditorField::Rarity => self.rarity = cycle(&self.rarity, false),
+                EditorField::Category => self.category = cycle(&self.category, false),
+                _ => {}
+            },
+            None => match self.focus {
+                EditorField::Name => {
+                    if self.name.handle_key(key_event) && !self.identifier_edited {
+                        self.identifier.set_value(&slugify(self.name.value()));
+                    }
+                }
+                // The identifier links shops and wish lists to the item, so it is fixed once saved.
+                EditorField::Identifier if self.editing.is_none() => {
+                    if self.identifier.handle_key(key_event) {
+                        self.identifier_edited = true;
+                    }
+                }
+                EditorField::Identifier | EditorField::Rarity | EditorField::Category => {}
+                EditorField::Details => {
+                    self.details.handle_key(key_event);
+                }
+            },
+        }
+
+        self.error = None;
+    }
+
+    fn field_block(&self, field: EditorField, title: String) -> Block<'static> {
+        let block = Block::bordered().title(title);
+
+        if self.focus == field {
+            block.border_type(BorderType::Thick)
+        } else {
+            block
+        }
+    }
+}
+
+impl HandlesKeyEvents for ItemEditor {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
+    }
+}
+
+impl RenderablePage for ItemEditor {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        match &self.editing {
+            Some(identifier) => i18n.i18n_with(I18nPhrase::EditItemTitle, &[("item", &identifier.to_string())]),
+            None => i18n.i18n(I18nPhrase::NewItemTitle),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: ratatui::prelude::Rect, i18n: &dyn I18ner) {
+        let [form_area, editor_area, status_area] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let [name_row, choice_row] = Layout::vertical([Constraint::Length(3); 2]).areas(form_area);
+        let [name_area, identifier_area] = Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)]).areas(name_row);
+        let [rarity_area, category_area] = Layout::horizontal([Constraint::Fill(1); 2]).areas(choice_row);
+        let [details_area, preview_area] = Layout::horizontal([Constraint::Fill(1); 2]).areas(editor_area);
+
+        self.name.render(frame, name_area, self.field_block(EditorField::Name, i18n.i18n(I18nPhrase::Name)), self.focus == EditorField::Name);
+
+        let identifier_title = i18n.i18n(if self.editing.is_some() { I18nPhrase::IdentifierFixed } else { I18nPhrase::Identifier });
+        self.identifier.render(
+            frame,
+            identifier_area,
+            self.field_block(EditorField::Identifier, identifier_title),
+            self.focus == EditorField::Identifier,
+        );
+
+        frame.render_widget(
+            Line::from(vec![Span::raw("◀ "), rarity_span(&self.rarity, i18n), Span::raw(" ▶")])
+                .centered(),
+            self.field_block(EditorField::Rarity, i18n.i18n(I18nPhrase::Rarity)).inner(rarity_area),
+        );
+        frame.render_widget(self.field_block(EditorField::Rarity, i18n.i18n(I18nPhrase::Rarity)), rarity_area);
+
+        frame.render_widget(
+            Line::raw(format!("◀ {} ▶", i18n.i18n(category_name(&self.category)))).centered(),
+            self.field_block(EditorField::Category, i18n.i18n(I18nPhrase::Category)).inner(category_area),
+        );
+        frame.render_widget(self.field_block(EditorField::Category, i18n.i18n(I18nPhrase::Category)), category_area);
+
+        let details_block = self.field_block(EditorField::Details, i18n.i18n(I18nPhrase::DetailsMarkdown));
+        self.details.render(frame, details_area, details_block, self.focus == EditorField::Details);
+
+        let draft = self.draft();
+        self.preview_scroll.render(
+            frame,
+            preview_area,
+            ItemCard::new(&draft, i18n).paragraph(),
+            Block::bordered().title(i18n.i18n(I18nPhrase::Preview)),
+        );
+
+        if let Some(message) = &self.error {
+            frame.render_widget(Line::raw(i18n.message(message)).fg(theme::palette().error).bold(), status_area);
+        }
+    }
+
+    fn handle_and_transact(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if key_event.kind == KeyEventKind::Press {
+                self.handle_key(key_event);
+            }
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyEvent, KeyModifiers};
+
+    use crate::{data::campaign::CampaignFolder, state::campaign::CampaignStore};
+
+    use super::*;
+
+    fn type_text(editor: &mut ItemEditor, text: &str) {
+        for c in text.chars() {
+            editor.handle_key(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    fn save(editor: &mut ItemEditor) {
+        editor.handle_key(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+    }
+
+    fn store() -> SharedCampaign {
+        let mut folder = CampaignFolder::empty("Test".into());
+        folder.item_registry.add(ItemType {
+            identifier: "mantel".into(),
+            ..ItemType::new("Mantel".into(), Rarity::Rare, ItemCategory::WondrousItem, "".into())
+        });
+        CampaignStore::shared(folder.into_campaign())
+    }
+
+    #[test]
+    fn items_need_a_name_and_identifier() {
+        let mut editor = ItemEditor::create(store());
+
+        save(&mut editor);
+        assert_eq!(editor.error, Some(I18nPhrase::ItemNeedsName.into()));
+
+        type_text(&mut editor, "!!");
+        save(&mut editor);
+        assert_eq!(editor.error, Some(I18nPhrase::ItemNeedsIdentifier.into()));
+        assert!(!editor.is_finished());
+    }
+
+    #[test]
+    fn new_items_do_not_replace_existing_ones() {
+        let store = store();
+        let mut editor = ItemEditor::create(store.clone());
+
+        type_text(&mut editor, "Mantel");
+        save(&mut editor);
+
+        assert!(matches!(editor.error, Some(Message::Phrase(I18nPhrase::DuplicateIdentifier, _))));
+        assert_eq!(store.borrow().campaign().registry.get(&"mantel".into()).map(|item| item.rarity.clone()), Some(Rarity::Rare));
+        assert!(!editor.is_finished());
+    }
+
+    #[test]
+    fn the_identifier_is_fixed_while_editing() {
+        let store = store();
+        let item = store.borrow().campaign().registry.get(&"mantel".into()).cloned().unwrap();
+        let mut editor = ItemEditor::edit(store.clone(), &item);
+
+        editor.handle_key(&KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        type_text(&mut editor, "-2");
+        save(&mut editor);
+
+        assert!(editor.is_finished());
+        assert!(store.borrow().campaign().registry.get(&"mantel-2".into()).is_none());
+        assert!(store.borrow().campaign().registry.get(&"mantel".into()).is_some());
+    }
+
+    #[test]
+    fn saved_items_are_written_to_the_campaign_folder() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dnd-simp-item-editor-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let store = CampaignStore::open(CampaignFolder::empty("Test".into()).into_campaign(), &dir).into_shared();
+        let mut editor = ItemEditor::create(store.clone());
+        type_text(&mut editor, "Bag of Holding");
+        save(&mut editor);
+
+        let written = std::fs::read_to_string(dir.join("items").join("bag-of-holding.yaml"));
+        std::fs::remove_dir_all(&dir)?;
+
+        assert_eq!(editor.error, None);
+        assert!(editor.is_finished());
+        assert!(written?.contains("Bag of Holding"));
+        Ok(())
+    }
+}
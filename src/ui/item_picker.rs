@@ -0,0 +1,146 @@
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Cell, Clear, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    data::item::{ItemIdentifier, ItemType},
+    registry::ItemRegistry,
+};
+
+use super::{
+    display::rarity_symbol,
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    input::TextInput,
+    translator::{I18nPhrase, I18ner, Message},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum PickerAction {
+    Pick,
+    Cancel,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickerOutcome {
+    Searching,
+    Picked(ItemIdentifier),
+    Cancelled,
+}
+
+/// Popup to search the registry by name or identifier and pick one item type.
+#[derive(Debug)]
+pub struct ItemPicker {
+    title: Message,
+    query: TextInput,
+    table_state: TableState,
+}
+
+impl ItemPicker {
+    pub fn new(title: impl Into<Message>) -> Self {
+        Self {
+            title: title.into(),
+            query: TextInput::default(),
+            table_state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn keymap(&self) -> KeyMap<PickerAction> {
+        KeyMap::for_text_input("item-picker")
+            .bind("pick", KeyCode::Enter, I18nPhrase::Pick, PickerAction::Pick)
+            .bind("cancel", KeyCode::Esc, I18nPhrase::Cancel, PickerAction::Cancel)
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousMatch, PickerAction::Up)
+            .bind_hidden("down", KeyCode::Down, I18nPhrase::NextMatch, PickerAction::Down)
+    }
+
+    fn matches<'r>(&self, registry: &'r ItemRegistry) -> Vec<&'r ItemType> {
+        let query = self.query.value().to_lowercase();
+
+        registry
+            .items()
+            .iter()
+            .filter(|item_type| {
+                item_type.names().any(|name| name.to_lowercase().contains(&query))
+                    || item_type.identifier.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, key_event: &KeyEvent, registry: &ItemRegistry) -> PickerOutcome {
+        match self.keymap().action(key_event) {
+            Some(PickerAction::Cancel) => return PickerOutcome::Cancelled,
+            Some(PickerAction::Pick) => {
+                let picked = self
+                    .table_state
+                    .selected()
+                    .and_then(|idx| self.matches(registry).get(idx).map(|item_type| item_type.identifier.clone()));
+
+                if let Some(identifier) = picked {
+                    return PickerOutcome::Picked(identifier);
+                }
+            }
+            Some(PickerAction::Up) => self.table_state.scroll_up_by(1),
+            Some(PickerAction::Down) => self.table_state.scroll_down_by(1),
+            None => {
+                if self.query.handle_key(key_event) {
+                    self.table_state.select(Some(0));
+                }
+            }
+        }
+
+        PickerOutcome::Searching
+    }
+
+    /// Draws the picker as a popup centered on top of `area`.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, registry: &ItemRegistry, i18n: &dyn I18ner) {
+        let [area] = Layout::vertical([Constraint::Percentage(70)]).flex(Flex::Center).areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center).areas(area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::bordered().title(i18n.message(&self.title));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [query_area, list_area] = Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(inner);
+
+        self.query.render(frame, query_area, Block::bordered().title(i18n.i18n(I18nPhrase::Search)), true);
+
+        let matches = self.matches(registry);
+        if let Some(selected) = self.table_state.selected() {
+            if selected >= matches.len() {
+                self.table_state.select(matches.len().checked_sub(1));
+            }
+        }
+
+        let table = Table::new(
+            matches.iter().map(|item_type| {
+                Row::new(vec![
+                    Cell::from(rarity_symbol(&item_type.rarity)),
+                    Cell::from(item_type.name_in(i18n.language()).to_string()),
+                    Cell::from(item_type.identifier.clone()),
+                ])
+            }),
+            [Constraint::Length(1), Constraint::Fill(2), Constraint::Fill(1)],
+        )
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol(">> ")
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        if matches.is_empty() {
+            frame.render_widget(ratatui::text::Line::raw(i18n.i18n(I18nPhrase::NoMatchingItems)).italic(), list_area);
+        } else {
+            frame.render_stateful_widget(table, list_area, &mut self.table_state);
+        }
+    }
+}
+
+impl HandlesKeyEvents for ItemPicker {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
+    }
+}
@@ -0,0 +1,312 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    apis::{cache::cached_dnd5eapi, dnd5eapi::download_item_types},
+    config::theme,
+    data::item::ItemIdentifier,
+    state::{
+        campaign::{CampaignCommand, SharedCampaign},
+        player_view::PlayerView,
+    },
+};
+
+use super::{
+    display::{category_name, rarity_symbol},
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    item_card::ItemCard,
+    item_editor::ItemEditor,
+    page::RenderablePage,
+    scroll::ScrollView,
+    srd_picker::{SrdPicker, SrdPickerOutcome},
+    translator::{I18nPhrase, I18ner, Message},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum ItemsAction {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    New,
+    Edit,
+    ShowPlayers,
+    ImportFromSrd,
+    CloseEditor,
+}
+
+/// Lists the item types of the registry and opens the [`ItemEditor`] for them.
+pub struct ItemsPage {
+    store: SharedCampaign,
+    item_table_state: TableState,
+    detail_scroll: ScrollView,
+
+    editor: Option<ItemEditor>,
+    srd_picker: Option<SrdPicker>,
+    /// Outcome of the last import from the SRD.
+    srd_status: Option<Result<Message, Message>>,
+
+    /// The item the players see, or why they could not be shown it.
+    shown_to_players: Result<Option<ItemIdentifier>, Message>,
+}
+
+impl ItemsPage {
+    pub fn new(store: SharedCampaign) -> Self {
+        Self {
+            store,
+            item_table_state: TableState::default().with_selected(Some(0)),
+            detail_scroll: ScrollView::new(),
+            editor: None,
+            srd_picker: None,
+            srd_status: None,
+            shown_to_players: Ok(None),
+        }
+    }
+
+    fn keymap(&self) -> KeyMap<ItemsAction> {
+        if self.editor.is_some() {
+            return KeyMap::new("items").bind("cancel", KeyCode::Esc, I18nPhrase::Cancel, ItemsAction::CloseEditor);
+        }
+
+        KeyMap::new("items")
+            .bind("new", KeyCode::Char('n'), I18nPhrase::NewItem, ItemsAction::New)
+            .bind("edit", KeyCode::Char('e'), I18nPhrase::Edit, ItemsAction::Edit)
+            .bind_hidden("edit", KeyCode::Enter, I18nPhrase::Edit, ItemsAction::Edit)
+            .bind("show-players", KeyCode::Char('v'), I18nPhrase::ShowPlayers, ItemsAction::ShowPlayers)
+            .bind("import-srd", KeyCode::Char('i'), I18nPhrase::ImportFromSrd, ItemsAction::ImportFromSrd)
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousItem, ItemsAction::Up)
+            .bind_hidden("down", KeyCode::Down, I18nPhrase::NextItem, ItemsAction::Down)
+            .bind("page-down", KeyCode::PageDown, I18nPhrase::ScrollDetails, ItemsAction::PageDown)
+            .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::ScrollDetailsUp, ItemsAction::PageUp)
+    }
+
+    fn draw_self(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        let [list_area, detail_area] = Layout::horizontal([Constraint::Fill(1); 2]).areas(area);
+
+        let store = self.store.borrow();
+        let items = store.campaign().registry.items();
+
+        let palette = theme::palette();
+        let mut list_block = Block::bordered().title(i18n.i18n(I18nPhrase::Items));
+        match &self.srd_status {
+            Some(Ok(imported)) => list_block = list_block.title_bottom(Line::raw(i18n.message(imported)).fg(palette.highlight)),
+            Some(Err(error)) => list_block = list_block.title_bottom(Line::raw(i18n.message(error)).fg(palette.error)),
+            None => {}
+        }
+
+        let table = Table::new(
+            items.iter().map(|item| {
+                Row::new(vec![
+                    Cell::from(rarity_symbol(&item.rarity)),
+                    Cell::from(item.name_in(i18n.language()).to_string()),
+                    Cell::from(i18n.i18n(category_name(&item.category))),
+                ])
+            }),
+            [Constraint::Length(1), Constraint::Fill(2), Constraint::Fill(1)],
+        )
+        .header(Row::new(vec![String::new(), i18n.i18n(I18nPhrase::Name), i18n.i18n(I18nPhrase::Category)]).style(Style::default().bg(palette.header)))
+        .block(list_block)
+        .highlight_symbol(">> ")
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        frame.render_stateful_widget(table, list_area, &mut self.item_table_state);
+
+        let selected = self.item_table_state.selected().and_then(|idx| items.get(idx));
+        let details = match selected {
+            Some(item_type) => ItemCard::new(item_type, i18n).language(i18n.language()).paragraph(),
+            None => Paragraph::new(i18n.i18n(I18nPhrase::NoItemSelected)).italic(),
+        };
+
+        let mut block = Block::bordered();
+        match &self.shown_to_players {
+            Ok(Some(shown)) if selected.is_some_and(|item_type| &item_type.identifier == shown) => {
+                block = block.title_bottom(Line::raw(i18n.i18n(I18nPhrase::ShownToPlayers)).fg(palette.highlight));
+            }
+            Ok(_) => {}
+            Err(error) => block = block.title_bottom(Line::raw(i18n.message(error)).fg(palette.error)),
+        }
+
+        self.detail_scroll.render(frame, detail_area, details, block);
+    }
+
+    fn show_players(&mut self) {
+        let store = self.store.borrow();
+        let Some(item_type) = self.item_table_state.selected().and_then(|idx| store.campaign().registry.items().get(idx)) else {
+            return;
+        };
+
+        self.shown_to_players = match store.player_screen() {
+            Some(screen) => match screen.show(&PlayerView::single(item_type.clone())) {
+                Ok(()) => Ok(Some(item_type.identifier.clone())),
+                Err(e) => {
+                    log::error!("Could not show the players {}: {e:?}", item_type.identifier);
+                    Err(Message::from(I18nPhrase::CouldNotShowPlayers).with("error", e))
+                }
+            },
+            None => Err(I18nPhrase::NoPlayerScreen.into()),
+        };
+    }
+
+    fn open_srd_picker(&mut self) {
+        self.srd_status = None;
+        match cached_dnd5eapi(None) {
+            Ok(requester) => self.srd_picker = Some(SrdPicker::new(&requester)),
+            Err(e) => self.srd_status = Some(Err(Message::from(I18nPhrase::CouldNotLoadSrd).with("error", e))),
+        }
+    }
+
+    fn handle_srd_picker_key(&mut self, key_event: &KeyEvent) {
+        let Some(picker) = &mut self.srd_picker else {
+            return;
+        };
+
+        match picker.handle_key(key_event) {
+            SrdPickerOutcome::Picking => {}
+            SrdPickerOutcome::Cancelled => self.srd_picker = None,
+            SrdPickerOutcome::Picked(sub_urls) => {
+                self.srd_picker = None;
+                self.srd_status = Some(self.import_from_srd(&sub_urls));
+            }
+        }
+    }
+
+    /// Imports the items and their variants, keeping the translations of items that are known already.
+    fn import_from_srd(&mut self, sub_urls: &[String]) -> Result<Message, Message> {
+        let requester = cached_dnd5eapi(None).map_err(|e| Message::from(I18nPhrase::CouldNotLoadSrd).with("error", e))?;
+        let download = download_item_types(sub_urls, true, &requester);
+        for (sub_url, e) in &download.failed {
+            log::error!("Could not import {sub_url} from the SRD: {e:?}");
+        }
+
+        let imported = download.items.len();
+        let mut identifiers = Vec::with_capacity(imported);
+        for item_type in download.items {
+            let item_type = self.store.borrow().campaign().registry.as_import(item_type);
+            identifiers.push(item_type.identifier.clone());
+            if let Err(e) = self.store.borrow_mut().apply(CampaignCommand::UpsertItem(item_type)) {
+                log::error!("Could not store an item from the SRD: {e:?}");
+                return Err(Message::Verbatim(format!("{e:#}")));
+            }
+        }
+
+        let mut store = self.store.borrow_mut();
+        if store.folder().is_some() {
+            let mut unwritten = 0;
+            let mut last_error = None;
+            for identifier in &identifiers {
+                if let Err(e) = store.save_item(identifier) {
+                    log::error!("Could not write item {identifier}: {e:?}");
+                    unwritten += 1;
+                    last_error = Some(e);
+                }
+            }
+
+            if let Some(e) = last_error {
+                return Err(Message::from(I18nPhrase::SrdImportNotWritten)
+                    .with("count", imported)
+                    .with("unwritten", unwritten)
+                    .with("error", format!("{e:#}")));
+            }
+        }
+
+        Ok(Message::from(I18nPhrase::SrdImported)
+            .with("count", imported)
+            .with("failed", download.failed.len()))
+    }
+
+    fn perform(&mut self, action: ItemsAction) {
+        match action {
+            ItemsAction::Up => {
+                self.item_table_state.scroll_up_by(1);
+                self.detail_scroll.reset();
+            }
+            ItemsAction::Down => {
+                self.item_table_state.scroll_down_by(1);
+                self.detail_scroll.reset();
+            }
+            ItemsAction::PageUp => self.detail_scroll.page_up(),
+            ItemsAction::PageDown => self.detail_scroll.page_down(),
+            ItemsAction::ShowPlayers => self.show_players(),
+            ItemsAction::ImportFromSrd => self.open_srd_picker(),
+            ItemsAction::New => self.editor = Some(ItemEditor::create(self.store.clone())),
+            ItemsAction::CloseEditor => self.editor = None,
+            ItemsAction::Edit => {
+                let selected = self.item_table_state.selected().and_then(|idx| {
+                    self.store.borrow().campaign().registry.items().get(idx).cloned()
+                });
+
+                if let Some(item_type) = selected {
+                    self.editor = Some(ItemEditor::edit(self.store.clone(), &item_type));
+                }
+            }
+        }
+    }
+}
+
+impl HandlesKeyEvents for ItemsPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        if let Some(picker) = &self.srd_picker {
+            return picker.get_handlers();
+        }
+
+        let mut handlers = self.keymap().handlers();
+        if let Some(editor) = &self.editor {
+            handlers.extend(editor.get_handlers());
+        }
+        handlers
+    }
+}
+
+impl RenderablePage for ItemsPage {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        i18n.i18n(I18nPhrase::Items)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        if let Some(editor) = &mut self.editor {
+            editor.draw(frame, area, i18n);
+        } else {
+            self.draw_self(frame, area, i18n);
+        }
+
+        if let Some(picker) = &mut self.srd_picker {
+            picker.render(frame, area, &self.store.borrow().campaign().registry, i18n);
+        }
+    }
+
+    fn handle_and_transact(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if key_event.kind != KeyEventKind::Press {
+                return;
+            }
+
+            if self.srd_picker.is_some() {
+                self.handle_srd_picker_key(key_event);
+                return;
+            }
+
+            if let Some(action) = self.keymap().action(key_event) {
+                self.perform(action);
+                return;
+            }
+        }
+
+        if let Some(editor) = &mut self.editor {
+            editor.handle_and_transact(event);
+
+            if editor.is_finished() {
+                self.editor = None;
+            }
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.editor.is_some() || self.srd_picker.is_some()
+    }
+}
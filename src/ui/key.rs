@@ -1,30 +1,36 @@
-use ratatui::crossterm::event::{KeyCode, KeyModifiers};
-use ratatui::widgets::{Block, Paragraph, Widget};
+use ratatui::widgets::{Block, Paragraph, Widget, Wrap};
 use ratatui::prelude::*;
-use super::flow::KeyHandler;
+use crate::config::theme;
 
-pub struct KeyMenu {
+use super::{flow::KeyHandler, translator::I18ner};
+
+/// Hint bar listing the keys of the current page, followed by the keys that work everywhere.
+pub struct KeyMenu<'a> {
+    pub i18n: &'a dyn I18ner,
     pub handles: Vec<KeyHandler>,
+    pub global_handles: Vec<KeyHandler>,
 }
 
-fn key_span<'a>(handler: &KeyHandler) -> Vec<Span<'a>> {
-    let mut spans = vec![];
-
-    if handler.modifiers != KeyModifiers::NONE {
-        spans.push(Span::from(handler.modifiers.to_string()).black().on_white());
-        spans.push(Span::raw("+").black().on_white());
-    }
-    
-    spans.append(&mut vec![
-        Span::from(handler.code.to_string()).black().on_white(),
+fn key_span<'a>(handler: &KeyHandler, i18n: &dyn I18ner) -> Vec<Span<'a>> {
+    let palette = theme::palette();
+    vec![
+        Span::from(handler.key_label()).fg(palette.key).bg(palette.key_background),
         Span::raw(" "),
-        Span::raw(handler.command.clone()),
-    ]);
+        Span::raw(i18n.i18n(handler.command)),
+    ]
+}
+
+fn hint_spans<'a>(handlers: &[KeyHandler], i18n: &dyn I18ner) -> Vec<Span<'a>> {
+    let spans: Vec<Vec<Span>> = handlers
+        .iter()
+        .filter(|handler| !handler.hidden)
+        .map(|handler| key_span(handler, i18n))
+        .collect();
 
-    spans
+    spans.join(&Span::from("    "))
 }
 
-impl Widget for KeyMenu {
+impl Widget for KeyMenu<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let b = Block::bordered()
             .border_set(symbols::border::DOUBLE);
@@ -32,16 +38,16 @@ impl Widget for KeyMenu {
         let inner = b.inner(area);
         b.render(area, buf);
 
-        let spans: Vec<Vec<Span>> = self.handles
-            .iter()
-            .map(|handler| 
-                key_span(handler))
-            .collect();
+        let muted = theme::palette().muted;
+        let mut spans = hint_spans(&self.handles, self.i18n);
+        let global_spans = hint_spans(&self.global_handles, self.i18n);
 
-        let true_spans = spans.join(&Span::from("    "));
-        let l = Line::from(true_spans);
+        if !spans.is_empty() && !global_spans.is_empty() {
+            spans.push(Span::raw("  │  ").fg(muted));
+        }
+        spans.extend(global_spans.into_iter().map(|span| span.fg(muted)));
 
-        let p = Paragraph::new(Text::from(l));
+        let p = Paragraph::new(Text::from(Line::from(spans))).wrap(Wrap { trim: true });
         p.render(inner, buf);
     }
-}
\ No newline at end of file
+}
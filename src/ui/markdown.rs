@@ -0,0 +1,125 @@
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span, Text},
+};
+
+/// Converts markdown into owned ratatui text.
+///
+/// Item details often come from the SRD as one paragraph per line, so single line breaks
+/// start a new paragraph here. Pipe tables are rendered as aligned columns because
+/// `tui_markdown` does not support them.
+pub fn markdown_to_text(source: &str) -> Text<'static> {
+    let mut text = Text::default();
+    let mut paragraphs: Vec<&str> = vec![];
+    let mut table_rows: Vec<&str> = vec![];
+
+    for line in source.lines().map(str::trim) {
+        if line.starts_with('|') {
+            if !paragraphs.is_empty() {
+                append_paragraphs(&mut text, &paragraphs);
+                paragraphs.clear();
+            }
+            table_rows.push(line);
+        } else {
+            if !table_rows.is_empty() {
+                append_table(&mut text, &table_rows);
+                table_rows.clear();
+            }
+            if !line.is_empty() {
+                paragraphs.push(line);
+            }
+        }
+    }
+
+    append_paragraphs(&mut text, &paragraphs);
+    append_table(&mut text, &table_rows);
+
+    text
+}
+
+fn append_paragraphs(text: &mut Text<'static>, paragraphs: &[&str]) {
+    if paragraphs.is_empty() {
+        return;
+    }
+
+    let source = paragraphs.join("\n\n");
+    let rendered = tui_markdown::from_str(&source);
+
+    text.lines.extend(rendered.lines.into_iter().map(|line| {
+        let spans: Vec<Span<'static>> = line
+            .spans
+            .into_iter()
+            .map(|span| Span::styled(span.content.into_owned(), span.style))
+            .collect();
+        Line::from(spans).style(line.style)
+    }));
+}
+
+fn split_cells(row: &str) -> Vec<String> {
+    row.trim_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+fn is_separator_row(cells: &[String]) -> bool {
+    cells.iter().all(|cell| {
+        let dashes = cell.trim_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+fn append_table(text: &mut Text<'static>, rows: &[&str]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = rows.iter().map(|row| split_cells(row)).collect();
+    let has_header = rows.get(1).is_some_and(|row| is_separator_row(row));
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter(|row| !is_separator_row(row))
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    if !text.lines.is_empty() {
+        text.lines.push(Line::default());
+    }
+
+    for (idx, row) in rows.iter().enumerate() {
+        if has_header && idx == 1 {
+            let rule = widths
+                .iter()
+                .map(|width| "─".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("─┼─");
+            text.lines.push(Line::raw(rule));
+            continue;
+        }
+
+        let cells = widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                let cell = row.get(column).map(String::as_str).unwrap_or("");
+                format!("{cell:<width$}")
+            })
+            .collect::<Vec<_>>()
+            .join(" │ ");
+
+        if has_header && idx == 0 {
+            text.lines.push(Line::raw(cells).bold());
+        } else {
+            text.lines.push(Line::raw(cells));
+        }
+    }
+
+    text.lines.push(Line::default());
+}
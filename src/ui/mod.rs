@@ -3,12 +3,23 @@ pub mod page;
 
 pub mod home;
 pub mod offer;
+pub mod player_view;
 pub mod characters;
+pub mod items;
+pub mod item_editor;
 pub mod shop;
 pub mod shops;
 pub mod settings;
+pub mod weather;
 
 pub mod display;
+pub mod handout;
+pub mod input;
+pub mod item_card;
+pub mod item_picker;
+pub mod markdown;
+pub mod scroll;
+pub mod srd_picker;
 pub mod flow;
 pub mod key;
 
@@ -1,62 +1,170 @@
-use std::{cell::RefCell, cmp::min, rc::Rc};
+use std::cmp::min;
 
 use ratatui::{
-    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers}, layout::{Constraint, Layout, Rect}, style::{Style, Stylize}, text::{Line, Span}, widgets::{Block, Paragraph, Wrap}
+    crossterm::event::{Event, KeyCode, KeyEventKind}, layout::{Constraint, Layout}, style::Stylize, text::{Line, Span}, widgets::{Block, Paragraph}
 };
 
 use crate::{
-    data::{gold::GoldAmount, shop::{Shop, StockedItem}},
-};
-use crate::{
-    registry::{xanathar_magic_item_cost, CostExpressionFunction},
-    ui::flow::KeyHandler,
+    config::{settings::SharedSettings, theme},
+    data::{calendar::CampaignDate, gold::{roll_gold, GoldAmount}, item::ItemType},
+    export::Handout,
+    state::{
+        campaign::SharedCampaign,
+        player_view::{PlayerCard, PlayerView},
+    },
 };
+use crate::registry::CostExpressionFunction;
 
-use crate::ui::key::KeyMenu;
-use super::{display::AsRatatuiSpan, flow::HandlesKeyEvents, page::RenderablePage, translator::{I18nPhrase, I18ner}};
-use tyche::Expr;
+use super::{flow::{HandlesKeyEvents, KeyHandler, KeyMap}, handout::{export_handout, export_status_line, ExportStatus}, item_card::ItemCard, page::RenderablePage, scroll::ScrollView, translator::{I18nPhrase, I18ner, Message, Translator}};
 
-use crate::data::gold::AsGoldCurrency;
+#[derive(Debug, Clone, Copy)]
+enum OfferAction {
+    NextOffer,
+    PreviousOffer,
+    RollPrices,
+    HidePrices,
+    ToggleShowPlayers,
+    Export,
+    Scroll(KeyCode),
+}
 
 #[derive(Debug)]
-struct Offer<'a> {
-    pub stocked_item: StockedItem<'a>,
+struct Offer {
+    pub item_type: ItemType,
     pub price: Option<GoldAmount>,
 }
 
 #[derive(Debug)]
-pub struct OfferPage<'a> {
-    shop: &'a Shop<'a>,
+pub struct OfferPage {
+    store: SharedCampaign,
+    settings: SharedSettings,
+    shop_idx: usize,
+    offered_on: CampaignDate,
 
-    current_offer: Vec<Offer<'a>>,
+    current_offer: Vec<Offer>,
+    card_scrolls: Vec<ScrollView>,
 
     offer_idx: usize,
+
+    /// Whether the players see the offer, it is shown again after every change while they do.
+    shown_to_players: bool,
+    error: Option<Message>,
+    export_status: ExportStatus,
 }
 
-impl<'a> OfferPage<'a> {
-    pub fn new(shop: &'a Shop<'a>) -> Self {
-        let current_offer = shop
-            .produce_offer(3)
-            .into_iter()
-            .cloned()
-            .map(|i| Offer {
-                stocked_item: i,
-                price: None,
-            })
-            .collect();
+impl OfferPage {
+    pub fn new(store: SharedCampaign, settings: SharedSettings, shop_idx: usize, offered_on: CampaignDate) -> Self {
+        let current_offer = {
+            let store = store.borrow();
+            let campaign = store.campaign();
+            let offer_size = settings.borrow().settings().offer_size;
+
+            campaign.shops[shop_idx]
+                .produce_offer(offer_size)
+                .into_iter()
+                .filter_map(|stocked| campaign.registry.get(&stocked.identifier))
+                .map(|item_type| Offer {
+                    item_type: item_type.clone(),
+                    price: None,
+                })
+                .collect::<Vec<_>>()
+        };
+
         Self {
-            shop,
+            store,
+            settings,
+            shop_idx,
+            offered_on,
+            card_scrolls: current_offer.iter().map(|_| ScrollView::new()).collect(),
             current_offer,
             offer_idx: 0,
+            shown_to_players: false,
+            error: None,
+            export_status: None,
         }
     }
 
+    fn keymap(&self) -> KeyMap<OfferAction> {
+        let show_players = match self.shown_to_players {
+            true => I18nPhrase::HideFromPlayers,
+            false => I18nPhrase::ShowPlayers,
+        };
+
+        KeyMap::new("offer")
+            .bind("next-offer", KeyCode::Right, I18nPhrase::NextOffer, OfferAction::NextOffer)
+            .bind("previous-offer", KeyCode::Left, I18nPhrase::PreviousOffer, OfferAction::PreviousOffer)
+            .bind("roll-prices", KeyCode::Char('p'), I18nPhrase::RollPrices, OfferAction::RollPrices)
+            .bind("toggle-formula", KeyCode::Char('P'), I18nPhrase::ShowPriceFormula, OfferAction::HidePrices)
+            .bind("show-players", KeyCode::Char('v'), show_players, OfferAction::ToggleShowPlayers)
+            .bind("export", KeyCode::Char('E'), I18nPhrase::ExportHandout, OfferAction::Export)
+            .bind("down", KeyCode::Down, I18nPhrase::Scroll, OfferAction::Scroll(KeyCode::Down))
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::ScrollUp, OfferAction::Scroll(KeyCode::Up))
+            .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::PageUp, OfferAction::Scroll(KeyCode::PageUp))
+            .bind_hidden("page-down", KeyCode::PageDown, I18nPhrase::PageDown, OfferAction::Scroll(KeyCode::PageDown))
+            .bind_hidden("top", KeyCode::Home, I18nPhrase::ScrollToTop, OfferAction::Scroll(KeyCode::Home))
+            .bind_hidden("bottom", KeyCode::End, I18nPhrase::ScrollToBottom, OfferAction::Scroll(KeyCode::End))
+    }
+
+    /// The offer as the players may see it, without price formulas, stock or who wants what.
+    fn player_view(&self) -> PlayerView {
+        PlayerView {
+            shop: Some(self.store.borrow().campaign().shops[self.shop_idx].name.clone()),
+            cards: self
+                .current_offer
+                .iter()
+                .map(|offer| PlayerCard {
+                    item_type: offer.item_type.clone(),
+                    price: offer.price,
+                })
+                .collect(),
+            focused: self.offer_idx,
+        }
+    }
+
+    fn show_players(&mut self, view: Option<PlayerView>) {
+        let Some(screen) = self.store.borrow().player_screen() else {
+            self.shown_to_players = false;
+            self.error = Some(I18nPhrase::NoPlayerScreen.into());
+            return;
+        };
+
+        let result = match view {
+            Some(view) => screen.show(&view),
+            None => screen.clear(),
+        };
+
+        self.error = result.err().map(|e| {
+            log::error!("Could not show the players the offer: {e:?}");
+            Message::from(I18nPhrase::CouldNotShowPlayers).with("error", e)
+        });
+    }
+
+    fn export(&mut self) {
+        let settings = self.settings.borrow();
+        let i18n = Translator::new(settings.settings());
+        let offer: Vec<_> = self
+            .current_offer
+            .iter()
+            .map(|offer| (offer.item_type.clone(), offer.price))
+            .collect();
+        let handout = Handout::offer(self.title(&i18n), &offer, &i18n);
+
+        self.export_status = Some(export_handout(&self.store.borrow(), settings.settings(), &handout, &i18n));
+    }
+
+    fn toggle_show_players(&mut self) {
+        self.shown_to_players = !self.shown_to_players;
+
+        let view = self.shown_to_players.then(|| self.player_view());
+        self.show_players(view);
+    }
+
     pub fn hide_prices(&mut self) {
         self.current_offer = self
             .current_offer
             .iter()
             .map(|offer| 
-                Offer { stocked_item: offer.stocked_item.clone(), price: None })
+                Offer { item_type: offer.item_type.clone(), price: None })
             .collect();
     }
 
@@ -64,62 +172,67 @@ impl<'a> OfferPage<'a> {
         self.current_offer = self
             .current_offer
             .iter()
-            .map(|offer| {
-                let opt_d_expr: Result<Expr, _> = cost_expr(&offer.stocked_item.item_type).as_str().parse();
-
-                let roll = if let Ok(d_expr) = opt_d_expr {
-                    d_expr
-                        .eval(&mut tyche::dice::roller::FastRand::default())
-                        .ok()
-                        .map(|ev| ev.calc())
-                } else {
-                    None
-                };
-
-                let price = if let Some(real_roll) = roll {
-                    if let Ok(val) = real_roll {
-                        Some((val as isize).gold())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                Offer {
-                    stocked_item: offer.stocked_item.clone(),
-                    price,
-                }
+            .map(|offer| Offer {
+                item_type: offer.item_type.clone(),
+                price: roll_gold(&cost_expr(&offer.item_type)),
             })
             .collect();
     }
 }
 
-impl<'a> RenderablePage for OfferPage<'a> {
-    fn title(&self) -> String {
-        format!("Offer for {}", self.shop.name)
+impl RenderablePage for OfferPage {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        let store = self.store.borrow();
+        let campaign = store.campaign();
+
+        i18n.i18n_with(
+            I18nPhrase::OfferTitle,
+            &[
+                ("shop", &campaign.shops[self.shop_idx].name),
+                ("date", &campaign.calendar.format(&self.offered_on)),
+            ],
+        )
     }
 
     fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn I18ner) {
-        let [offers_area, menu_area] = Layout::default()
+        let [title_area, offers_area] = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
-            .constraints([Constraint::Fill(1), Constraint::Length(3)])
+            .constraints([Constraint::Length(1), Constraint::Fill(1)])
             .areas(area);
-        
-        let offer_areas: [Rect; 3] = Layout::default()
+
+        let palette = theme::palette();
+        let mut title = vec![Span::raw(self.title(i18n)).bold()];
+        if self.shown_to_players {
+            title.push(Span::raw(format!(" · {}", i18n.i18n(I18nPhrase::ShownToPlayers))).fg(palette.highlight));
+        }
+        if let Some(error) = &self.error {
+            title.push(Span::raw(format!(" · {}", i18n.message(error))).fg(palette.error));
+        }
+
+        if let Some(status) = export_status_line(&self.export_status, i18n) {
+            let style = status.style;
+            title.push(Span::raw(" · "));
+            title.extend(status.spans.into_iter().map(|span| span.patch_style(style)));
+        }
+
+        frame.render_widget(Line::from(title).centered(), title_area);
+
+        let offer_areas = Layout::default()
             .direction(ratatui::layout::Direction::Horizontal)
-            .constraints([Constraint::Fill(1); 3])
-            .areas(offers_area);
+            .constraints(vec![Constraint::Fill(1); self.current_offer.len()])
+            .split(offers_area);
+        let pricing = self.settings.borrow().settings().pricing;
 
-        for (idx, (offer_area, offer)) in offer_areas
-            .into_iter()
+        for (idx, ((offer_area, offer), card_scroll)) in offer_areas
+            .iter()
             .zip(self.current_offer.iter())
+            .zip(self.card_scrolls.iter_mut())
             .enumerate()
         {
             let [upper_area, lower_area] = Layout::default()
                 .direction(ratatui::layout::Direction::Vertical)
                 .constraints([Constraint::Fill(1), Constraint::Length(3)])
-                .areas(offer_area);
+                .areas(*offer_area);
 
             let block = if idx == self.offer_idx {
                 Block::bordered().border_type(ratatui::widgets::BorderType::Thick)
@@ -127,20 +240,9 @@ impl<'a> RenderablePage for OfferPage<'a> {
                 Block::bordered().border_type(ratatui::widgets::BorderType::Plain)
             };
 
-            let par = Paragraph::new(vec![
-                Line::raw(offer.stocked_item.item_type.name.clone()),
-                Line::from(vec![
-                    Span::raw(offer.stocked_item.item_type.category.to_string()).italic(),
-                    Span::raw(", "),
-                    offer.stocked_item.item_type.rarity.as_span(),
-                ]),
-                Line::raw(" "),
-                Line::raw(offer.stocked_item.item_type.details.clone()),
-            ])
-            .block(block)
-            .wrap(Wrap { trim: true });
-
-            frame.render_widget(par, upper_area);
+            let card = ItemCard::new(&offer.item_type, i18n).language(i18n.language()).paragraph();
+
+            card_scroll.render(frame, upper_area, card, block);
 
             let l = if let Some(price) = &offer.price {
                 Line::from(vec![
@@ -152,15 +254,29 @@ impl<'a> RenderablePage for OfferPage<'a> {
                 Line::from(vec![
                     Span::raw(i18n.i18n(I18nPhrase::Roll)),
                     Span::raw(" "),
-                    Span::raw(xanathar_magic_item_cost(&offer.stocked_item.item_type)),
-                    Span::raw(" gp"),
+                    Span::raw(i18n.i18n_with(
+                        I18nPhrase::GoldPieces,
+                        &[("amount", &pricing.cost_expression(&offer.item_type))],
+                    )),
                 ])
                 .centered()
             };
 
-            frame.render_widget(l, lower_area);
+            let wanted_by: Vec<String> = self
+                .store
+                .borrow()
+                .campaign()
+                .wishing_characters(&offer.item_type.identifier)
+                .iter()
+                .map(|character| character.name.clone())
+                .collect();
+
+            let mut lines = vec![l];
+            if !wanted_by.is_empty() {
+                lines.push(Line::raw(i18n.i18n_with(I18nPhrase::WantedBy, &[("names", &wanted_by.join(", "))])).fg(palette.highlight).bold().centered());
+            }
 
-            frame.render_widget(KeyMenu { handles: self.get_handlers() }, menu_area);
+            frame.render_widget(Paragraph::new(lines), lower_area);
         }
     }
 
@@ -172,40 +288,40 @@ impl<'a> RenderablePage for OfferPage<'a> {
 
             log::debug!("KeyCode for Offer {:?}", key_event);
 
-            match key_event.code {
-                KeyCode::Right => self.offer_idx = min(2, self.offer_idx + 1),
-                KeyCode::Left => self.offer_idx = self.offer_idx.saturating_sub(1),
-                KeyCode::Char('P') => self.hide_prices(),
-                KeyCode::Char('p') => self.realize_prices(&xanathar_magic_item_cost),
-                _ => {}  
+            let action = self.keymap().action(key_event);
+            match action {
+                Some(OfferAction::NextOffer) => {
+                    self.offer_idx = min(self.current_offer.len().saturating_sub(1), self.offer_idx + 1)
+                }
+                Some(OfferAction::PreviousOffer) => self.offer_idx = self.offer_idx.saturating_sub(1),
+                Some(OfferAction::HidePrices) => self.hide_prices(),
+                Some(OfferAction::RollPrices) => {
+                    let pricing = self.settings.borrow().settings().pricing;
+                    self.realize_prices(&move |item| pricing.cost_expression(item))
+                }
+                Some(OfferAction::ToggleShowPlayers) => self.toggle_show_players(),
+                Some(OfferAction::Export) => self.export(),
+                Some(OfferAction::Scroll(code)) => {
+                    if let Some(card_scroll) = self.card_scrolls.get_mut(self.offer_idx) {
+                        card_scroll.handle_key(code);
+                    }
+                }
+                None => {}
+            }
+
+            let changes_offer = matches!(
+                action,
+                Some(OfferAction::NextOffer | OfferAction::PreviousOffer | OfferAction::RollPrices | OfferAction::HidePrices)
+            );
+            if self.shown_to_players && changes_offer {
+                self.show_players(Some(self.player_view()));
             }
         }
     }
 }
 
-impl<'a> HandlesKeyEvents for OfferPage<'a> {
-    fn get_handlers(&self) -> Vec<super::flow::KeyHandler> {
-        vec![
-            KeyHandler {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::NONE,
-                command: "Next Offer".into(),
-            },
-            KeyHandler {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::NONE,
-                command: "Previous Offer".into(),
-            },
-            KeyHandler {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::NONE,
-                command: "(Re)roll prices".into(),
-            },
-            KeyHandler {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::SHIFT,
-                command: "Show price formula".into(),
-            },
-        ]
+impl HandlesKeyEvents for OfferPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
     }
 }
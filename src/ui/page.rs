@@ -1,9 +1,16 @@
 use ratatui::{crossterm::event::Event, layout::Rect, Frame};
 
-use super::translator::I18ner;
+use super::{flow::HandlesKeyEvents, translator::I18ner};
 
-pub trait RenderablePage {
-    fn title(&self) -> String;
+/// A page declares its key bindings through [`HandlesKeyEvents`], so the app can show them.
+pub trait RenderablePage: HandlesKeyEvents {
+    fn title(&self, i18n: &dyn I18ner) -> String;
     fn draw(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner);
     fn handle_and_transact(&mut self, event: &Event);
+
+    /// Whether the page is editing text right now, so that plain keys must reach it
+    /// instead of triggering global shortcuts like quitting or switching tabs.
+    fn captures_input(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType},
+    DefaultTerminal, Frame,
+};
+
+use crate::{
+    config::{settings::Settings, theme},
+    state::player_view::{PlayerScreenWatcher, PlayerView},
+};
+
+use super::{
+    flow::KeyMap,
+    item_card::ItemCard,
+    key::KeyMenu,
+    scroll::ScrollView,
+    translator::{I18nPhrase, I18ner, Message, Translator},
+};
+
+/// How often the player view looks for something new from the dungeon master.
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy)]
+enum PlayerViewAction {
+    Scroll(KeyCode),
+    Quit,
+}
+
+/// Second instance for the players, showing only the cards and prices the dungeon master shows them.
+/// It has no controls besides scrolling, everything else happens in the instance of the dungeon master.
+pub struct PlayerViewApp {
+    watcher: PlayerScreenWatcher,
+    settings: Settings,
+
+    view: PlayerView,
+    card_scrolls: Vec<ScrollView>,
+    error: Option<Message>,
+
+    is_running: bool,
+}
+
+impl PlayerViewApp {
+    pub fn new(watcher: PlayerScreenWatcher, settings: Settings) -> Self {
+        Self {
+            watcher,
+            settings,
+            view: PlayerView::default(),
+            card_scrolls: vec![],
+            error: None,
+            is_running: true,
+        }
+    }
+
+    fn keymap(&self) -> KeyMap<PlayerViewAction> {
+        KeyMap::new("player-view")
+            .bind("down", KeyCode::Down, I18nPhrase::Scroll, PlayerViewAction::Scroll(KeyCode::Down))
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::ScrollUp, PlayerViewAction::Scroll(KeyCode::Up))
+            .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::PageUp, PlayerViewAction::Scroll(KeyCode::PageUp))
+            .bind_hidden("page-down", KeyCode::PageDown, I18nPhrase::PageDown, PlayerViewAction::Scroll(KeyCode::PageDown))
+            .bind("quit", KeyCode::Char('q'), I18nPhrase::Quit, PlayerViewAction::Quit)
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        while self.is_running {
+            self.update();
+            terminal.draw(|frame| self.draw(frame))?;
+            self.handle_events()?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self) {
+        match self.watcher.poll() {
+            Some(Ok(view)) => {
+                if view.cards != self.view.cards {
+                    self.card_scrolls = view.cards.iter().map(|_| ScrollView::new()).collect();
+                }
+                self.view = view;
+                self.error = None;
+            }
+            Some(Err(e)) => {
+                log::error!("Could not read the player view: {e:?}");
+                self.error = Some(Message::from(I18nPhrase::CouldNotReadPlayerView).with("error", e));
+            }
+            None => {}
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let i18n = Translator::new(&self.settings);
+        let palette = theme::palette();
+
+        let [title_area, cards_area, hint_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1), Constraint::Length(3)]).areas(frame.area());
+
+        let title = match &self.view.shop {
+            Some(shop) => shop.clone(),
+            None => i18n.i18n(I18nPhrase::PlayerViewTitle),
+        };
+        let mut title = vec![Span::raw(title).bold()];
+        if let Some(error) = &self.error {
+            title.push(Span::raw(format!(" · {}", i18n.message(error))).fg(palette.error));
+        }
+        frame.render_widget(Line::from(title).centered(), title_area);
+
+        if self.view.cards.is_empty() {
+            let [waiting_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(ratatui::layout::Flex::Center)
+                .areas(cards_area);
+            frame.render_widget(
+                Line::raw(i18n.i18n(I18nPhrase::WaitingForDungeonMaster)).italic().fg(palette.muted).centered(),
+                waiting_area,
+            );
+        } else {
+            self.draw_cards(frame, cards_area, &i18n);
+        }
+
+        let hints = KeyMenu {
+            i18n: &i18n,
+            handles: self.keymap().handlers(),
+            global_handles: vec![],
+        };
+        frame.render_widget(hints, hint_area);
+    }
+
+    fn draw_cards(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        let card_areas = Layout::horizontal(vec![Constraint::Fill(1); self.view.cards.len()]).split(area);
+
+        for (idx, ((card_area, card), card_scroll)) in card_areas
+            .iter()
+            .zip(self.view.cards.iter())
+            .zip(self.card_scrolls.iter_mut())
+            .enumerate()
+        {
+            let [upper_area, lower_area] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(*card_area);
+
+            let block = if idx == self.view.focused {
+                Block::bordered().border_type(BorderType::Thick)
+            } else {
+                Block::bordered().border_type(BorderType::Plain)
+            };
+
+            let paragraph = ItemCard::new(&card.item_type, i18n).language(i18n.language()).paragraph();
+            card_scroll.render(frame, upper_area, paragraph, block);
+
+            if let Some(price) = &card.price {
+                let price = Line::from(vec![
+                    Span::raw(i18n.i18n(I18nPhrase::Price)),
+                    Span::raw(" "),
+                    Span::raw(price.to_string()),
+                ]);
+                frame.render_widget(price.bold().centered(), lower_area);
+            }
+        }
+    }
+
+    fn handle_events(&mut self) -> anyhow::Result<()> {
+        if !event::poll(WATCH_INTERVAL)? {
+            return Ok(());
+        }
+
+        if let Event::Key(key_event) = event::read()? {
+            if key_event.kind != KeyEventKind::Press {
+                return Ok(());
+            }
+
+            match self.keymap().action(&key_event) {
+                Some(PlayerViewAction::Scroll(code)) => {
+                    if let Some(card_scroll) = self.card_scrolls.get_mut(self.view.focused) {
+                        card_scroll.handle_key(code);
+                    }
+                }
+                Some(PlayerViewAction::Quit) => self.is_running = false,
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn run_player_view(watcher: PlayerScreenWatcher, settings: Settings) -> anyhow::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = PlayerViewApp::new(watcher, settings).run(&mut terminal);
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,165 @@
+use ratatui::{
+    crossterm::event::KeyCode,
+    layout::{Margin, Rect},
+    widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+/// Scroll state for a wrapped text pane.
+///
+/// The content and viewport heights are measured on every render, so the offset
+/// always stays within the content and the scrollbar matches what is visible.
+#[derive(Debug, Default)]
+pub struct ScrollView {
+    offset: u16,
+    content_height: u16,
+    viewport_height: u16,
+    scrollbar_state: ScrollbarState,
+}
+
+impl ScrollView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn max_offset(&self) -> u16 {
+        self.content_height.saturating_sub(self.viewport_height)
+    }
+
+    fn clamp(&mut self) {
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    /// Jumps back to the top, e.g. because different content is shown now.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.offset = self.offset.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.offset = self.offset.saturating_add(lines);
+        self.clamp();
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.viewport_height.max(1));
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.viewport_height.max(1));
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.offset = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.offset = self.max_offset();
+    }
+
+    /// Handles Up, Down, PageUp, PageDown, Home and End. Returns whether the key was used.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll_down(1),
+            KeyCode::PageUp => self.page_up(),
+            KeyCode::PageDown => self.page_down(),
+            KeyCode::Home => self.scroll_to_top(),
+            KeyCode::End => self.scroll_to_bottom(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Renders `paragraph` inside `block` at the current offset, with a scrollbar on the
+    /// right border if the content does not fit.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, paragraph: Paragraph, block: Block) {
+        let inner = block.inner(area);
+
+        self.viewport_height = inner.height;
+        self.content_height = paragraph.line_count(inner.width).try_into().unwrap_or(u16::MAX);
+        self.clamp();
+
+        self.scrollbar_state = self
+            .scrollbar_state
+            .content_length(self.max_offset() as usize + 1)
+            .viewport_content_length(self.viewport_height.into())
+            .position(self.offset.into());
+
+        frame.render_widget(paragraph.scroll((self.offset, 0)).block(block), area);
+
+        if self.content_height > self.viewport_height {
+            let scroll_bar = Scrollbar::default().orientation(ScrollbarOrientation::VerticalRight);
+
+            frame.render_stateful_widget(
+                scroll_bar,
+                area.inner(Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut self.scrollbar_state,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, text::Line, Terminal};
+
+    use super::*;
+
+    /// Renders 20 lines into a bordered pane of 10 rows, so 8 of them are visible at a time.
+    fn render(view: &mut ScrollView) {
+        let mut terminal = Terminal::new(TestBackend::new(20, 10)).unwrap();
+        let lines: Vec<Line> = (0..20).map(|n| Line::raw(n.to_string())).collect();
+
+        terminal
+            .draw(|frame| view.render(frame, frame.area(), Paragraph::new(lines), Block::bordered()))
+            .unwrap();
+    }
+
+    #[test]
+    fn scrolling_stops_at_the_last_line() {
+        let mut view = ScrollView::new();
+        render(&mut view);
+
+        view.scroll_down(100);
+        assert_eq!(view.offset, 12);
+
+        view.offset = 50;
+        render(&mut view);
+        assert_eq!(view.offset, 12);
+    }
+
+    #[test]
+    fn pages_are_as_high_as_the_viewport() {
+        let mut view = ScrollView::new();
+        render(&mut view);
+
+        view.page_down();
+        assert_eq!(view.offset, 8);
+        view.page_down();
+        assert_eq!(view.offset, 12);
+        view.page_up();
+        assert_eq!(view.offset, 4);
+        view.page_up();
+        assert_eq!(view.offset, 0);
+    }
+
+    #[test]
+    fn home_and_end_jump_to_either_end() {
+        let mut view = ScrollView::new();
+        render(&mut view);
+
+        assert!(view.handle_key(KeyCode::End));
+        assert_eq!(view.offset, 12);
+        assert!(view.handle_key(KeyCode::Home));
+        assert_eq!(view.offset, 0);
+        assert!(!view.handle_key(KeyCode::Enter));
+    }
+}
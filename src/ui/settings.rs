@@ -1,45 +1,353 @@
-use ratatui::{crossterm::event::{Event, KeyCode, KeyEventKind}, layout::{Constraint, Layout}, text::Line};
+use std::path::PathBuf;
 
-use super::{key, page::RenderablePage};
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::config::{
+    settings::{HandoutFormat, Language, PricingStrategy, Settings, SharedSettings, Theme, MAX_AUTOSAVE_MINUTES, MAX_OFFER_SIZE, MIN_OFFER_SIZE},
+    theme,
+};
+
+use super::{
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    input::{cycle, TextInput},
+    page::RenderablePage,
+    translator::{I18nPhrase, I18ner},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+enum SettingsField {
+    DefaultCampaign,
+    Language,
+    Pricing,
+    OfferSize,
+    Theme,
+    HandoutFormat,
+    HidePricesInHandouts,
+    AutosaveInterval,
+    LogLevel,
+    NerdFont,
+}
+
+/// How a setting is edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldKind {
+    Path,
+    Choice,
+    Number,
+    Toggle,
+}
+
+impl SettingsField {
+    fn label(&self) -> I18nPhrase {
+        match self {
+            Self::DefaultCampaign => I18nPhrase::DefaultCampaign,
+            Self::Language => I18nPhrase::Language,
+            Self::Pricing => I18nPhrase::MagicItemPrices,
+            Self::OfferSize => I18nPhrase::ItemsPerOffer,
+            Self::Theme => I18nPhrase::ColorTheme,
+            Self::HandoutFormat => I18nPhrase::HandoutFormat,
+            Self::HidePricesInHandouts => I18nPhrase::HidePricesInHandouts,
+            Self::AutosaveInterval => I18nPhrase::AutosaveInterval,
+            Self::LogLevel => I18nPhrase::LogLevel,
+            Self::NerdFont => I18nPhrase::NerdFontIcons,
+        }
+    }
+
+    fn description(&self) -> I18nPhrase {
+        match self {
+            Self::DefaultCampaign => I18nPhrase::DefaultCampaignHelp,
+            Self::Language => I18nPhrase::LanguageHelp,
+            Self::Pricing => I18nPhrase::MagicItemPricesHelp,
+            Self::OfferSize => I18nPhrase::ItemsPerOfferHelp,
+            Self::Theme => I18nPhrase::ColorThemeHelp,
+            Self::HandoutFormat => I18nPhrase::HandoutFormatHelp,
+            Self::HidePricesInHandouts => I18nPhrase::HidePricesInHandoutsHelp,
+            Self::AutosaveInterval => I18nPhrase::AutosaveIntervalHelp,
+            Self::LogLevel => I18nPhrase::LogLevelHelp,
+            Self::NerdFont => I18nPhrase::NerdFontIconsHelp,
+        }
+    }
+
+    fn kind(&self) -> FieldKind {
+        match self {
+            Self::DefaultCampaign => FieldKind::Path,
+            Self::Language | Self::Pricing | Self::Theme | Self::HandoutFormat | Self::LogLevel => FieldKind::Choice,
+            Self::OfferSize | Self::AutosaveInterval => FieldKind::Number,
+            Self::HidePricesInHandouts | Self::NerdFont => FieldKind::Toggle,
+        }
+    }
+
+    fn value(&self, settings: &Settings, i18n: &dyn I18ner) -> String {
+        match self {
+            Self::DefaultCampaign => settings
+                .default_campaign
+                .as_ref()
+                .map_or_else(|| i18n.i18n(I18nPhrase::NoneSet), |path| path.display().to_string()),
+            Self::Language => format!("‹ {} ›", language_name(settings.language, i18n)),
+            Self::Pricing => format!("‹ {} ›", i18n.i18n(pricing_name(settings.pricing))),
+            Self::OfferSize => format!("‹ {} ›", settings.offer_size),
+            Self::Theme => format!("‹ {} ›", i18n.i18n(theme_name(settings.theme))),
+            Self::HandoutFormat => format!("‹ {} ›", handout_format_name(settings.handout_format, i18n)),
+            Self::HidePricesInHandouts => toggle_name(settings.hide_prices_in_handouts, i18n),
+            Self::AutosaveInterval => format!("‹ {} ›", autosave_name(settings.autosave_minutes, i18n)),
+            Self::LogLevel => format!("‹ {} ›", settings.log_level),
+            Self::NerdFont => toggle_name(settings.nerd_font, i18n),
+        }
+    }
+
+    /// Moves a choice or number one step, or flips a toggle.
+    fn step(&self, settings: &mut Settings, forward: bool) {
+        match self {
+            Self::DefaultCampaign => {}
+            Self::Language => settings.language = cycle(&settings.language, forward),
+            Self::Pricing => settings.pricing = cycle(&settings.pricing, forward),
+            Self::OfferSize => {
+                settings.offer_size = if forward {
+                    settings.offer_size.saturating_add(1)
+                } else {
+                    settings.offer_size.saturating_sub(1)
+                }
+                .clamp(MIN_OFFER_SIZE, MAX_OFFER_SIZE)
+            }
+            Self::Theme => settings.theme = cycle(&settings.theme, forward),
+            Self::HandoutFormat => settings.handout_format = cycle(&settings.handout_format, forward),
+            Self::HidePricesInHandouts => settings.hide_prices_in_handouts = !settings.hide_prices_in_handouts,
+            Self::AutosaveInterval => {
+                settings.autosave_minutes = if forward {
+                    settings.autosave_minutes.saturating_add(1).min(MAX_AUTOSAVE_MINUTES)
+                } else {
+                    settings.autosave_minutes.saturating_sub(1)
+                }
+            }
+            Self::LogLevel => settings.log_level = cycle(&settings.log_level, forward),
+            Self::NerdFont => settings.nerd_font = !settings.nerd_font,
+        }
+    }
+}
+
+/// Languages are named in their own language, so they can be found in any of them.
+fn language_name(language: Language, i18n: &dyn I18ner) -> String {
+    match language {
+        Language::System => format!("{} ({})", i18n.i18n(I18nPhrase::SystemLanguage), language.resolve()),
+        language => language.to_string(),
+    }
+}
+
+fn toggle_name(on: bool, i18n: &dyn I18ner) -> String {
+    i18n.i18n(if on { I18nPhrase::ToggleOn } else { I18nPhrase::ToggleOff })
+}
+
+fn autosave_name(minutes: u8, i18n: &dyn I18ner) -> String {
+    match minutes {
+        0 => i18n.i18n(I18nPhrase::AutosaveOff),
+        minutes => i18n.i18n_with(I18nPhrase::AutosaveEvery, &[("minutes", &minutes.to_string())]),
+    }
+}
+
+/// Markdown and HTML are names, only plain text is translated.
+fn handout_format_name(format: HandoutFormat, i18n: &dyn I18ner) -> String {
+    match format {
+        HandoutFormat::Text => i18n.i18n(I18nPhrase::PlainText),
+        format => format.to_string(),
+    }
+}
+
+fn pricing_name(pricing: PricingStrategy) -> I18nPhrase {
+    match pricing {
+        PricingStrategy::Xanathar => I18nPhrase::XanatharsGuide,
+        PricingStrategy::DungeonMastersGuide => I18nPhrase::DungeonMastersGuide,
+    }
+}
+
+fn theme_name(theme: Theme) -> I18nPhrase {
+    match theme {
+        Theme::Dark => I18nPhrase::DarkTheme,
+        Theme::Light => I18nPhrase::LightTheme,
+        Theme::HighContrast => I18nPhrase::HighContrastTheme,
+        Theme::ColorBlindSafe => I18nPhrase::ColorBlindSafeTheme,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SettingsAction {
+    Up,
+    Down,
+    Next,
+    Previous,
+    Toggle,
+    Edit,
+    Clear,
+    Confirm,
+    Cancel,
+}
 
 #[derive(Debug)]
 pub struct SettingsPage {
-    my_string: String,
+    settings: SharedSettings,
+    table_state: TableState,
+
+    /// The default campaign path while it is typed.
+    path_input: Option<TextInput>,
+    error: Option<String>,
 }
 
 impl SettingsPage {
-    pub fn new() -> Self {
+    pub fn new(settings: SharedSettings) -> Self {
         Self {
-            my_string: String::new()
+            settings,
+            table_state: TableState::default().with_selected(Some(0)),
+            path_input: None,
+            error: None,
         }
     }
-}
 
-impl RenderablePage for SettingsPage {
-    fn title(&self) -> String {
-        "Settings".into()
+    fn selected_field(&self) -> SettingsField {
+        let selected = self.table_state.selected().unwrap_or(0);
+        SettingsField::iter().nth(selected).unwrap_or(SettingsField::DefaultCampaign)
+    }
+
+    fn keymap(&self) -> KeyMap<SettingsAction> {
+        if self.path_input.is_some() {
+            return KeyMap::for_text_input("settings")
+                .bind("confirm", KeyCode::Enter, I18nPhrase::Confirm, SettingsAction::Confirm)
+                .bind("cancel", KeyCode::Esc, I18nPhrase::Cancel, SettingsAction::Cancel);
+        }
+
+        let keymap = match self.selected_field().kind() {
+            FieldKind::Path => KeyMap::new("settings")
+                .bind("edit", KeyCode::Enter, I18nPhrase::Edit, SettingsAction::Edit)
+                .bind("clear", KeyCode::Delete, I18nPhrase::Clear, SettingsAction::Clear),
+            FieldKind::Choice | FieldKind::Number => KeyMap::new("settings")
+                .bind("next", KeyCode::Right, I18nPhrase::Next, SettingsAction::Next)
+                .bind("previous", KeyCode::Left, I18nPhrase::Previous, SettingsAction::Previous),
+            FieldKind::Toggle => KeyMap::new("settings")
+                .bind("toggle", KeyCode::Char(' '), I18nPhrase::Toggle, SettingsAction::Toggle)
+                .bind_hidden("toggle", KeyCode::Enter, I18nPhrase::Toggle, SettingsAction::Toggle),
+        };
+
+        keymap
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousSetting, SettingsAction::Up)
+            .bind_hidden("down", KeyCode::Down, I18nPhrase::NextSetting, SettingsAction::Down)
     }
 
-    fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn super::translator::I18ner) {
-        let [upper_area, below_area] = Layout::default()
-            .direction(ratatui::layout::Direction::Vertical)
-            .constraints(vec![Constraint::Fill(1), Constraint::Fill(1),])
-            .areas(area);
+    /// Changes the settings, saves them and applies what does not take effect on its own.
+    fn update(&mut self, change: impl FnOnce(&mut Settings)) {
+        let mut store = self.settings.borrow_mut();
+        self.error = store.update(change).err().map(|e| format!("{e:#}"));
 
-        frame.render_widget(Line::raw("There are no settings yet. But you can type some nice text below..."), upper_area);
-        frame.render_widget(Line::raw(self.my_string.as_str()), below_area);
+        log::set_max_level(store.settings().log_level.filter());
+        theme::apply(store.settings().theme);
     }
 
-    fn handle_and_transact(&mut self, event: &ratatui::crossterm::event::Event) {
-        if let Event::Key(key_event) = event {
-            if key_event.kind != KeyEventKind::Press {
-                return
+    fn perform(&mut self, action: SettingsAction) {
+        let field = self.selected_field();
+
+        match action {
+            SettingsAction::Up => self.table_state.select_previous(),
+            SettingsAction::Down => {
+                let last = SettingsField::iter().count() - 1;
+                let selected = self.table_state.selected().map_or(0, |idx| (idx + 1).min(last));
+                self.table_state.select(Some(selected));
+            }
+            SettingsAction::Next | SettingsAction::Toggle => self.update(|settings| field.step(settings, true)),
+            SettingsAction::Previous => self.update(|settings| field.step(settings, false)),
+            SettingsAction::Edit => {
+                let path = self.settings.borrow().settings().default_campaign.clone();
+                let path = path.map(|path| path.display().to_string()).unwrap_or_default();
+                self.path_input = Some(TextInput::new(&path));
             }
-    
-            match key_event.code {
-                KeyCode::Char(c) => self.my_string.push(c),
-                _ => {},
+            SettingsAction::Clear => self.update(|settings| settings.default_campaign = None),
+            SettingsAction::Confirm => {
+                if let Some(input) = self.path_input.take() {
+                    let path = input.value().trim();
+                    let path = (!path.is_empty()).then(|| PathBuf::from(path));
+                    self.update(|settings| settings.default_campaign = path);
+                }
             }
+            SettingsAction::Cancel => self.path_input = None,
+        }
+    }
+
+    fn footer(&self, i18n: &dyn I18ner) -> Vec<Line<'static>> {
+        let store = self.settings.borrow();
+        let palette = theme::palette();
+
+        let mut lines = vec![Line::raw(i18n.i18n(self.selected_field().description()))];
+        lines.push(match store.path() {
+            Some(path) => Line::raw(i18n.i18n_with(I18nPhrase::SavedTo, &[("path", &path.display().to_string())])).fg(palette.muted),
+            None => Line::raw(i18n.i18n(I18nPhrase::SettingsNotSaved)).fg(palette.muted),
+        });
+        if let Some(error) = &self.error {
+            lines.push(Line::raw(error.clone()).fg(palette.error));
         }
+        lines
+    }
+}
+
+impl HandlesKeyEvents for SettingsPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
     }
-}
\ No newline at end of file
+}
+
+impl RenderablePage for SettingsPage {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        i18n.i18n(I18nPhrase::Settings)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
+        let [table_area, input_area, footer_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(if self.path_input.is_some() { 3 } else { 0 }),
+            Constraint::Length(4),
+        ])
+        .areas(area);
+
+        let table = {
+            let store = self.settings.borrow();
+            let rows = SettingsField::iter()
+                .map(|field| Row::new(vec![i18n.i18n(field.label()), field.value(store.settings(), i18n)]));
+
+            Table::new(rows, [Constraint::Length(20), Constraint::Fill(1)])
+                .header(Row::new(vec![i18n.i18n(I18nPhrase::Setting), i18n.i18n(I18nPhrase::Value)]).style(Style::default().bg(theme::palette().header)))
+                .block(Block::bordered().title(i18n.i18n(I18nPhrase::Settings)))
+                .highlight_style(Style::new().bold())
+                .highlight_symbol(">> ")
+                .highlight_spacing(ratatui::widgets::HighlightSpacing::Always)
+        };
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+
+        if let Some(input) = &self.path_input {
+            input.render(frame, input_area, Block::bordered().title(i18n.i18n(I18nPhrase::CampaignFolderPath)), true);
+        }
+
+        frame.render_widget(Paragraph::new(self.footer(i18n)).wrap(Wrap { trim: false }), footer_area);
+    }
+
+    fn handle_and_transact(&mut self, event: &Event) {
+        let Event::Key(key_event) = event else {
+            return;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+
+        if let Some(action) = self.keymap().action(key_event) {
+            self.perform(action);
+        } else if let Some(input) = &mut self.path_input {
+            input.handle_key(key_event);
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.path_input.is_some()
+    }
+}
@@ -1,21 +1,32 @@
-use std::{cell::RefCell, rc::Rc};
-
 use ratatui::{
     crossterm::event::{Event, KeyCode, KeyEventKind},
-    layout::{Constraint, Layout, Margin, Rect},
+    layout::{Constraint, Layout, Rect},
     style::Stylize,
-    text::{Line, Span, Text},
     widgets::{
-        Block, BorderType, Paragraph, Row, Scrollbar, ScrollbarState, Table, TableState, Wrap,
+        Block, BorderType, Cell, Paragraph, Row, Table, TableState,
     }, Frame,
 };
 
-use crate::data::{item::ItemType, shop::{Shop, StockedItem}};
+use crate::{
+    config::{settings::SharedSettings, theme},
+    data::{calendar::CampaignDate, item::ItemIdentifier, shop::{Stock, StockedItem}},
+    export::Handout,
+    state::campaign::{CampaignCommand, SharedCampaign},
+};
 
-use super::{offer::OfferPage, page::RenderablePage, translator::I18ner};
+use super::{
+    display::{rarity_symbol, stock_name},
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    handout::{export_handout, export_status_line, ExportStatus},
+    item_card::ItemCard,
+    item_picker::{ItemPicker, PickerOutcome},
+    offer::OfferPage,
+    page::RenderablePage,
+    scroll::ScrollView,
+    translator::{I18nPhrase, I18ner, Translator},
+};
 
 use strum::IntoEnumIterator;
-use tui_markdown;
 
 use std::collections::VecDeque;
 
@@ -41,57 +52,154 @@ impl FocusedArea {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Transaction {
     ScrollUp,
     ScrollDown,
     PageUp,
     PageDown,
+    ScrollToTop,
+    ScrollToBottom,
     ShiftFocusForward,
     ShiftFocusBackward,
     CreateOffer,
+    CloseOffer,
+    SellSelected,
+    OpenItemPicker,
+    AddItem(String),
+    IncreaseStock,
+    ToggleInfinite,
+    RemoveSelected,
+    ExportInventory,
 }
 
 #[derive(Debug)]
-pub struct ShopPage<'a> {
-    shop: &'a Shop<'a>,
+pub struct ShopPage {
+    store: SharedCampaign,
+    settings: SharedSettings,
+    shop_idx: usize,
+    visited_on: CampaignDate,
     inventory_table_state: TableState,
     focus: FocusedArea,
 
-    // refactor this into a f'ing useful abstraction for scrolling text content
-    detail_scroll_state: ScrollbarState,
-    detail_scroll: u16,
-    detail_height: u16,
+    detail_scroll: ScrollView,
+    inventory_height: u16,
 
-    overlay_page: Option<OfferPage<'a>>,
+    overlay_page: Option<OfferPage>,
+    picker: Option<ItemPicker>,
+    export_status: ExportStatus,
 
     transactions: VecDeque<Transaction>,
 }
 
-impl<'a> ShopPage<'a> {
-    pub fn new(shop: &'a Shop<'a>) -> Self {
-        Self {
-            inventory_table_state: TableState::default().with_selected(
-                if !shop.get_inventory().is_empty() {
-                    Some(0)
-                } else {
-                    None
-                },
-            ),
-            shop,
+impl ShopPage {
+    pub fn new(store: SharedCampaign, settings: SharedSettings, shop_idx: usize, visited_on: CampaignDate) -> Self {
+        let mut page = Self {
+            inventory_table_state: TableState::default(),
+            store,
+            settings,
+            shop_idx,
+            visited_on,
             focus: FocusedArea::Inventory,
             transactions: VecDeque::new(),
-            detail_scroll: 0,
-            detail_height: 10, // questionable initializer
+            detail_scroll: ScrollView::new(),
+            inventory_height: 1,
             overlay_page: None,
-            detail_scroll_state: ScrollbarState::default(),
+            picker: None,
+            export_status: None,
+        };
+        page.select_inventory_row(0);
+        page
+    }
+
+    /// Whether an offer or the item picker is open on top of the shop.
+    pub fn is_showing_popup(&self) -> bool {
+        self.overlay_page.is_some() || self.picker.is_some()
+    }
+
+    fn keymap(&self) -> KeyMap<Transaction> {
+        if self.overlay_page.is_some() {
+            return KeyMap::new("shop").bind("close-offer", KeyCode::Esc, I18nPhrase::BackToShop, Transaction::CloseOffer);
         }
+
+        KeyMap::new("shop")
+            .bind("create-offer", KeyCode::Char('o'), I18nPhrase::GenerateOffer, Transaction::CreateOffer)
+            .bind("sell", KeyCode::Char('s'), I18nPhrase::SellOne, Transaction::SellSelected)
+            .bind("add-item", KeyCode::Char('a'), I18nPhrase::AddItem, Transaction::OpenItemPicker)
+            .bind("more-stock", KeyCode::Char('+'), I18nPhrase::OneMore, Transaction::IncreaseStock)
+            .bind("toggle-infinite", KeyCode::Char('i'), I18nPhrase::Infinite, Transaction::ToggleInfinite)
+            .bind("remove", KeyCode::Char('x'), I18nPhrase::Remove, Transaction::RemoveSelected)
+            .bind("export", KeyCode::Char('E'), I18nPhrase::ExportHandout, Transaction::ExportInventory)
+            .bind_hidden("remove", KeyCode::Delete, I18nPhrase::Remove, Transaction::RemoveSelected)
+            .bind("next-focus", KeyCode::Right, I18nPhrase::SwitchFocus, Transaction::ShiftFocusForward)
+            .bind_hidden("previous-focus", KeyCode::Left, I18nPhrase::SwitchFocusBack, Transaction::ShiftFocusBackward)
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::ScrollUp, Transaction::ScrollUp)
+            .bind_hidden("down", KeyCode::Down, I18nPhrase::ScrollDown, Transaction::ScrollDown)
+            .bind_hidden("page-up", KeyCode::PageUp, I18nPhrase::PageUp, Transaction::PageUp)
+            .bind_hidden("page-down", KeyCode::PageDown, I18nPhrase::PageDown, Transaction::PageDown)
+            .bind_hidden("top", KeyCode::Home, I18nPhrase::ScrollToTop, Transaction::ScrollToTop)
+            .bind_hidden("bottom", KeyCode::End, I18nPhrase::ScrollToBottom, Transaction::ScrollToBottom)
+    }
+
+    fn inventory_len(&self) -> usize {
+        self.store.borrow().campaign().shops[self.shop_idx].get_inventory().len()
     }
 
     pub fn selected_item(&self) -> Option<StockedItem> {
         let idx = self.inventory_table_state.selected()?;
-        let item = self.shop.get_inventory()[idx].to_owned();
-        Some(item)
+
+        self.store.borrow().campaign().shops[self.shop_idx]
+            .get_inventory()
+            .get(idx)
+            .cloned()
+    }
+
+    fn apply(&mut self, command: CampaignCommand) {
+        if let Err(e) = self.store.borrow_mut().apply(command) {
+            log::warn!("Could not change shop: {e:?}");
+        }
+    }
+
+    fn set_selected_stock(&mut self, change: impl Fn(&Stock) -> Stock) {
+        let Some(selected) = self.selected_item() else {
+            return;
+        };
+
+        self.apply(CampaignCommand::SetStock {
+            shop: self.shop_idx,
+            stock: change(&selected.stock),
+            item: selected.identifier,
+        });
+    }
+
+    fn add_item(&mut self, identifier: String) {
+        let index = self.inventory_len();
+
+        self.apply(CampaignCommand::InsertStock {
+            shop: self.shop_idx,
+            index,
+            item: StockedItem {
+                identifier,
+                stock: Stock::Stocked(std::num::NonZero::<u32>::MIN),
+                days_sold_out: 0,
+            },
+        });
+
+        self.select_inventory_row(index);
+    }
+
+    fn remove_selected(&mut self) {
+        let Some(selected) = self.selected_item() else {
+            return;
+        };
+
+        self.apply(CampaignCommand::RemoveStock {
+            shop: self.shop_idx,
+            item: selected.identifier,
+        });
+
+        let selected_row = self.inventory_table_state.selected().unwrap_or(0);
+        self.select_inventory_row(selected_row);
     }
 
     fn perform_transactions(&mut self) {
@@ -103,53 +211,87 @@ impl<'a> ShopPage<'a> {
         }
     }
 
+    fn select_inventory_row(&mut self, idx: usize) {
+        let inventory_len = self.inventory_len();
+        if inventory_len == 0 {
+            self.inventory_table_state.select(None);
+        } else {
+            self.inventory_table_state.select(Some(idx.min(inventory_len - 1)));
+        }
+        self.detail_scroll.reset();
+    }
+
     fn transact(&mut self, transaction: Transaction) -> Option<Transaction> {
+        let selected = self.inventory_table_state.selected().unwrap_or(0);
+        let page = self.inventory_height.max(1) as usize;
+
         match transaction {
             Transaction::ScrollUp => match self.focus {
-                FocusedArea::Details => self.detail_scroll = self.detail_scroll.saturating_sub(1),
-                FocusedArea::Inventory => {
-                    self.inventory_table_state.scroll_up_by(1);
-                    self.detail_scroll = 0;
-                }
+                FocusedArea::Details => self.detail_scroll.scroll_up(1),
+                FocusedArea::Inventory => self.select_inventory_row(selected.saturating_sub(1)),
             },
             Transaction::ScrollDown => match self.focus {
-                FocusedArea::Details => self.detail_scroll = self.detail_scroll.saturating_add(1),
-                FocusedArea::Inventory => {
-                    self.inventory_table_state.scroll_down_by(1);
-                    self.detail_scroll = 0;
-                }
+                FocusedArea::Details => self.detail_scroll.scroll_down(1),
+                FocusedArea::Inventory => self.select_inventory_row(selected.saturating_add(1)),
             },
             Transaction::PageUp => match self.focus {
-                FocusedArea::Details => {
-                    self.detail_scroll = self.detail_scroll.saturating_sub(self.detail_height)
-                }
-                FocusedArea::Inventory => {
-                    self.inventory_table_state.scroll_up_by(self.detail_height); // @todo wrong variable, should be height of inventory
-                    self.detail_scroll = 0;
-                }
+                FocusedArea::Details => self.detail_scroll.page_up(),
+                FocusedArea::Inventory => self.select_inventory_row(selected.saturating_sub(page)),
             },
             Transaction::PageDown => match self.focus {
-                FocusedArea::Details => {
-                    self.detail_scroll = self.detail_scroll.saturating_add(self.detail_height)
-                }
-                FocusedArea::Inventory => {
-                    self.inventory_table_state
-                        .scroll_down_by(self.detail_height); // @todo wrong variable, should be height of inventory
-                    self.detail_scroll = 0;
-                }
+                FocusedArea::Details => self.detail_scroll.page_down(),
+                FocusedArea::Inventory => self.select_inventory_row(selected.saturating_add(page)),
+            },
+            Transaction::ScrollToTop => match self.focus {
+                FocusedArea::Details => self.detail_scroll.scroll_to_top(),
+                FocusedArea::Inventory => self.select_inventory_row(0),
+            },
+            Transaction::ScrollToBottom => match self.focus {
+                FocusedArea::Details => self.detail_scroll.scroll_to_bottom(),
+                FocusedArea::Inventory => self.select_inventory_row(usize::MAX),
             },
             Transaction::ShiftFocusForward => self.focus = self.focus.next(),
             Transaction::ShiftFocusBackward => self.focus = self.focus.previous(),
             Transaction::CreateOffer => {
-                self.overlay_page = Some(OfferPage::new(self.shop));
+                self.overlay_page = Some(OfferPage::new(
+                    self.store.clone(),
+                    self.settings.clone(),
+                    self.shop_idx,
+                    self.visited_on,
+                ));
             }
+            Transaction::CloseOffer => self.overlay_page = None,
+            Transaction::SellSelected => self.set_selected_stock(Stock::after_sale),
+            Transaction::OpenItemPicker => self.picker = Some(ItemPicker::new(I18nPhrase::AddItemTitle)),
+            Transaction::AddItem(identifier) => self.add_item(identifier),
+            Transaction::IncreaseStock => self.set_selected_stock(Stock::one_more),
+            Transaction::ToggleInfinite => self.set_selected_stock(|stock| match stock {
+                Stock::Infinite => Stock::Stocked(std::num::NonZero::<u32>::MIN),
+                _ => Stock::Infinite,
+            }),
+            Transaction::RemoveSelected => self.remove_selected(),
+            Transaction::ExportInventory => self.export_inventory(),
         }
 
-        log::info!("Transaction in ShopPage. New State: {:?}", self);
+        log::info!(
+            "Transaction in ShopPage. Focus: {:?}, selected row: {:?}",
+            self.focus,
+            self.inventory_table_state.selected()
+        );
 
         None
     }
 
+    fn export_inventory(&mut self) {
+        let store = self.store.borrow();
+        let settings = self.settings.borrow();
+        let i18n = Translator::new(settings.settings());
+        let campaign = store.campaign();
+        let handout = Handout::inventory(campaign, &campaign.shops[self.shop_idx], settings.settings().pricing, &i18n);
+
+        self.export_status = Some(export_handout(&store, settings.settings(), &handout, &i18n));
+    }
+
     fn border_type_for_area(&self, content: FocusedArea) -> BorderType {
         if self.focus == content {
             BorderType::Thick
@@ -159,83 +301,124 @@ impl<'a> ShopPage<'a> {
     }
 
     fn draw_self(&mut self, frame: &mut Frame, area: Rect, i18n: &dyn I18ner) {
-        let [content_area, menu_area] = Layout::default()
-            .direction(ratatui::layout::Direction::Vertical)
-            .constraints([Constraint::Fill(1), Constraint::Length(3)])
-            .areas(area);
-
         let [inventory_area, details_area] = Layout::default()
             .direction(ratatui::layout::Direction::Horizontal)
             .constraints([Constraint::Fill(1), Constraint::Fill(1)])
-            .areas(content_area);
+            .areas(area);
+
+        let store = self.store.borrow();
+        let campaign = store.campaign();
+        let shop = &campaign.shops[self.shop_idx];
+        let inventory: Vec<_> = shop
+            .get_inventory()
+            .iter()
+            .map(|stocked| (stocked, campaign.registry.get(&stocked.identifier)))
+            .collect();
+
+        let mut inventory_block = Block::bordered()
+            .title(format!("{} ({})", shop.name, campaign.calendar.format(&self.visited_on)))
+            .border_type(self.border_type_for_area(FocusedArea::Inventory));
+        if let Some(status) = export_status_line(&self.export_status, i18n) {
+            inventory_block = inventory_block.title_bottom(status);
+        }
+
+        // One line of the table goes to its header.
+        self.inventory_height = inventory_block.inner(inventory_area).height.saturating_sub(1);
+
+        let wanted_by = |identifier: &ItemIdentifier| {
+            campaign
+                .wishing_characters(identifier)
+                .iter()
+                .map(|character| character.name.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
 
+        let palette = theme::palette();
         let table = Table::new(
-            self.shop
-                .get_inventory()
+            inventory
                 .iter()
-                .map(|item| Row::new(vec![item.item_type.rarity.to_string(), item.item_type.name.clone()])),
-            [1, 50],
+                .map(|(stocked, item_type)| match item_type {
+                    Some(item_type) => Row::new(vec![
+                        Cell::from(rarity_symbol(&item_type.rarity)),
+                        Cell::from(item_type.name_in(i18n.language()).to_string()),
+                        Cell::from(wanted_by(&stocked.identifier)),
+                        Cell::from(stock_name(&stocked.stock, i18n)),
+                    ]),
+                    None => Row::new(vec![
+                        "?".to_string(),
+                        i18n.i18n_with(I18nPhrase::UnknownItem, &[("item", &stocked.identifier.to_string())]),
+                        wanted_by(&stocked.identifier),
+                        stock_name(&stocked.stock, i18n),
+                    ])
+                    .fg(palette.warning),
+                }),
+            [Constraint::Length(1), Constraint::Fill(2), Constraint::Fill(1), Constraint::Length(11)],
         )
-        .block(
-            Block::bordered()
-                .title(self.shop.name.clone())
-                .border_type(self.border_type_for_area(FocusedArea::Inventory)),
+        .header(
+            Row::new(vec![
+                String::new(),
+                i18n.i18n(I18nPhrase::Item),
+                i18n.i18n(I18nPhrase::WantedByColumn),
+                i18n.i18n(I18nPhrase::Stock),
+            ])
+            .bold(),
         )
+        .block(inventory_block)
         //.row_highlight_style(Style::new().white().on_green())
         .highlight_symbol(">> ")
         .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
         frame.render_stateful_widget(table, inventory_area, &mut self.inventory_table_state);
 
-        let s: String = if let Some(item) = self.selected_item() {
-            item.item_type.details.clone()
-        } else {
-            "(no item selected)".into()
-        };
-
-        let text = tui_markdown::from_str(s.as_str());
-
-        // @todo find a way to detemine the length of the content properly
-        // self.detail_scroll = min(self.detail_scroll, lines as u16);
-        self.detail_scroll_state.position(self.detail_scroll.into());
-
-        let paragraph_block = Block::bordered()
-            .title("Detailtext")
+        let details_block = Block::bordered()
+            .title(i18n.i18n(I18nPhrase::Details))
             .border_type(self.border_type_for_area(FocusedArea::Details));
 
-        self.detail_height = paragraph_block.inner(area).height;
-
-        let nice_paragraph = Paragraph::new(text)
-            .wrap(Wrap { trim: true })
-            .scroll((self.detail_scroll, 0))
-            .block(paragraph_block);
+        let selected = self
+            .inventory_table_state
+            .selected()
+            .and_then(|idx| inventory.get(idx));
+
+        let details = match selected {
+            Some((_, Some(item_type))) => ItemCard::new(item_type, i18n).language(i18n.language()).paragraph(),
+            Some((stocked, None)) => Paragraph::new(i18n.i18n_with(
+                I18nPhrase::RegistryLacksItem,
+                &[("item", &stocked.identifier.to_string())],
+            ))
+            .wrap(ratatui::widgets::Wrap { trim: false }),
+            None => Paragraph::new(i18n.i18n(I18nPhrase::NoItemSelected)),
+        };
 
-        frame.render_widget(nice_paragraph, details_area);
+        self.detail_scroll.render(frame, details_area, details, details_block);
 
-        let scroll_bar =
-            Scrollbar::default().orientation(ratatui::widgets::ScrollbarOrientation::VerticalRight);
 
-        frame.render_stateful_widget(
-            scroll_bar,
-            details_area.inner(Margin {
-                vertical: 1,
-                horizontal: 0,
-            }),
-            &mut self.detail_scroll_state,
-        );
+        if let Some(picker) = &mut self.picker {
+            picker.render(frame, area, &campaign.registry, i18n);
+        }
+    }
+}
 
-        let menu_bar = Paragraph::new(Text::from(vec![Line::from(vec![
-            Span::raw("o").black().on_white(),
-            Span::raw(" generate offer"),
-        ])]))
-        .centered();
+impl HandlesKeyEvents for ShopPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        if let Some(picker) = &self.picker {
+            return picker.get_handlers();
+        }
 
-        frame.render_widget(menu_bar, menu_area);
+        let mut handlers = self.keymap().handlers();
+        if let Some(page) = &self.overlay_page {
+            handlers.extend(page.get_handlers());
+        }
+        handlers
     }
 }
 
-impl<'a> RenderablePage for ShopPage<'a> {
-    fn title(&self) -> String {
-        self.shop.name.clone()
+impl RenderablePage for ShopPage {
+    fn title(&self, _i18n: &dyn I18ner) -> String {
+        self.store.borrow().campaign().shops[self.shop_idx].name.clone()
+    }
+
+    fn captures_input(&self) -> bool {
+        self.picker.is_some()
     }
 
     fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn I18ner) {
@@ -247,46 +430,38 @@ impl<'a> RenderablePage for ShopPage<'a> {
     }
 
     fn handle_and_transact(&mut self, event: &Event) {
-        if let Event::Key(key_event) = &event {
-            match key_event.code {
-                KeyCode::Esc if key_event.kind == KeyEventKind::Press => {
-                    self.overlay_page = None;
-                    return;
-                },
-                _ => {},
+        if let (Some(picker), Event::Key(key_event)) = (&mut self.picker, event) {
+            if key_event.kind != KeyEventKind::Press {
+                return;
             }
+
+            let outcome = picker.handle_key(key_event, &self.store.borrow().campaign().registry);
+            match outcome {
+                PickerOutcome::Searching => {}
+                PickerOutcome::Cancelled => self.picker = None,
+                PickerOutcome::Picked(identifier) => {
+                    self.picker = None;
+                    self.transactions.push_back(Transaction::AddItem(identifier));
+                }
+            }
+
+            self.perform_transactions();
+            return;
         }
 
-        if let Some(ref mut page) = &mut self.overlay_page {
-            page.handle_and_transact(event);
-        } else if let Event::Key(key_event) = &event {
-            log::debug!("ShopPage handled event {:?}", key_event);
+        if let Event::Key(key_event) = &event {
+            if key_event.kind != KeyEventKind::Press {
+                return;
+            }
 
-            match key_event.code {
-                KeyCode::Up if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::ScrollUp)
-                }
-                KeyCode::Down if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::ScrollDown)
-                }
-                KeyCode::PageUp if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::PageUp)
-                }
-                KeyCode::PageDown if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::PageDown)
-                }
-                KeyCode::Right if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::ShiftFocusForward)
-                }
-                KeyCode::Left if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::ShiftFocusBackward)
-                }
-                KeyCode::Char('o') if key_event.kind == KeyEventKind::Press => {
-                    self.transactions.push_back(Transaction::CreateOffer)
-                }
-                _ => {}
-            };
+            if let Some(transaction) = self.keymap().action(key_event) {
+                log::debug!("ShopPage handled event {:?}", key_event);
+                self.transactions.push_back(transaction);
+            } else if let Some(ref mut page) = &mut self.overlay_page {
+                page.handle_and_transact(event);
+            }
         }
+
         self.perform_transactions();
     }
 }
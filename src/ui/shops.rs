@@ -1,88 +1,292 @@
-use std::{cell::RefCell, rc::Rc};
-
 use ratatui::{
-    crossterm::event::{Event, KeyCode, KeyEventKind}, layout::Rect, style::{palette::material::GREEN, Style, Stylize}, widgets::{Block, Row, Table, TableState}
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Row, Table, TableState},
 };
 
-use crate::data::shop::Shop;
+use crate::{
+    config::{settings::SharedSettings, theme},
+    data::shop::Shop,
+    state::campaign::{CampaignChange, CampaignCommand, ChangeCursor, SharedCampaign},
+};
 
 use crate::ui::page::RenderablePage;
 
-use super::{shop::ShopPage, translator::I18ner};
+use super::{
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    input::TextInput,
+    shop::ShopPage,
+    translator::{I18nPhrase, I18ner},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum ShopsAction {
+    Up,
+    Down,
+    Open,
+    LeaveShop,
+    New,
+    Rename,
+    Delete,
+    Confirm,
+    Cancel,
+}
 
-pub struct ShopsPage<'a> {
-    shops: Vec<&'a Shop<'a>>,
+enum ShopsPrompt {
+    Create(TextInput),
+    Rename(usize, TextInput),
+    ConfirmDelete(usize),
+}
+
+pub struct ShopsPage {
+    store: SharedCampaign,
+    /// Shops are known by their index, so inserting or removing one moves the others.
+    changes: ChangeCursor,
+    settings: SharedSettings,
     shop_table_state: TableState,
 
-    open_shop_page: Option<ShopPage<'a>>,
+    prompt: Option<ShopsPrompt>,
+    prompt_error: Option<String>,
+
+    open_shop_page: Option<ShopPage>,
 }
 
-impl<'a> ShopsPage<'a> {
-    pub fn new(shops: Vec<&'a Shop<'a>>) -> Self {
+impl ShopsPage {
+    pub fn new(store: SharedCampaign, settings: SharedSettings) -> Self {
+        let has_shops = !store.borrow().campaign().shops.is_empty();
+        let changes = store.borrow().subscribe();
+
         Self {
-            shop_table_state: TableState::default().with_selected(if !shops.is_empty() {
+            shop_table_state: TableState::default().with_selected(if has_shops {
                 Some(0)
             } else {
                 None
             }),
-            shops,
+            store,
+            changes,
+            settings,
+            prompt: None,
+            prompt_error: None,
             open_shop_page: None,
         }
     }
 
+    fn restock_summary(shop: &Shop, i18n: &dyn I18ner) -> String {
+        match shop.restock.return_after_days {
+            Some(days) => i18n.i18n_with(I18nPhrase::ReturnAfterDays, &[("days", &days.to_string())]),
+            None => "-".into(),
+        }
+    }
+
     fn draw_self(&mut self, frame: &mut ratatui::Frame, area: Rect, i18n: &dyn I18ner) {
+        let [table_area, prompt_area] = Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(area);
+
+        let store = self.store.borrow();
+        let palette = theme::palette();
+
         let table = Table::new(
-            self.shops.iter().map(|shop| {
-                let s = shop.name.clone();
-                Row::new(vec!["Generic".to_string(), s])
+            store.campaign().shops.iter().map(|shop| {
+                let inventory = shop.get_inventory();
+                let available = inventory.iter().filter(|stocked| stocked.stock.is_available()).count();
+
+                let row = Row::new(vec![
+                    shop.name.clone(),
+                    shop.short_name.clone().unwrap_or_default(),
+                    format!("{available}/{}", inventory.len()),
+                    Self::restock_summary(shop, i18n),
+                ]);
+
+                // Someone in the party is looking for an item this shop has in stock.
+                if store.campaign().stocks_wished_item(shop) {
+                    row.style(Style::new().fg(palette.highlight).bold())
+                } else {
+                    row
+                }
             }),
-            [1, 50],
+            [Constraint::Fill(2), Constraint::Fill(1), Constraint::Length(11), Constraint::Fill(1)],
         )
-        .header(Row::new(vec!["Category", "Name"]).style(Style::default().bg(GREEN.c600)))
-        .block(Block::bordered())
-        .style(Style::new().white())
+        .header(Row::new(
+            [I18nPhrase::Name, I18nPhrase::ShortName, I18nPhrase::Items, I18nPhrase::Restock].map(|phrase| i18n.i18n(phrase)),
+        ).style(Style::default().bg(palette.header)))
+        .block(Block::bordered().title(i18n.i18n(I18nPhrase::Shops)))
+        .style(Style::new().fg(palette.text))
         // .row_highlight_style(Style::new().white().on_green())
         .highlight_symbol(">> ")
         .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
 
-        frame.render_stateful_widget(table, area, &mut self.shop_table_state);
-    }
-
-    fn handle_shopspage_event(&mut self, event: &Event) {
-        match event {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                match key_event.code {
-                    KeyCode::Up => {
-                        self.shop_table_state.scroll_up_by(1);
-                    }
-                    KeyCode::Down => {
-                        self.shop_table_state.scroll_down_by(1);
-                    }
-                    KeyCode::Enter => {
-                        let opt_idx = self.shop_table_state.selected();
-
-                        if let Some(idx) = opt_idx {
-                            let shop = &self.shops[idx];
-                            self.open_shop_page = Some(ShopPage::new(shop));
-                        }
-                    }
-                    KeyCode::Esc => {
-                        self.open_shop_page = None;
-                    }
-                    _ => {}
+        frame.render_stateful_widget(table, table_area, &mut self.shop_table_state);
+
+        let title = |phrase| match &self.prompt_error {
+            Some(error) => Line::raw(error.clone()).fg(palette.error),
+            None => Line::raw(i18n.i18n(phrase)),
+        };
+
+        match &self.prompt {
+            Some(ShopsPrompt::Create(input)) => {
+                input.render(frame, prompt_area, Block::bordered().title(title(I18nPhrase::NewShopName)), true);
+            }
+            Some(ShopsPrompt::Rename(_, input)) => {
+                input.render(frame, prompt_area, Block::bordered().title(title(I18nPhrase::NewName)), true);
+            }
+            Some(ShopsPrompt::ConfirmDelete(idx)) => {
+                let name = store.campaign().shops.get(*idx).map_or("", |shop| shop.name.as_str());
+                frame.render_widget(
+                    Line::raw(i18n.i18n_with(I18nPhrase::CloseShopForGood, &[("name", name)])).bold().centered(),
+                    Block::bordered().inner(prompt_area),
+                );
+                frame.render_widget(Block::bordered(), prompt_area);
+            }
+            None => {}
+        }
+    }
+
+    fn apply(&mut self, command: CampaignCommand) -> bool {
+        match self.store.borrow_mut().apply(command) {
+            Ok(()) => true,
+            Err(e) => {
+                self.prompt_error = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    fn select_shop(&mut self, idx: usize) {
+        let count = self.store.borrow().campaign().shops.len();
+        self.shop_table_state.select(count.checked_sub(1).map(|last| idx.min(last)));
+    }
+
+    fn keymap(&self) -> KeyMap<ShopsAction> {
+        if let Some(page) = &self.open_shop_page {
+            return if page.is_showing_popup() {
+                KeyMap::new("shops")
+            } else {
+                KeyMap::new("shops").bind("leave-shop", KeyCode::Esc, I18nPhrase::BackToShops, ShopsAction::LeaveShop)
+            };
+        }
+
+        match &self.prompt {
+            Some(ShopsPrompt::Create(_) | ShopsPrompt::Rename(..)) => KeyMap::for_text_input("shops")
+                .bind("confirm", KeyCode::Enter, I18nPhrase::Confirm, ShopsAction::Confirm)
+                .bind("cancel", KeyCode::Esc, I18nPhrase::Cancel, ShopsAction::Cancel),
+            Some(ShopsPrompt::ConfirmDelete(_)) => KeyMap::new("shops")
+                .bind("confirm", KeyCode::Char('y'), I18nPhrase::CloseTheShop, ShopsAction::Confirm)
+                .bind("cancel", KeyCode::Char('n'), I18nPhrase::KeepIt, ShopsAction::Cancel)
+                .bind_hidden("cancel", KeyCode::Esc, I18nPhrase::KeepIt, ShopsAction::Cancel),
+            None => KeyMap::new("shops")
+                .bind("open", KeyCode::Enter, I18nPhrase::Visit, ShopsAction::Open)
+                .bind("new", KeyCode::Char('n'), I18nPhrase::NewShop, ShopsAction::New)
+                .bind("rename", KeyCode::Char('r'), I18nPhrase::Rename, ShopsAction::Rename)
+                .bind("delete", KeyCode::Char('d'), I18nPhrase::CloseShop, ShopsAction::Delete)
+                .bind_hidden("delete", KeyCode::Delete, I18nPhrase::CloseShop, ShopsAction::Delete)
+                .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousShop, ShopsAction::Up)
+                .bind_hidden("down", KeyCode::Down, I18nPhrase::NextShop, ShopsAction::Down),
+        }
+    }
+
+    fn confirm_prompt(&mut self) {
+        match self.prompt.take() {
+            Some(ShopsPrompt::Create(input)) => {
+                let name = input.value().trim().to_string();
+                let index = self.store.borrow().campaign().shops.len();
+
+                if self.apply(CampaignCommand::InsertShop { index, shop: Shop::new(name) }) {
+                    self.select_shop(index);
+                } else {
+                    self.prompt = Some(ShopsPrompt::Create(input));
                 }
             }
-            _ => {}
+            Some(ShopsPrompt::Rename(shop, input)) => {
+                let command = CampaignCommand::RenameShop { shop, name: input.value().trim().to_string() };
+
+                if !self.apply(command) {
+                    self.prompt = Some(ShopsPrompt::Rename(shop, input));
+                }
+            }
+            Some(ShopsPrompt::ConfirmDelete(shop)) => {
+                let closed = self.apply(CampaignCommand::RemoveShop { shop });
+                if closed {
+                    self.select_shop(shop);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn perform(&mut self, action: ShopsAction) {
+        let selected = self.shop_table_state.selected();
+
+        match action {
+            ShopsAction::Up => self.shop_table_state.scroll_up_by(1),
+            ShopsAction::Down => self.shop_table_state.scroll_down_by(1),
+            ShopsAction::Open => {
+                if let Some(idx) = selected {
+                    let visited_on = self.store.borrow().campaign().date;
+                    self.open_shop_page = Some(ShopPage::new(self.store.clone(), self.settings.clone(), idx, visited_on));
+                }
+            }
+            ShopsAction::LeaveShop => self.open_shop_page = None,
+            ShopsAction::New => self.prompt = Some(ShopsPrompt::Create(TextInput::default())),
+            ShopsAction::Rename => {
+                let name = selected.and_then(|idx| self.store.borrow().campaign().shops.get(idx).map(|shop| shop.name.clone()));
+                if let (Some(idx), Some(name)) = (selected, name) {
+                    self.prompt = Some(ShopsPrompt::Rename(idx, TextInput::new(&name)));
+                }
+            }
+            ShopsAction::Delete => {
+                if let Some(idx) = selected {
+                    self.prompt = Some(ShopsPrompt::ConfirmDelete(idx));
+                }
+            }
+            ShopsAction::Confirm => self.confirm_prompt(),
+            ShopsAction::Cancel => {
+                self.prompt = None;
+                self.prompt_error = None;
+            }
+        }
+    }
+
+    /// Closes the open shop and prompts about a shop once shops were inserted or removed in the
+    /// meantime, e.g. by an undo, as their index may point to another shop now.
+    fn close_moved_shops(&mut self) {
+        let store = self.store.borrow();
+        let count = store.campaign().shops.len();
+        let list_changed = store.changes_since(&mut self.changes).contains(&CampaignChange::ShopList);
+        drop(store);
+
+        if list_changed {
+            self.open_shop_page = None;
+            if matches!(self.prompt, Some(ShopsPrompt::Rename(..) | ShopsPrompt::ConfirmDelete(_))) {
+                self.prompt = None;
+                self.prompt_error = None;
+            }
+        }
+
+        if self.shop_table_state.selected().is_some_and(|idx| idx >= count) {
+            self.select_shop(usize::MAX);
         }
     }
 }
 
-impl<'a> RenderablePage for ShopsPage<'a> {
-    fn title(&self) -> String {
-        "Shops".into()
+impl HandlesKeyEvents for ShopsPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        let mut handlers = self.keymap().handlers();
+        if let Some(page) = &self.open_shop_page {
+            handlers.extend(page.get_handlers());
+        }
+        handlers
+    }
+}
+
+impl RenderablePage for ShopsPage {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        i18n.i18n(I18nPhrase::Shops)
     }
 
     fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn I18ner) {
+        self.close_moved_shops();
+
         if let Some(ref mut page) = self.open_shop_page {
             page.draw(frame, area, i18n);
         } else {
@@ -91,10 +295,72 @@ impl<'a> RenderablePage for ShopsPage<'a> {
     }
 
     fn handle_and_transact(&mut self, event: &Event) {
+        self.close_moved_shops();
+
+        if let Event::Key(key_event) = event {
+            if key_event.kind != KeyEventKind::Press {
+                return;
+            }
+
+            if let Some(action) = self.keymap().action(key_event) {
+                self.perform(action);
+                return;
+            }
+        }
+
         if let Some(ref mut page) = self.open_shop_page {
             page.handle_and_transact(event);
-        } else {
-            self.handle_shopspage_event(event);
+        } else if let (Some(ShopsPrompt::Create(input) | ShopsPrompt::Rename(_, input)), Event::Key(key_event)) =
+            (&mut self.prompt, event)
+        {
+            input.handle_key(key_event);
+            self.prompt_error = None;
         }
     }
+
+    fn captures_input(&self) -> bool {
+        match &self.open_shop_page {
+            Some(page) => page.captures_input(),
+            None => matches!(self.prompt, Some(ShopsPrompt::Create(_) | ShopsPrompt::Rename(..))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyEvent, KeyModifiers};
+
+    use crate::{
+        config::settings::{Settings, SettingsStore},
+        data::campaign::CampaignFolder,
+        state::campaign::CampaignStore,
+        ui::translator::Translator,
+    };
+
+    use super::*;
+
+    fn press(page: &mut ShopsPage, code: KeyCode) {
+        page.handle_and_transact(&Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn an_undo_that_moves_the_open_shop_closes_it() -> anyhow::Result<()> {
+        let mut folder = CampaignFolder::empty("Test".into());
+        folder.shops = ["Anna's", "Bert's", "Carla's"].map(|name| Shop::new(name.into())).to_vec();
+        let store = CampaignStore::shared(folder.into_campaign());
+        let mut page = ShopsPage::new(store.clone(), SettingsStore::in_memory(Settings::default()).into_shared());
+
+        press(&mut page, KeyCode::Char('d'));
+        press(&mut page, KeyCode::Char('y'));
+        press(&mut page, KeyCode::Down);
+        press(&mut page, KeyCode::Enter);
+        assert_eq!(page.open_shop_page.as_ref().map(|shop| shop.title(&Translator::new(&Settings::default()))), Some("Carla's".into()));
+
+        store.borrow_mut().undo()?;
+        page.handle_and_transact(&Event::FocusGained);
+
+        assert!(page.open_shop_page.is_none());
+        assert_eq!(store.borrow().campaign().shops[0].name, "Anna's");
+        Ok(())
+    }
 }
@@ -0,0 +1,185 @@
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Clear, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    apis::dnd5eapi::{Dnd5eApiMagicItemList, Dnd5eApiMagicItemListing, PerformsRequest},
+    config::theme,
+    registry::ItemRegistry,
+};
+
+use super::{
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    input::TextInput,
+    translator::{I18nPhrase, I18ner, Message},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum SrdPickerAction {
+    Toggle,
+    Import,
+    Cancel,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SrdPickerOutcome {
+    Picking,
+    /// Sub urls of the picked items.
+    Picked(Vec<String>),
+    Cancelled,
+}
+
+/// Popup to search the SRD magic items of the dnd5eapi and pick any number of them to import.
+pub struct SrdPicker {
+    list: Result<Dnd5eApiMagicItemList, Message>,
+    query: TextInput,
+    table_state: TableState,
+    /// Sub urls in the order they were picked.
+    picked: Vec<String>,
+}
+
+impl SrdPicker {
+    /// Loads the list of magic items, which comes from the cache once it was synced.
+    pub fn new(requester: &dyn PerformsRequest) -> Self {
+        let list = Dnd5eApiMagicItemList::download(requester).map_err(|e| {
+            log::error!("Could not load the SRD magic items: {e:?}");
+            Message::from(I18nPhrase::CouldNotLoadSrd).with("error", format!("{e:#}"))
+        });
+
+        Self {
+            list,
+            query: TextInput::default(),
+            table_state: TableState::default().with_selected(Some(0)),
+            picked: vec![],
+        }
+    }
+
+    fn keymap(&self) -> KeyMap<SrdPickerAction> {
+        KeyMap::for_text_input("srd-picker")
+            .bind("toggle", KeyCode::Tab, I18nPhrase::ToggleSelection, SrdPickerAction::Toggle)
+            .bind("import", KeyCode::Enter, I18nPhrase::ImportSelected, SrdPickerAction::Import)
+            .bind("cancel", KeyCode::Esc, I18nPhrase::Cancel, SrdPickerAction::Cancel)
+            .bind_hidden("up", KeyCode::Up, I18nPhrase::PreviousMatch, SrdPickerAction::Up)
+            .bind_hidden("down", KeyCode::Down, I18nPhrase::NextMatch, SrdPickerAction::Down)
+    }
+
+    fn highlighted(&self) -> Option<String> {
+        let idx = self.table_state.selected()?;
+        matches(&self.list, self.query.value()).get(idx).map(|listing| listing.url.clone())
+    }
+
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> SrdPickerOutcome {
+        match self.keymap().action(key_event) {
+            Some(SrdPickerAction::Cancel) => return SrdPickerOutcome::Cancelled,
+            Some(SrdPickerAction::Import) => {
+                if self.picked.is_empty() {
+                    self.picked.extend(self.highlighted());
+                }
+                if !self.picked.is_empty() {
+                    return SrdPickerOutcome::Picked(std::mem::take(&mut self.picked));
+                }
+            }
+            Some(SrdPickerAction::Toggle) => {
+                if let Some(url) = self.highlighted() {
+                    match self.picked.iter().position(|picked| *picked == url) {
+                        Some(idx) => {
+                            self.picked.remove(idx);
+                        }
+                        None => self.picked.push(url),
+                    }
+                    self.table_state.scroll_down_by(1);
+                }
+            }
+            Some(SrdPickerAction::Up) => self.table_state.scroll_up_by(1),
+            Some(SrdPickerAction::Down) => self.table_state.scroll_down_by(1),
+            None => {
+                if self.query.handle_key(key_event) {
+                    self.table_state.select(Some(0));
+                }
+            }
+        }
+
+        SrdPickerOutcome::Picking
+    }
+
+    /// Draws the picker as a popup centered on top of `area`. Items the registry has already are dimmed.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, registry: &ItemRegistry, i18n: &dyn I18ner) {
+        let [area] = Layout::vertical([Constraint::Percentage(80)]).flex(Flex::Center).areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center).areas(area);
+        frame.render_widget(Clear, area);
+
+        let palette = theme::palette();
+        let block = Block::bordered()
+            .title(i18n.i18n(I18nPhrase::SrdPickerTitle))
+            .title_bottom(i18n.i18n_with(I18nPhrase::SrdSelectedCount, &[("count", &self.picked.len().to_string())]));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [query_area, list_area] = Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(inner);
+
+        self.query.render(frame, query_area, Block::bordered().title(i18n.i18n(I18nPhrase::Search)), true);
+
+        if let Err(error) = &self.list {
+            frame.render_widget(Line::raw(i18n.message(error)).fg(palette.error), list_area);
+            return;
+        }
+
+        let matches = matches(&self.list, self.query.value());
+        if let Some(selected) = self.table_state.selected() {
+            if selected >= matches.len() {
+                self.table_state.select(matches.len().checked_sub(1));
+            }
+        }
+
+        let table = Table::new(
+            matches.iter().map(|listing| {
+                let marker = if self.picked.contains(&listing.url) { "[x]" } else { "[ ]" };
+                let row = Row::new(vec![
+                    Cell::from(marker),
+                    Cell::from(listing.name.clone()),
+                    Cell::from(listing.index.clone()),
+                ]);
+
+                if registry.get(&listing.index).is_some() {
+                    row.fg(palette.muted)
+                } else {
+                    row
+                }
+            }),
+            [Constraint::Length(3), Constraint::Fill(2), Constraint::Fill(1)],
+        )
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol(">> ")
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        if matches.is_empty() {
+            frame.render_widget(Line::raw(i18n.i18n(I18nPhrase::NoMatchingItems)).italic(), list_area);
+        } else {
+            frame.render_stateful_widget(table, list_area, &mut self.table_state);
+        }
+    }
+}
+
+/// The query is a case-insensitive regular expression, or plain text while it is none.
+fn matches<'l>(list: &'l Result<Dnd5eApiMagicItemList, Message>, query: &str) -> Vec<&'l Dnd5eApiMagicItemListing> {
+    let Ok(list) = list else {
+        return vec![];
+    };
+
+    list.search_all(&format!("(?i){query}"))
+        .or_else(|_| list.search_all(&format!("(?i){}", regex::escape(query))))
+        .unwrap_or_default()
+}
+
+impl HandlesKeyEvents for SrdPicker {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
+    }
+}
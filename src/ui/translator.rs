@@ -1,20 +1,91 @@
-pub enum I18nPhrase {
-    Roll,
-    Price
-}
+use crate::{
+    config::settings::{Language, Settings},
+    i18n::{fill, translate},
+};
+
+pub use crate::i18n::I18nPhrase;
 
 pub trait I18ner {
     fn i18n(&self, phrase: I18nPhrase) -> String;
+
+    /// The language shown, for content like item names that brings its own translations.
+    fn language(&self) -> Language;
+
+    /// The phrase with its `{name}` placeholders filled in.
+    fn i18n_with(&self, phrase: I18nPhrase, args: &[(&str, &str)]) -> String {
+        fill(&self.i18n(phrase), args)
+    }
+
+    fn message(&self, message: &Message) -> String {
+        match message {
+            Message::Phrase(phrase, args) => {
+                let args: Vec<(&str, &str)> = args.iter().map(|(name, value)| (*name, value.as_str())).collect();
+                self.i18n_with(*phrase, &args)
+            }
+            Message::Verbatim(text) => text.clone(),
+        }
+    }
 }
 
+/// Text that is kept until it is drawn, when a translator is at hand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Phrase(I18nPhrase, Vec<(&'static str, String)>),
+    /// Text that is not translated, like an error of the campaign store.
+    Verbatim(String),
+}
 
-pub struct EngNerdI18n {}
+impl Message {
+    pub fn with(self, name: &'static str, value: impl ToString) -> Self {
+        match self {
+            Self::Phrase(phrase, mut args) => {
+                args.push((name, value.to_string()));
+                Self::Phrase(phrase, args)
+            }
+            verbatim => verbatim,
+        }
+    }
+}
 
-impl I18ner for EngNerdI18n {
-    fn i18n(&self, phrase: I18nPhrase) -> String {
+impl From<I18nPhrase> for Message {
+    fn from(phrase: I18nPhrase) -> Self {
+        Self::Phrase(phrase, vec![])
+    }
+}
+
+/// Phrases in the language of the settings. Labels that have a Nerd Font icon show the icon
+/// instead if the user has the font.
+pub struct Translator {
+    language: Language,
+    nerd_font: bool,
+}
+
+impl Translator {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            language: settings.language.resolve(),
+            nerd_font: settings.nerd_font,
+        }
+    }
+
+    fn glyph(phrase: I18nPhrase) -> Option<&'static str> {
         match phrase {
-            I18nPhrase::Price => "\u{ede8}".into(),
-            I18nPhrase::Roll => "\u{e270}".into(),
+            I18nPhrase::Price => Some("\u{ede8}"),
+            I18nPhrase::Roll => Some("\u{e270}"),
+            _ => None,
+        }
+    }
+}
+
+impl I18ner for Translator {
+    fn i18n(&self, phrase: I18nPhrase) -> String {
+        match Self::glyph(phrase) {
+            Some(glyph) if self.nerd_font => glyph.into(),
+            _ => translate(self.language, phrase).into(),
         }
     }
-}
\ No newline at end of file
+
+    fn language(&self) -> Language {
+        self.language
+    }
+}
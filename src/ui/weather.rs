@@ -0,0 +1,194 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Row, Table},
+};
+
+use crate::{
+    data::weather::{Precipitation, Season, Weather, Wind},
+    state::campaign::SharedCampaign,
+};
+
+use super::{
+    flow::{HandlesKeyEvents, KeyHandler, KeyMap},
+    page::RenderablePage,
+    translator::{I18nPhrase, I18ner},
+};
+
+const MIN_FORECAST_DAYS: u32 = 1;
+const MAX_FORECAST_DAYS: u32 = 14;
+
+pub struct WeatherPage {
+    store: SharedCampaign,
+
+    forecast_days: u32,
+}
+
+impl WeatherPage {
+    pub fn new(store: SharedCampaign) -> Self {
+        Self {
+            store,
+            forecast_days: 7,
+        }
+    }
+
+    fn keymap(&self) -> KeyMap<i32> {
+        KeyMap::new("weather")
+            .bind("longer", KeyCode::Char('+'), I18nPhrase::LongerForecast, 1)
+            .bind("shorter", KeyCode::Char('-'), I18nPhrase::ShorterForecast, -1)
+    }
+
+    fn weather_lines(weather: &Weather, i18n: &dyn I18ner) -> Vec<Line<'static>> {
+        let label = |phrase| Span::raw(format!("{}: ", i18n.i18n(phrase))).bold();
+
+        let mut lines = vec![
+            Line::from(vec![
+                label(I18nPhrase::Season),
+                Span::raw(i18n.i18n(season_name(weather.season))),
+            ]),
+            Line::from(vec![
+                label(I18nPhrase::Temperature),
+                Span::raw(format!("{} °C", weather.temperature)),
+            ]),
+            Line::from(vec![
+                label(I18nPhrase::Precipitation),
+                Span::raw(i18n.i18n(precipitation_name(weather.precipitation))),
+            ]),
+            Line::from(vec![
+                label(I18nPhrase::Wind),
+                Span::raw(i18n.i18n(wind_name(weather.wind))),
+            ]),
+        ];
+
+        if !weather.events.is_empty() {
+            lines.push(Line::from(vec![
+                label(I18nPhrase::Special),
+                Span::raw(weather.events.join(", ")).italic(),
+            ]));
+        }
+
+        lines
+    }
+}
+
+fn season_name(season: Season) -> I18nPhrase {
+    match season {
+        Season::Spring => I18nPhrase::SpringSeason,
+        Season::Summer => I18nPhrase::SummerSeason,
+        Season::Autumn => I18nPhrase::AutumnSeason,
+        Season::Winter => I18nPhrase::WinterSeason,
+    }
+}
+
+fn precipitation_name(precipitation: Precipitation) -> I18nPhrase {
+    match precipitation {
+        Precipitation::None => I18nPhrase::DryWeather,
+        Precipitation::LightRain => I18nPhrase::LightRain,
+        Precipitation::HeavyRain => I18nPhrase::HeavyRain,
+        Precipitation::LightSnow => I18nPhrase::LightSnow,
+        Precipitation::HeavySnow => I18nPhrase::HeavySnow,
+    }
+}
+
+fn wind_name(wind: Wind) -> I18nPhrase {
+    match wind {
+        Wind::Calm => I18nPhrase::CalmWind,
+        Wind::Breeze => I18nPhrase::Breeze,
+        Wind::Strong => I18nPhrase::StrongWind,
+        Wind::Gale => I18nPhrase::Gale,
+    }
+}
+
+impl HandlesKeyEvents for WeatherPage {
+    fn get_handlers(&self) -> Vec<KeyHandler> {
+        self.keymap().handlers()
+    }
+}
+
+impl RenderablePage for WeatherPage {
+    fn title(&self, i18n: &dyn I18ner) -> String {
+        i18n.i18n(I18nPhrase::Weather)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, i18n: &dyn I18ner) {
+        let [today_area, forecast_area] = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([Constraint::Length(7), Constraint::Fill(1)])
+            .areas(area);
+
+        let store = self.store.borrow();
+        let campaign = store.campaign();
+        let calendar = &campaign.calendar;
+
+        let forecast = campaign.climate.forecast(calendar, &campaign.date, self.forecast_days + 1);
+
+        if let Some((today, weather)) = forecast.first() {
+            let today_block = Block::bordered().title(format!(
+                "{} – {}",
+                campaign.climate.region,
+                calendar.format(today)
+            ));
+            frame.render_widget(
+                Paragraph::new(Self::weather_lines(weather, i18n)).block(today_block),
+                today_area,
+            );
+        }
+
+        let rows = forecast.iter().skip(1).map(|(day, weather)| {
+            Row::new(vec![
+                calendar.format(day),
+                format!("{} °C", weather.temperature),
+                i18n.i18n(precipitation_name(weather.precipitation)),
+                i18n.i18n(wind_name(weather.wind)),
+                weather.events.join(", "),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(28),
+                Constraint::Length(8),
+                Constraint::Length(16),
+                Constraint::Length(16),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(
+                [
+                    I18nPhrase::Day,
+                    I18nPhrase::TemperatureShort,
+                    I18nPhrase::PrecipitationShort,
+                    I18nPhrase::Wind,
+                    I18nPhrase::Special,
+                ]
+                .map(|phrase| i18n.i18n(phrase)),
+            )
+            .bold(),
+        )
+        .block(Block::bordered().title(i18n.i18n_with(
+            I18nPhrase::ForecastTitle,
+            &[("days", &self.forecast_days.to_string())],
+        )));
+
+        frame.render_widget(table, forecast_area);
+    }
+
+    fn handle_and_transact(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if key_event.kind != KeyEventKind::Press {
+                return;
+            }
+
+            if let Some(days) = self.keymap().action(key_event) {
+                self.forecast_days = self
+                    .forecast_days
+                    .saturating_add_signed(days)
+                    .clamp(MIN_FORECAST_DAYS, MAX_FORECAST_DAYS);
+            }
+        }
+    }
+}